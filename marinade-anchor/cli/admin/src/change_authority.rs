@@ -2,11 +2,16 @@ use crate::Common;
 
 use anyhow::Result;
 use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::instruction::Instruction;
 use cli_common::{
-    change_authority, instruction_helpers::InstructionHelpers,
-    marinade_finance::ChangeAuthorityData, rpc_client_helpers::RpcClientHelpers,
-    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, ExpandedPath, InputKeypair,
-    InputPubkey,
+    accept_authority, change_authority,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{ChangeAuthorityData, ProposeAuthorityData},
+    propose_authority,
+    rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder,
+    ExpandedPath, InputKeypair, InputPubkey,
 };
 use log::info;
 
@@ -16,6 +21,60 @@ use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
 pub struct ChangeAuthorityOptions {
+    // admin, validator manager and operational sol account use propose-authority
+    #[structopt(long)]
+    new_treasury_msol_account: Option<InputPubkey>,
+
+    #[structopt(env = "MARINADE_ADMIN")]
+    current_admin_authority: Option<InputKeypair>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl ChangeAuthorityOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        info!("change authority");
+
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+
+        //check there's at least one parameter set
+        if self.new_treasury_msol_account.is_none() {
+            return Err(anyhow::anyhow!("no parameters set"));
+        }
+
+        let data = ChangeAuthorityData {
+            treasury_msol_account: self
+                .new_treasury_msol_account
+                .as_ref()
+                .map(InputPubkey::as_pubkey),
+            ..Default::default()
+        };
+
+        if let Some(propose_output) = self.propose_output {
+            write_multisig_instruction(change_authority(&marinade.state, data), &propose_output)?;
+        } else {
+            // Run transaction
+            let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+            let admin_authority = if let Some(admin_authority) = &self.current_admin_authority {
+                info!("Using current admin authority {}", admin_authority);
+                admin_authority.as_keypair()
+            } else {
+                info!("Using fee payer as current admin authority");
+                common.fee_payer.as_keypair()
+            };
+
+            builder.change_authority(&marinade.state, admin_authority, data)?;
+
+            marinade.client.execute_transaction(builder.build_one())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ProposeAuthorityOptions {
     #[structopt(long)]
     new_admin: Option<InputPubkey>,
 
@@ -25,9 +84,6 @@ pub struct ChangeAuthorityOptions {
     #[structopt(long)]
     new_operational_sol_account: Option<InputPubkey>,
 
-    #[structopt(long)]
-    new_treasury_msol_account: Option<InputPubkey>,
-
     #[structopt(env = "MARINADE_ADMIN")]
     current_admin_authority: Option<InputKeypair>,
 
@@ -35,9 +91,9 @@ pub struct ChangeAuthorityOptions {
     propose_output: Option<ExpandedPath>,
 }
 
-impl ChangeAuthorityOptions {
+impl ProposeAuthorityOptions {
     pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
-        info!("change authority");
+        info!("propose authority");
 
         let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
 
@@ -45,12 +101,11 @@ impl ChangeAuthorityOptions {
         if self.new_admin.is_none()
             && self.new_validator_manager.is_none()
             && self.new_operational_sol_account.is_none()
-            && self.new_treasury_msol_account.is_none()
         {
             return Err(anyhow::anyhow!("no parameters set"));
         }
 
-        let data = ChangeAuthorityData {
+        let data = ProposeAuthorityData {
             admin: self.new_admin.as_ref().map(InputPubkey::as_pubkey),
             validator_manager: self
                 .new_validator_manager
@@ -60,40 +115,11 @@ impl ChangeAuthorityOptions {
                 .new_operational_sol_account
                 .as_ref()
                 .map(InputPubkey::as_pubkey),
-            treasury_msol_account: self
-                .new_treasury_msol_account
-                .as_ref()
-                .map(InputPubkey::as_pubkey),
         };
 
         if let Some(propose_output) = self.propose_output {
-            // Print transaction to stdout in multisig format
-            use ::borsh::BorshSerialize;
-            use multisig::{TransactionAccount, TransactionInstruction};
-
-            let instruction = change_authority(&marinade.state, data);
-
-            info!(
-                "instruction-data: {}",
-                base64::encode(instruction.data.clone())
-            );
-
-            let transaction = TransactionInstruction {
-                program_id: cli_common::marinade_finance::ID,
-                accounts: instruction
-                    .accounts
-                    .iter()
-                    .map(TransactionAccount::from)
-                    .collect(),
-                data: instruction.data,
-            };
-
-            if propose_output.to_str().unwrap() != "data" {
-                File::create(propose_output.as_path())?.write_all(&transaction.try_to_vec()?)?;
-                info!("tx saved in {}", propose_output);
-            }
+            write_multisig_instruction(propose_authority(&marinade.state, data), &propose_output)?;
         } else {
-            // Run transaction
             let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
 
             let admin_authority = if let Some(admin_authority) = &self.current_admin_authority {
@@ -104,10 +130,88 @@ impl ChangeAuthorityOptions {
                 common.fee_payer.as_keypair()
             };
 
-            builder.change_authority(&marinade.state, admin_authority, data)?;
+            builder.propose_authority(&marinade.state, admin_authority, data)?;
+
+            marinade.client.execute_transaction(builder.build_one())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct AcceptAuthorityOptions {
+    /// Proposed authority. Must be a keypair unless -p is used
+    #[structopt(long)]
+    new_authority: Option<InputPubkey>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl AcceptAuthorityOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        info!("accept authority");
+
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+
+        if let Some(propose_output) = self.propose_output {
+            let new_authority = self
+                .new_authority
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("--new-authority is required with -p"))?
+                .as_pubkey();
+            write_multisig_instruction(
+                accept_authority(&marinade.state, new_authority),
+                &propose_output,
+            )?;
+        } else {
+            let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+            let new_authority = if let Some(new_authority) = &self.new_authority {
+                let keypair = new_authority.try_as_keypair().ok_or_else(|| {
+                    anyhow::anyhow!("New authority {} must be a keypair", new_authority)
+                })?;
+                info!("Using new authority {}", new_authority);
+                keypair
+            } else {
+                info!("Using fee payer as new authority");
+                common.fee_payer.as_keypair()
+            };
+
+            builder.accept_authority(&marinade.state, new_authority);
 
             marinade.client.execute_transaction(builder.build_one())?;
         }
         Ok(())
     }
 }
+
+/// Print instruction data and save it in multisig format
+pub fn write_multisig_instruction(
+    instruction: Instruction,
+    propose_output: &ExpandedPath,
+) -> Result<()> {
+    use ::borsh::BorshSerialize;
+    use multisig::{TransactionAccount, TransactionInstruction};
+
+    info!(
+        "instruction-data: {}",
+        base64::encode(instruction.data.clone())
+    );
+
+    let transaction = TransactionInstruction {
+        program_id: cli_common::marinade_finance::ID,
+        accounts: instruction
+            .accounts
+            .iter()
+            .map(TransactionAccount::from)
+            .collect(),
+        data: instruction.data,
+    };
+
+    if propose_output.to_str().unwrap() != "data" {
+        File::create(propose_output.as_path())?.write_all(&transaction.try_to_vec()?)?;
+        info!("tx saved in {}", propose_output);
+    }
+    Ok(())
+}
@@ -1,7 +1,7 @@
-use crate::Common;
+use crate::{params_change::queue_change, Common};
 
 use anyhow::Result;
-use cli_common::marinade_finance::{ConfigMarinadeParams, Fee};
+use cli_common::marinade_finance::{queued_change::ParamsChange, ConfigMarinadeParams, Fee, State};
 use cli_common::solana_client::rpc_client::RpcClient;
 use cli_common::{
     config_marinade, instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
@@ -12,9 +12,34 @@ use log::info;
 use cli_common::solana_sdk::native_token::sol_to_lamports;
 
 use std::sync::Arc;
-use std::{fs::File, io::Write};
+use std::{fs::File, io::Write, str::FromStr};
 use structopt::StructOpt;
 
+#[derive(Debug)]
+pub struct PausedOperations(u32);
+
+impl FromStr for PausedOperations {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self(0)),
+            "all" => Ok(Self(State::PAUSE_ALL)),
+            _ => s
+                .split(',')
+                .map(|name| {
+                    State::PAUSE_OPERATIONS
+                        .iter()
+                        .find(|(_, operation)| *operation == name.trim())
+                        .map(|(flag, _)| *flag)
+                        .ok_or_else(|| anyhow::anyhow!("Unknown operation {}", name))
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(|flags| Self(flags.into_iter().fold(0, |mask, flag| mask | flag))),
+        }
+    }
+}
+
 #[derive(Debug, StructOpt)]
 pub struct ConfigMarinadeOptions {
     #[structopt(short = "f")]
@@ -44,6 +69,36 @@ pub struct ConfigMarinadeOptions {
     #[structopt(long)]
     liquidity_sol_cap: Option<f64>, //in SOL
 
+    #[structopt(
+        long,
+        help = "Comma separated operations to pause: deposit,deposit-stake-account,liquid-unstake,order-unstake,add-liquidity,stake-reserve,withdraw-stake-account. Or all/none"
+    )]
+    paused_operations: Option<PausedOperations>,
+
+    #[structopt(long)]
+    params_change_delay_epochs: Option<u64>,
+
+    #[structopt(long)]
+    withdraw_stake_account_fee: Option<Fee>,
+
+    #[structopt(
+        long,
+        help = "max validator commission percent for stake-reserve and deposit-stake-account (0 = no cap)"
+    )]
+    max_commission: Option<u8>,
+
+    #[structopt(
+        long,
+        help = "max SOL leaving the liquidity pool per epoch through liquid-unstake and instant-claim (0 = unlimited)"
+    )]
+    liquidity_epoch_outflow_cap: Option<f64>, // in SOL
+
+    #[structopt(
+        long,
+        help = "queue the change in this new account instead of applying it now (required when timelocked)"
+    )]
+    queue: Option<InputKeypair>,
+
     #[structopt(env = "MARINADE_ADMIN")]
     admin_authority: Option<InputKeypair>,
 
@@ -64,6 +119,11 @@ impl ConfigMarinadeOptions {
             && self.staking_sol_cap.is_none()
             && self.liquidity_sol_cap.is_none()
             && self.auto_add_validator_enabled.is_none()
+            && self.paused_operations.is_none()
+            && self.params_change_delay_epochs.is_none()
+            && self.withdraw_stake_account_fee.is_none()
+            && self.max_commission.is_none()
+            && self.liquidity_epoch_outflow_cap.is_none()
             && !self.remove_cap
         {
             return Err(anyhow::anyhow!("no parameters set"));
@@ -84,6 +144,13 @@ impl ConfigMarinadeOptions {
         } else {
             None
         };
+        let liquidity_epoch_outflow_cap = if self.liquidity_epoch_outflow_cap.is_some() {
+            Some(sol_to_lamports(self.liquidity_epoch_outflow_cap.unwrap()))
+        } else if self.remove_cap {
+            Some(std::u64::MAX)
+        } else {
+            None
+        };
 
         let params = ConfigMarinadeParams {
             rewards_fee: self.rewards_fee,
@@ -94,9 +161,28 @@ impl ConfigMarinadeOptions {
             staking_sol_cap,
             liquidity_sol_cap,
             auto_add_validator_enabled: self.auto_add_validator_enabled,
+            paused_operations: self.paused_operations.map(|paused| paused.0),
+            params_change_delay_epochs: self.params_change_delay_epochs,
+            withdraw_stake_account_fee: self.withdraw_stake_account_fee,
+            max_commission: self.max_commission,
+            liquidity_epoch_outflow_cap,
         };
         info!("{:?}", params);
 
+        if let Some(queued_change) = &self.queue {
+            if self.paused_operations.is_some() {
+                return Err(anyhow::anyhow!("paused operations can not be queued"));
+            }
+            return queue_change(
+                &common,
+                &marinade,
+                queued_change,
+                self.admin_authority.as_ref(),
+                self.propose_output.as_ref(),
+                ParamsChange::ConfigMarinade(params),
+            );
+        }
+
         if let Some(propose_output) = self.propose_output {
             // Print transaction to stdout in multisig format
             use ::borsh::BorshSerialize;
@@ -0,0 +1,162 @@
+use crate::{change_authority::write_multisig_instruction, Common};
+
+use anyhow::{bail, Result};
+use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::{rent::Rent, sysvar::rent};
+use cli_common::{
+    grow_list,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{list::List, stake_system::StakeRecord, validator_system::ValidatorRecord},
+    rpc_client_helpers::RpcClientHelpers, rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder, ExpandedPath, InputKeypair, InputPubkey,
+};
+use log::info;
+
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct GrowListOptions {
+    #[structopt(name = "list", possible_values = &["stake", "validator"])]
+    list: String,
+
+    #[structopt(name = "capacity", help = "new list capacity in records")]
+    capacity: u32,
+
+    #[structopt(long = "new-list", help = "new list account keypair")]
+    new_list: InputKeypair,
+
+    #[structopt(
+        long = "chunk",
+        default_value = "1000",
+        help = "records copied by one instruction"
+    )]
+    chunk: u32,
+
+    #[structopt(
+        long = "rent-receiver",
+        help = "gets the old list rent (fee payer by default)"
+    )]
+    rent_receiver: Option<InputPubkey>,
+
+    #[structopt(env = "MARINADE_ADMIN")]
+    admin_authority: Option<InputKeypair>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl GrowListOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+        let (list, record_size, legacy_discriminator, new_legacy_record_size, count) =
+            if self.list == "stake" {
+                let stake_system = &marinade.state.stake_system;
+                (
+                    *stake_system.stake_list_address(),
+                    stake_system.stake_record_size(),
+                    StakeRecord::LEGACY_DISCRIMINATOR,
+                    std::mem::size_of::<StakeRecord>() as u32,
+                    stake_system.stake_count(),
+                )
+            } else {
+                let validator_system = &marinade.state.validator_system;
+                (
+                    *validator_system.validator_list_address(),
+                    validator_system.validator_record_size(),
+                    ValidatorRecord::LEGACY_DISCRIMINATOR,
+                    std::mem::size_of::<ValidatorRecord>() as u32,
+                    validator_system.validator_count(),
+                )
+            };
+        let list_data = marinade.client.get_account_data(&list)?;
+        let current_capacity = List::capacity_of(record_size, list_data.len());
+        // legacy lists are migrated to the current record layout while moving
+        let new_record_size = if list_data[0..8] == legacy_discriminator[..] {
+            info!(
+                "Migrate {} list from the legacy {} bytes records to {} bytes",
+                self.list, record_size, new_legacy_record_size
+            );
+            new_legacy_record_size
+        } else {
+            record_size
+        };
+        if self.capacity <= current_capacity {
+            bail!(
+                "New capacity {} must be more than the current {}",
+                self.capacity,
+                current_capacity
+            );
+        }
+        if self.chunk == 0 {
+            bail!("Chunk must be positive");
+        }
+        let rent_receiver = self
+            .rent_receiver
+            .as_ref()
+            .map_or(common.fee_payer.as_pubkey(), |rent_receiver| {
+                rent_receiver.as_pubkey()
+            });
+        info!(
+            "Move {} list {} with {} records into {} with capacity {}",
+            self.list, list, count, self.new_list, self.capacity
+        );
+
+        let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+        let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+        builder.create_account(
+            self.new_list.as_keypair(),
+            List::bytes_for(new_record_size, self.capacity) as usize,
+            &cli_common::marinade_finance::ID,
+            &rent,
+            "new list",
+        )?;
+        // the list is locked until all the records are copied
+        let calls = ((count + self.chunk - 1) / self.chunk).max(1);
+
+        if let Some(propose_output) = self.propose_output {
+            if calls > 1 {
+                bail!(
+                    "{} records do not fit a single proposal. Use a bigger chunk",
+                    count
+                );
+            }
+            // create the account now, the multisig will move the list
+            marinade.client.execute_transaction(builder.build_one())?;
+            write_multisig_instruction(
+                grow_list(
+                    &marinade.state,
+                    list,
+                    self.new_list.as_pubkey(),
+                    rent_receiver,
+                    self.chunk,
+                ),
+                &propose_output,
+            )?;
+        } else {
+            let admin_authority = if let Some(admin_authority) = &self.admin_authority {
+                info!("Using admin authority {}", admin_authority);
+                admin_authority.as_keypair()
+            } else {
+                info!("Using fee payer as admin authority");
+                common.fee_payer.as_keypair()
+            };
+
+            for _ in 0..calls {
+                builder.grow_list(
+                    &marinade.state,
+                    admin_authority.clone(),
+                    list,
+                    self.new_list.as_pubkey(),
+                    rent_receiver,
+                    self.chunk,
+                )?;
+            }
+
+            marinade
+                .client
+                .execute_transaction_sequence(builder.combined_sequence())?;
+        }
+        Ok(())
+    }
+}
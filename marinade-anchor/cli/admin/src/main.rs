@@ -15,11 +15,17 @@ use structopt::StructOpt;
 
 pub mod change_authority;
 pub mod config_marinade;
+pub mod grow_list;
+pub mod params_change;
+pub mod referral;
 pub mod set_lp_params;
 pub mod transfer_spl_tokens;
 
-use change_authority::ChangeAuthorityOptions;
+use change_authority::{AcceptAuthorityOptions, ChangeAuthorityOptions, ProposeAuthorityOptions};
 use config_marinade::ConfigMarinadeOptions;
+use grow_list::GrowListOptions;
+use params_change::{CancelParamsChangeOptions, ExecuteParamsChangeOptions};
+use referral::{CreateReferralOptions, UpdateReferralOptions};
 use set_lp_params::SetLpParamsOptions;
 use transfer_spl_tokens::TransferSplTokenOptions;
 
@@ -59,8 +65,15 @@ struct Params {
 enum MardminCommand {
     SetLpParams(SetLpParamsOptions),
     ConfigMarinade(ConfigMarinadeOptions),
+    ExecuteParamsChange(ExecuteParamsChangeOptions),
+    CancelParamsChange(CancelParamsChangeOptions),
     ChangeAuthority(ChangeAuthorityOptions),
+    ProposeAuthority(ProposeAuthorityOptions),
+    AcceptAuthority(AcceptAuthorityOptions),
     TransferSplToken(TransferSplTokenOptions),
+    CreateReferral(CreateReferralOptions),
+    UpdateReferral(UpdateReferralOptions),
+    GrowList(GrowListOptions),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -109,7 +122,14 @@ fn main() -> anyhow::Result<()> {
     Ok(match params.command {
         MardminCommand::SetLpParams(options) => options.process(params.common, client),
         MardminCommand::ConfigMarinade(options) => options.process(params.common, client),
+        MardminCommand::ExecuteParamsChange(options) => options.process(params.common, client),
+        MardminCommand::CancelParamsChange(options) => options.process(params.common, client),
         MardminCommand::ChangeAuthority(options) => options.process(params.common, client),
+        MardminCommand::ProposeAuthority(options) => options.process(params.common, client),
+        MardminCommand::AcceptAuthority(options) => options.process(params.common, client),
         MardminCommand::TransferSplToken(options) => options.process(params.common, client),
+        MardminCommand::CreateReferral(options) => options.process(params.common, client),
+        MardminCommand::UpdateReferral(options) => options.process(params.common, client),
+        MardminCommand::GrowList(options) => options.process(params.common, client),
     }?)
 }
@@ -0,0 +1,160 @@
+use crate::{change_authority::write_multisig_instruction, Common};
+
+use anyhow::Result;
+use cli_common::anchor_lang::AccountDeserialize;
+use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::{pubkey::Pubkey, rent::Rent, sysvar::rent};
+use cli_common::{
+    cancel_params_change,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::queued_change::{ParamsChange, QueuedChangeData},
+    queue_params_change,
+    rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder,
+    ExpandedPath, InputKeypair,
+};
+use log::info;
+
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// Creates the queued-change account (fee payer gets the rent back) and queues the change
+pub fn queue_change(
+    common: &Common,
+    marinade: &RpcMarinade,
+    queued_change: &InputKeypair,
+    admin_authority: Option<&InputKeypair>,
+    propose_output: Option<&ExpandedPath>,
+    change: ParamsChange,
+) -> Result<()> {
+    info!(
+        "Queue change {} executable in {} epochs",
+        queued_change, marinade.state.params_change_delay_epochs
+    );
+    let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+
+    let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+    builder.create_account(
+        queued_change.as_keypair(),
+        QueuedChangeData::SPACE,
+        &cli_common::marinade_finance::ID,
+        &rent,
+        "queued-change",
+    )?;
+
+    if let Some(propose_output) = propose_output {
+        // create the account now, the multisig will queue the change
+        marinade.client.execute_transaction(builder.build_one())?;
+        write_multisig_instruction(
+            queue_params_change(
+                &marinade.state,
+                queued_change.as_pubkey(),
+                common.fee_payer.as_pubkey(),
+                change,
+            ),
+            propose_output,
+        )?;
+    } else {
+        let admin_authority = if let Some(admin_authority) = admin_authority {
+            info!("Using admin authority {}", admin_authority);
+            admin_authority.as_keypair()
+        } else {
+            info!("Using fee payer as admin authority");
+            common.fee_payer.as_keypair()
+        };
+
+        builder.queue_params_change(
+            &marinade.state,
+            admin_authority,
+            queued_change.as_pubkey(),
+            change,
+        )?;
+
+        marinade.client.execute_transaction(builder.build_one())?;
+    }
+    Ok(())
+}
+
+fn read_queued_change(marinade: &RpcMarinade, address: &Pubkey) -> Result<QueuedChangeData> {
+    let data = marinade.client.get_account_data_retrying(address)?;
+    let queued_change: QueuedChangeData =
+        AccountDeserialize::try_deserialize(&mut data.as_slice())?;
+    info!(
+        "Queued change {} from epoch {}: {:?}",
+        address, queued_change.earliest_execution_epoch, queued_change.change
+    );
+    Ok(queued_change)
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ExecuteParamsChangeOptions {
+    #[structopt(name = "queued-change")]
+    queued_change: Pubkey,
+}
+
+impl ExecuteParamsChangeOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+        let queued_change = read_queued_change(&marinade, &self.queued_change)?;
+
+        let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+        builder.execute_params_change(
+            &marinade.state,
+            self.queued_change,
+            queued_change.rent_payer,
+        );
+        marinade.client.execute_transaction(builder.build_one())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct CancelParamsChangeOptions {
+    #[structopt(name = "queued-change")]
+    queued_change: Pubkey,
+
+    #[structopt(env = "MARINADE_ADMIN")]
+    admin_authority: Option<InputKeypair>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl CancelParamsChangeOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+        let queued_change = read_queued_change(&marinade, &self.queued_change)?;
+
+        if let Some(propose_output) = self.propose_output {
+            write_multisig_instruction(
+                cancel_params_change(
+                    &marinade.state,
+                    self.queued_change,
+                    queued_change.rent_payer,
+                ),
+                &propose_output,
+            )?;
+        } else {
+            let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+            let admin_authority = if let Some(admin_authority) = &self.admin_authority {
+                info!("Using admin authority {}", admin_authority);
+                admin_authority.as_keypair()
+            } else {
+                info!("Using fee payer as admin authority");
+                common.fee_payer.as_keypair()
+            };
+
+            builder.cancel_params_change(
+                &marinade.state,
+                admin_authority,
+                self.queued_change,
+                queued_change.rent_payer,
+            )?;
+
+            marinade.client.execute_transaction(builder.build_one())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{change_authority::write_multisig_instruction, Common};
+
+use anyhow::{bail, Result};
+use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::{rent::Rent, sysvar::rent};
+use cli_common::{
+    create_referral,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{referral::ReferralStateData, Fee},
+    rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder,
+    transaction_helpers::TransactionBuilderHelpers,
+    update_referral, ExpandedPath, InputKeypair, InputPubkey,
+};
+use log::info;
+
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct CreateReferralOptions {
+    #[structopt(name = "referral-code")]
+    referral_code: String,
+
+    #[structopt(name = "partner")]
+    partner: InputPubkey,
+
+    #[structopt(
+        long = "share",
+        help = "% of the liquid unstake treasury cut paid to the partner"
+    )]
+    treasury_cut_share: Fee,
+
+    #[structopt(
+        long = "msol-account",
+        help = "partner mSOL account for the fee share (partner associated token account by default)"
+    )]
+    msol_account: Option<InputPubkey>,
+
+    #[structopt(long = "referral-state", help = "new referral account keypair")]
+    referral_state: InputKeypair,
+
+    #[structopt(env = "MARINADE_ADMIN")]
+    admin_authority: Option<InputKeypair>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl CreateReferralOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+        if ReferralStateData::referral_code(&self.referral_code).is_none() {
+            bail!(
+                "Referral code must have 1..={} bytes",
+                ReferralStateData::CODE_LEN
+            );
+        }
+        if marinade.find_referral(&self.referral_code).is_ok() {
+            bail!("Referral {} already exists", self.referral_code);
+        }
+        info!(
+            "Create referral {} ({}) for partner {} with {} of the treasury cut",
+            self.referral_code, self.referral_state, self.partner, self.treasury_cut_share
+        );
+
+        let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+        let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+        let msol_account = if let Some(msol_account) = &self.msol_account {
+            msol_account.as_pubkey()
+        } else {
+            builder.get_or_create_associated_token_account(
+                marinade.client.clone(),
+                &self.partner.as_pubkey(),
+                &marinade.state.msol_mint,
+                "partner mSOL",
+            )?
+        };
+        builder.create_account(
+            self.referral_state.as_keypair(),
+            ReferralStateData::SPACE,
+            &cli_common::marinade_finance::ID,
+            &rent,
+            "referral",
+        )?;
+
+        if let Some(propose_output) = self.propose_output {
+            // create the accounts now, the multisig will init the referral
+            marinade.client.execute_transaction(builder.build_one())?;
+            write_multisig_instruction(
+                create_referral(
+                    &marinade.state,
+                    self.referral_state.as_pubkey(),
+                    self.partner.as_pubkey(),
+                    msol_account,
+                    self.referral_code,
+                    self.treasury_cut_share,
+                ),
+                &propose_output,
+            )?;
+        } else {
+            let admin_authority = if let Some(admin_authority) = &self.admin_authority {
+                info!("Using admin authority {}", admin_authority);
+                admin_authority.as_keypair()
+            } else {
+                info!("Using fee payer as admin authority");
+                common.fee_payer.as_keypair()
+            };
+
+            builder.create_referral(
+                &marinade.state,
+                admin_authority,
+                self.referral_state.as_pubkey(),
+                self.partner.as_pubkey(),
+                msol_account,
+                self.referral_code,
+                self.treasury_cut_share,
+            )?;
+
+            marinade.client.execute_transaction(builder.build_one())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct UpdateReferralOptions {
+    #[structopt(name = "referral-code")]
+    referral_code: String,
+
+    #[structopt(long, help = "stop accounting and paying the referral")]
+    pause: bool,
+
+    #[structopt(long, conflicts_with = "pause")]
+    resume: bool,
+
+    #[structopt(
+        long = "share",
+        help = "% of the liquid unstake treasury cut paid to the partner"
+    )]
+    treasury_cut_share: Option<Fee>,
+
+    #[structopt(env = "MARINADE_ADMIN")]
+    admin_authority: Option<InputKeypair>,
+
+    #[structopt(short = "p")]
+    propose_output: Option<ExpandedPath>,
+}
+
+impl UpdateReferralOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> Result<()> {
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+        let pause = match (self.pause, self.resume) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+        if pause.is_none() && self.treasury_cut_share.is_none() {
+            bail!("missing parameters");
+        }
+        let (referral_state, referral) = marinade.find_referral(&self.referral_code)?;
+        info!(
+            "Update referral {} ({}) of partner {}",
+            self.referral_code, referral_state, referral.partner_account
+        );
+
+        if let Some(propose_output) = self.propose_output {
+            write_multisig_instruction(
+                update_referral(
+                    &marinade.state,
+                    referral_state,
+                    pause,
+                    self.treasury_cut_share,
+                ),
+                &propose_output,
+            )?;
+        } else {
+            let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+            let admin_authority = if let Some(admin_authority) = &self.admin_authority {
+                info!("Using admin authority {}", admin_authority);
+                admin_authority.as_keypair()
+            } else {
+                info!("Using fee payer as admin authority");
+                common.fee_payer.as_keypair()
+            };
+
+            builder.update_referral(
+                &marinade.state,
+                admin_authority,
+                referral_state,
+                pause,
+                self.treasury_cut_share,
+            )?;
+
+            marinade.client.execute_transaction(builder.build_one())?;
+        }
+        Ok(())
+    }
+}
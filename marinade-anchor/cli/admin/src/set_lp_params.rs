@@ -1,12 +1,20 @@
-use crate::Common;
+use crate::{params_change::queue_change, Common};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use cli_common::solana_client::rpc_client::RpcClient;
 use cli_common::solana_sdk::native_token::{lamports_to_sol, sol_to_lamports};
 use cli_common::{
-    instruction_helpers::InstructionHelpers, marinade_finance::Fee,
-    rpc_client_helpers::RpcClientHelpers, rpc_marinade::RpcMarinade, set_lp_params,
-    transaction_builder::TransactionBuilder, ExpandedPath, InputKeypair,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{
+        liq_pool::{FeeCurve, FeeCurvePoint},
+        queued_change::ParamsChange,
+        Fee,
+    },
+    rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade,
+    set_lp_params,
+    transaction_builder::TransactionBuilder,
+    ExpandedPath, InputKeypair,
 };
 use log::info;
 
@@ -27,6 +35,24 @@ pub struct SetLpParamsOptions {
     #[structopt(short = "t", help = "liquidity target in SOL")]
     liquidity_target: Option<f64>,
 
+    #[structopt(long = "linear-curve", help = "use the linear fee curve (default)")]
+    linear_curve: bool,
+
+    #[structopt(
+        long = "point",
+        help = "piecewise fee curve point liquidity_SOL:fee_%, e.g. 1000:1.5 (repeat for more points)"
+    )]
+    curve_points: Vec<FeeCurvePoint>,
+
+    #[structopt(long = "half-life", help = "exponential fee curve half life in SOL")]
+    half_life: Option<f64>,
+
+    #[structopt(
+        long,
+        help = "queue the change in this new account instead of applying it now (required when timelocked)"
+    )]
+    queue: Option<InputKeypair>,
+
     #[structopt(env = "MARINADE_ADMIN")]
     admin_authority: Option<InputKeypair>,
 
@@ -38,7 +64,13 @@ impl SetLpParamsOptions {
     pub fn process(self, common: Common, client: Arc<RpcClient>) -> anyhow::Result<()> {
         let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
 
-        if self.min_fee.is_none() && self.max_fee.is_none() && self.liquidity_target.is_none() {
+        if self.min_fee.is_none()
+            && self.max_fee.is_none()
+            && self.liquidity_target.is_none()
+            && !self.linear_curve
+            && self.curve_points.is_empty()
+            && self.half_life.is_none()
+        {
             info!("missing parameters");
             return Err(anyhow!("missing parameters"));
         }
@@ -49,11 +81,40 @@ impl SetLpParamsOptions {
             .liquidity_target
             .unwrap_or(lamports_to_sol(marinade.state.liq_pool.lp_liquidity_target));
 
+        let fee_curve = match (
+            self.linear_curve,
+            self.curve_points.is_empty(),
+            self.half_life,
+        ) {
+            (false, true, None) => marinade.state.liq_pool_fee_curve,
+            (true, true, None) => FeeCurve::linear(),
+            (false, false, None) => FeeCurve::piecewise(&self.curve_points)?,
+            (false, true, Some(half_life)) => FeeCurve::exponential(sol_to_lamports(half_life)),
+            _ => bail!("Use only one of --linear-curve, --point or --half-life"),
+        };
+        fee_curve.check(min_fee, max_fee, sol_to_lamports(liquidity_target))?;
+
         info!(
-            "Set LP min_fee = {}, max_fee = {}, liquidity_target = {} SOL",
-            min_fee, max_fee, liquidity_target
+            "Set LP min_fee = {}, max_fee = {}, liquidity_target = {} SOL, fee_curve = {}",
+            min_fee, max_fee, liquidity_target, fee_curve
         );
 
+        if let Some(queued_change) = &self.queue {
+            return queue_change(
+                &common,
+                &marinade,
+                queued_change,
+                self.admin_authority.as_ref(),
+                self.propose_output.as_ref(),
+                ParamsChange::SetLpParams {
+                    min_fee,
+                    max_fee,
+                    liquidity_target: sol_to_lamports(liquidity_target),
+                    fee_curve,
+                },
+            );
+        }
+
         if let Some(propose_output) = self.propose_output {
             // Print transaction to stdout in multisig format
             use ::borsh::BorshSerialize;
@@ -64,6 +125,7 @@ impl SetLpParamsOptions {
                 min_fee,
                 max_fee,
                 sol_to_lamports(liquidity_target),
+                fee_curve,
             );
             info!(
                 "instruction-data: {}",
@@ -102,6 +164,7 @@ impl SetLpParamsOptions {
                 min_fee,
                 max_fee,
                 sol_to_lamports(liquidity_target),
+                fee_curve,
             )?;
 
             marinade.client.execute_transaction(builder.build_one())?;
@@ -0,0 +1,37 @@
+// Creates the epoch rewards ring buffer of the instance (once, paid by the fee payer).
+// From then on every update-price records the rewards of the epoch there
+
+use crate::Common;
+use anyhow::Result;
+use cli_common::marinade_finance::epoch_rewards::EpochRewards;
+use cli_common::solana_sdk::native_token::lamports_to_sol;
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
+};
+use log::info;
+
+pub fn process(
+    common: &Common,
+    marinade: &RpcMarinade,
+    builder: &mut TransactionBuilder,
+) -> Result<()> {
+    if marinade.epoch_rewards()?.is_some() {
+        info!("Epoch rewards are already created");
+        return Ok(());
+    }
+    let rent = marinade
+        .client
+        .get_minimum_balance_for_rent_exemption(EpochRewards::SPACE)?;
+    info!(
+        "Create epoch rewards {} for {} SOL",
+        EpochRewards::find_address(&marinade.state.key).0,
+        lamports_to_sol(rent)
+    );
+    let rent_payer = builder.fee_payer_signer();
+    builder.create_epoch_rewards(&marinade.state, rent_payer);
+    marinade
+        .client
+        .process_transaction(common.simulate, builder.build_one())?;
+    Ok(())
+}
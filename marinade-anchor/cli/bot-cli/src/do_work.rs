@@ -11,7 +11,7 @@ use cli_common::{
     instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
     rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
 };
-use log::info;
+use log::{info, warn};
 use std::ops::Sub;
 use std::time::SystemTime;
 use structopt::StructOpt;
@@ -72,6 +72,23 @@ impl DoWorkOptions {
             current_validators.len(),
             max_validators
         );
+        // full lists must be moved into bigger accounts with `mardmin grow-list`
+        const LIST_FULL_WARNING_PERCENT: usize = 90;
+        if current_validators.len() * 100 >= max_validators as usize * LIST_FULL_WARNING_PERCENT {
+            warn!(
+                "Validator list is almost full: {} of {}",
+                current_validators.len(),
+                max_validators
+            );
+        }
+        let (current_stakes, max_stakes) = marinade.stake_list()?;
+        if current_stakes.len() * 100 >= max_stakes as usize * LIST_FULL_WARNING_PERCENT {
+            warn!(
+                "Stake list is almost full: {} of {}",
+                current_stakes.len(),
+                max_stakes
+            );
+        }
         // create a hashmap PubKey->index
         let validators_with_score_count = current_validators
             .iter()
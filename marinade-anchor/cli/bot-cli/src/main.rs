@@ -16,12 +16,17 @@ use cli_common::{
     transaction_builder::TransactionBuilder, Cluster, ExpandedPath, InputKeypair, InputPubkey,
 };
 
+pub mod create_epoch_rewards;
 pub mod do_work;
 pub mod merge_stakes;
+pub mod rebalance_liq_pool;
+pub mod reclaim_rent;
 pub mod stake_delta;
 pub mod update_price;
 
 use do_work::*;
+use rebalance_liq_pool::*;
+use reclaim_rent::*;
 use stake_delta::*;
 use update_price::*;
 
@@ -78,6 +83,9 @@ enum BotCliCommand {
     UpdatePrice(UpdatePriceOptions),
     MergeStakes,
     DoWork(DoWorkOptions),
+    RebalanceLiqPool(RebalanceLiqPoolOptions),
+    CreateEpochRewards,
+    ReclaimRent(ReclaimRentOptions),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -144,6 +152,11 @@ fn main() -> anyhow::Result<()> {
             merge_stakes::process(&cli.common, &marinade, &mut builder, 0)?
         }
         BotCliCommand::DoWork(x) => x.process(&cli.common, &mut marinade, &mut builder)?,
+        BotCliCommand::RebalanceLiqPool(x) => x.process(&cli.common, &marinade, &mut builder)?,
+        BotCliCommand::ReclaimRent(x) => x.process(&cli.common, &marinade, &mut builder)?,
+        BotCliCommand::CreateEpochRewards => {
+            create_epoch_rewards::process(&cli.common, &marinade, &mut builder)?
+        }
     }
     Ok(())
 }
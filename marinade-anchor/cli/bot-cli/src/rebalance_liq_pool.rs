@@ -0,0 +1,137 @@
+// Liq-pool rebalance crank
+// liquid-unstake leaves mSOL in the liq-pool mSOL leg, so the SOL leg drains over time.
+// This crank delayed-unstakes the mSOL leg into tickets owned by the SOL leg
+// and claims those tickets back into the SOL leg when they are due
+
+use crate::Common;
+use anyhow::Result;
+use cli_common::marinade_finance::{liq_pool::LiqPoolHelpers, ticket_account::TicketAccountData};
+use cli_common::solana_sdk::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    program_pack::Pack,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar::rent,
+};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, spl_token, transaction_builder::TransactionBuilder,
+};
+use log::{info, warn};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+/// same as claim.rs WAIT_EPOCHS
+const WAIT_EPOCHS: u64 = 2;
+
+#[derive(StructOpt, Debug)]
+pub struct RebalanceLiqPoolOptions {
+    #[structopt(
+        long = "msol-amount",
+        help = "mSOL to unstake from the liq-pool (default: all the mSOL leg up to the liquidity target)"
+    )]
+    msol_amount: Option<f64>,
+
+    #[structopt(long = "claim-only", help = "only claim due liq-pool tickets")]
+    claim_only: bool,
+}
+
+impl RebalanceLiqPoolOptions {
+    pub fn process(
+        self,
+        common: &Common,
+        marinade: &RpcMarinade,
+        builder: &mut TransactionBuilder,
+    ) -> Result<()> {
+        let clock = marinade.get_clock()?;
+        let sol_leg = marinade.state.liq_pool_sol_leg_address();
+
+        // claim due tickets into the SOL leg
+        for (ticket_address, ticket) in marinade.tickets(&sol_leg)? {
+            if ticket.lamports_amount == 0 || ticket.created_epoch + WAIT_EPOCHS > clock.epoch {
+                continue;
+            }
+            info!(
+                "Claim liq-pool ticket {} {} SOL",
+                ticket_address,
+                lamports_to_sol(ticket.lamports_amount)
+            );
+            builder.claim(&marinade.state, ticket_address, sol_leg);
+            if let Err(err) = marinade
+                .client
+                .process_transaction(common.simulate, builder.build_one())
+            {
+                // the reserve may not be ready yet, retry on next run
+                warn!("TX ERR {:?}", err);
+            }
+        }
+
+        if self.claim_only {
+            return Ok(());
+        }
+
+        let msol_leg_amount = spl_token::state::Account::unpack(
+            &marinade
+                .client
+                .get_account_data_retrying(&marinade.state.liq_pool.msol_leg)?,
+        )?
+        .amount;
+        // the program only refills the SOL leg (with receivable tickets) up to the liquidity target
+        let sol_leg_lamports = marinade
+            .client
+            .get_balance(&sol_leg)?
+            .saturating_sub(marinade.state.rent_exempt_for_token_acc)
+            .saturating_add(marinade.state.liq_pool_tickets_receivable);
+        if sol_leg_lamports >= marinade.state.liq_pool.lp_liquidity_target {
+            info!(
+                "liq-pool SOL leg {} SOL is at the liquidity target, nothing to rebalance",
+                lamports_to_sol(sol_leg_lamports)
+            );
+            return Ok(());
+        }
+        let shortfall_msol = marinade.state.calc_msol_from_lamports(
+            marinade.state.liq_pool.lp_liquidity_target - sol_leg_lamports,
+        )?;
+        let msol_amount = self
+            .msol_amount
+            .map_or(msol_leg_amount, sol_to_lamports)
+            .min(msol_leg_amount)
+            .min(shortfall_msol);
+        let lamports_amount = marinade.state.calc_lamports_from_msol_amount(msol_amount)?;
+        if lamports_amount < marinade.state.min_withdraw {
+            info!(
+                "liq-pool mSOL leg {} mSOL, nothing to rebalance",
+                lamports_to_sol(msol_leg_amount)
+            );
+            return Ok(());
+        }
+
+        let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+        // Create a empty ticket account (transfer rent-exempt lamports)
+        const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+        let ticket_account = Arc::new(Keypair::new());
+        let ticket_address = ticket_account.pubkey();
+        builder.begin();
+        builder
+            .create_account(
+                ticket_account,
+                TICKET_ACCOUNT_SPACE,
+                &cli_common::marinade_finance::ID,
+                &rent,
+                "liq-pool ticket-account",
+            )
+            .unwrap();
+        builder.rebalance_liq_pool(&marinade.state, ticket_address, msol_amount);
+        builder.commit();
+        info!(
+            "Rebalance {} mSOL from liq-pool into ticket {}",
+            lamports_to_sol(msol_amount),
+            ticket_address
+        );
+        marinade
+            .client
+            .process_transaction(common.simulate, builder.build_one())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+// Rent reclaim crank
+// duplication flags of validators removed from the list and claimed tickets left with lamports
+// are closed and their rent goes to the operational_sol_account
+
+use crate::Common;
+use anyhow::Result;
+use cli_common::marinade_finance::validator_system::ValidatorRecord;
+use cli_common::solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
+};
+use log::info;
+use std::collections::HashSet;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ReclaimRentOptions {
+    #[structopt(
+        long = "validator-vote",
+        help = "also check the flag of this (closed) vote account"
+    )]
+    validator_votes: Vec<Pubkey>,
+}
+
+impl ReclaimRentOptions {
+    pub fn process(
+        self,
+        common: &Common,
+        marinade: &RpcMarinade,
+        builder: &mut TransactionBuilder,
+    ) -> Result<()> {
+        let (validator_list, _) = marinade.validator_list()?;
+        let listed: HashSet<Pubkey> = validator_list
+            .iter()
+            .map(|validator| validator.validator_account)
+            .collect();
+        let flags: HashSet<Pubkey> = marinade.flag_accounts()?.into_iter().collect();
+
+        let vote_accounts = marinade.client.get_vote_accounts()?;
+        let mut validator_votes: HashSet<Pubkey> = vote_accounts
+            .current
+            .iter()
+            .chain(vote_accounts.delinquent.iter())
+            .map(|vote_account| Pubkey::from_str(&vote_account.vote_pubkey))
+            .collect::<Result<_, _>>()?;
+        validator_votes.extend(self.validator_votes);
+
+        for validator_vote in validator_votes {
+            if listed.contains(&validator_vote) {
+                continue;
+            }
+            let duplication_flag =
+                ValidatorRecord::find_duplication_flag(&marinade.state.key, &validator_vote).0;
+            if !flags.contains(&duplication_flag) {
+                continue;
+            }
+            info!(
+                "Reclaim rent of validator {} duplication flag {}",
+                validator_vote, duplication_flag
+            );
+            builder.reclaim_rent(&marinade.state, duplication_flag, Some(validator_vote));
+        }
+
+        for (ticket_address, _) in marinade.claimed_tickets()? {
+            let lamports = marinade
+                .client
+                .get_account_retrying(&ticket_address)?
+                .map_or(0, |account| account.lamports);
+            info!(
+                "Reclaim rent of claimed ticket {} {} SOL",
+                ticket_address,
+                lamports_to_sol(lamports)
+            );
+            builder.reclaim_rent(&marinade.state, ticket_address, None);
+        }
+
+        marinade
+            .client
+            .process_transaction_sequence(common.simulate, builder.combined_sequence())?;
+        Ok(())
+    }
+}
@@ -24,6 +24,7 @@ use cli_common::{
     InputKeypair,
 };
 use log::{error, info, warn};
+use std::collections::HashMap;
 use std::time::SystemTime;
 use std::{convert::TryInto, sync::Arc, thread, time};
 use structopt::StructOpt;
@@ -99,16 +100,22 @@ impl StakeDeltaOptions {
             return Ok(true);
         }
 
+        // lamports waiting for directed stake are not distributed by score
+        let score_stake_delta = if total_stake_delta > 0 {
+            (total_stake_delta - marinade.state.total_directed_stake as i128).max(0)
+        } else {
+            total_stake_delta
+        };
         // compute stake_target as currently staked +/- delta
         let total_stake_target: u64 =
-            (marinade.state.validator_system.total_active_balance as i128 + total_stake_delta)
+            (marinade.state.validator_system.total_active_balance as i128 + score_stake_delta)
                 .try_into()?;
 
         // create validators_info vec
         struct ValidatorInfo {
             index: u32,
             record: ValidatorRecord,
-            stake_delta: i128, // delta between actual stake and target stake according to score, positive=>need more stake, negative=>needs unstake
+            stake_delta: i128, // delta between actual stake and target stake according to score and directed stake, positive=>need more stake, negative=>needs unstake
         }
         let (validator_list, _) = marinade.validator_list()?;
         let mut validators_info: Vec<ValidatorInfo> = validator_list
@@ -117,13 +124,13 @@ impl StakeDeltaOptions {
             .map(|(index, record)| {
                 let stake_target = marinade
                     .state
-                    .validator_system
                     .validator_stake_target(&record, total_stake_target)
                     .unwrap();
                 ValidatorInfo {
                     index: index as u32,
                     record,
-                    stake_delta: stake_target as i128 - record.active_balance as i128,
+                    stake_delta: stake_target as i128 - record.active_balance as i128
+                        + record.directed_stake_lamports as i128,
                 }
             })
             .collect();
@@ -146,15 +153,46 @@ impl StakeDeltaOptions {
                 lamports_to_sol(total_stake_delta as u64),
                 total_stake_delta
             );
-            // sort validators to process first the one with most score
-            validators_info.sort_by_key(|info| -(info.record.score as i64));
+            // sort validators to process first the ones with directed stake, then the one with most score
+            validators_info.sort_by_key(|info| {
+                (
+                    info.record.directed_stake_lamports == 0,
+                    -(info.record.score as i64),
+                )
+            });
+            // the program does not stake into validators over max_commission
+            let vote_accounts = marinade.client.get_vote_accounts()?;
+            let commissions: HashMap<String, u8> = vote_accounts
+                .current
+                .into_iter()
+                .chain(vote_accounts.delinquent.into_iter())
+                .map(|vote_account| (vote_account.vote_pubkey, vote_account.commission))
+                .collect();
             //execute at most self.limit staking instructions, one per validator
-            //starting with the better scored
+            //starting with the directed and the better scored
             for validator_info in &validators_info {
                 if validator_info.stake_delta < 0 {
                     continue; // this validator does not need stake
                 }
-                if validator_info.stake_delta < marinade.state.stake_system.min_stake as i128 {
+                if let Some(commission) =
+                    commissions.get(&validator_info.record.validator_account.to_string())
+                {
+                    if marinade.state.max_commission > 0
+                        && *commission > marinade.state.max_commission
+                    {
+                        warn!(
+                            "Validator {} commission {}% is over max commission {}%",
+                            validator_info.record.validator_account,
+                            commission,
+                            marinade.state.max_commission
+                        );
+                        continue;
+                    }
+                }
+                // directed stake is always moved (on-chain program stakes at least min_stake)
+                if validator_info.record.directed_stake_lamports == 0
+                    && validator_info.stake_delta < marinade.state.stake_system.min_stake as i128
+                {
                     continue; // we don't move less than marinade.state.min_stake (1 SOL)
                 }
                 if validator_info.record.last_stake_delta_epoch == clock.epoch {
@@ -5,22 +5,67 @@
 // Having single-action functions makes the entire system less efficient (read calls & tx) but more resilient.
 // The idea is that you can call bot cranks in any order or at any time and it will be
 // a small operation and will not corrupt state or block the system no matter the order of the calls
+//
+// With hundreds of stake accounts the stakes are packed into update_*_batch instructions,
+// as many as fit a transaction (and MAX_BATCH_LEN for the compute budget).
+// A failed batch is retried on the next run as before
 
 use crate::Common;
 use anyhow::Result;
 use cli_common::anchor_lang::prelude::*;
-use cli_common::solana_sdk::{clock::Epoch, pubkey::Pubkey, sysvar::stake_history};
+use cli_common::solana_sdk::{
+    clock::Epoch, instruction::Instruction, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    sysvar::stake_history, transaction::Transaction,
+};
 use cli_common::{
     instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
-    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, update_active_batch,
+    update_deactivated_batch,
 };
 use log::{debug, info, warn};
 use std::collections::HashMap;
 use structopt::StructOpt;
 
+/// stake accounts updated by one instruction (limited by the compute budget)
+const MAX_BATCH_LEN: usize = 10;
+
 #[derive(StructOpt, Debug)]
 pub struct UpdatePriceOptions {}
 
+fn fits_transaction(instruction: Instruction, fee_payer: &Pubkey) -> bool {
+    let transaction = Transaction::new_with_payer(&[instruction], Some(fee_payer));
+    bincode::serialize(&transaction).unwrap().len() <= PACKET_DATA_SIZE
+}
+
+/// returns false if the transaction failed
+fn process_batch(
+    common: &Common,
+    marinade: &RpcMarinade,
+    builder: &mut TransactionBuilder,
+) -> bool {
+    match marinade
+        .client
+        .process_transaction(common.simulate, builder.build_one())
+    {
+        Ok(_) => true,
+        Err(err) => {
+            // just show the err, count it, and continue with next batch
+            // stakes will be retried on next run of do-work in 5 minutes
+            warn!("TX ERR {:?}", err);
+            false
+        }
+    }
+}
+
+/// how many of the stakes fit one update_*_batch instruction
+fn batch_len(stakes: usize, fits: impl Fn(usize) -> bool) -> usize {
+    let mut len = 1;
+    while len < stakes.min(MAX_BATCH_LEN) && fits(len + 1) {
+        len += 1;
+    }
+    len
+}
+
 impl UpdatePriceOptions {
     // fn update_price()
     // returns true if all stakes are processed
@@ -73,6 +118,10 @@ impl UpdatePriceOptions {
         let mut count_tx_ok: u32 = 0;
         let mut count_tx_err: u32 = 0;
         let mut count_processed: u32 = 0;
+        // (stake_account, stake_index, validator_index)
+        let mut active_stakes: Vec<(Pubkey, u32, u32)> = Vec::new();
+        // (stake_account, stake_index)
+        let mut deactivated_stakes: Vec<(Pubkey, u32)> = Vec::new();
         //let mut all_stakes_updated = true; // Will be set to false when we reject to put some stake needed for update into transaction
 
         //for each stake account
@@ -110,53 +159,17 @@ impl UpdatePriceOptions {
                     " -- stake_info.index {}, validator_index {}",
                     stake_info.index, validator_index
                 );
-                builder.begin();
-                builder.update_active(
-                    &marinade.state,
+                active_stakes.push((
                     stake_info.record.stake_account,
                     stake_info.index,
                     validator_index,
-                );
-                builder.commit();
-                match marinade
-                    .client
-                    .process_transaction(common.simulate, builder.build_one())
-                {
-                    Ok(_) => {
-                        count_tx_ok += 1;
-                    }
-                    Err(err) => {
-                        // just show the err, count it, and continue with next account
-                        // account will be retried on next run of do-work in 5 minutes
-                        warn!("TX ERR {:?}", err);
-                        count_tx_err += 1;
-                    }
-                };
+                ));
             //
             // if deactivated
             } else if effective == 0 {
                 info!("Update deactivated {}", stake_info.record.stake_account);
-                builder.begin();
-                builder.update_deactivated(
-                    &marinade.state,
-                    stake_info.record.stake_account,
-                    stake_info.index,
-                );
-                builder.commit();
-                match marinade
-                    .client
-                    .process_transaction(common.simulate, builder.build_one())
-                {
-                    Ok(_) => {
-                        count_tx_ok += 1;
-                    }
-                    Err(err) => {
-                        // just show the err, count it, and continue with next account
-                        // account will be retried on next run of do-work in 5 minutes
-                        warn!("TX ERR {:?}", err);
-                        count_tx_err += 1;
-                    }
-                };
+                // stakes_info_reversed keeps the indexes decreasing, as update_deactivated_batch needs
+                deactivated_stakes.push((stake_info.record.stake_account, stake_info.index));
             //
             // assume cooling-down
             } else {
@@ -225,6 +238,75 @@ impl UpdatePriceOptions {
             // }
         }
 
+        let fee_payer = builder.fee_payer();
+        // active stakes first, the deactivated ones are removed from the stake list
+        // and the last stake takes the place of each removed one
+        let mut rest = &active_stakes[..];
+        while !rest.is_empty() {
+            let args = |len: usize| {
+                let batch = &rest[..len];
+                (
+                    batch.iter().map(|stake| stake.0).collect::<Vec<_>>(),
+                    batch.iter().map(|stake| stake.1).collect::<Vec<_>>(),
+                    batch.iter().map(|stake| stake.2).collect::<Vec<_>>(),
+                )
+            };
+            let len = batch_len(rest.len(), |len| {
+                let (stake_accounts, stake_indexes, validator_indexes) = args(len);
+                fits_transaction(
+                    update_active_batch(
+                        &marinade.state,
+                        &stake_accounts,
+                        stake_indexes,
+                        validator_indexes,
+                    ),
+                    &fee_payer,
+                )
+            });
+            let (stake_accounts, stake_indexes, validator_indexes) = args(len);
+            builder.begin();
+            builder.update_active_batch(
+                &marinade.state,
+                &stake_accounts,
+                stake_indexes,
+                validator_indexes,
+            );
+            builder.commit();
+            if process_batch(common, marinade, builder) {
+                count_tx_ok += 1;
+            } else {
+                count_tx_err += 1;
+            }
+            rest = &rest[len..];
+        }
+        let mut rest = &deactivated_stakes[..];
+        while !rest.is_empty() {
+            let args = |len: usize| {
+                let batch = &rest[..len];
+                (
+                    batch.iter().map(|stake| stake.0).collect::<Vec<_>>(),
+                    batch.iter().map(|stake| stake.1).collect::<Vec<_>>(),
+                )
+            };
+            let len = batch_len(rest.len(), |len| {
+                let (stake_accounts, stake_indexes) = args(len);
+                fits_transaction(
+                    update_deactivated_batch(&marinade.state, &stake_accounts, stake_indexes),
+                    &fee_payer,
+                )
+            });
+            let (stake_accounts, stake_indexes) = args(len);
+            builder.begin();
+            builder.update_deactivated_batch(&marinade.state, &stake_accounts, stake_indexes);
+            builder.commit();
+            if process_batch(common, marinade, builder) {
+                count_tx_ok += 1;
+            } else {
+                count_tx_err += 1;
+            }
+            rest = &rest[len..];
+        }
+
         info!(
             "count processed:{}, tx Ok:{}, tx Err:{}",
             count_processed, count_tx_ok, count_tx_err
@@ -3,15 +3,24 @@ use marinade_finance_offchain_sdk::{solana_sdk, WithKey};
 use std::sync::Arc;
 
 use anyhow::bail;
-use marinade_finance_offchain_sdk::anchor_lang::{
-    prelude::Pubkey, AccountDeserialize, AnchorDeserialize,
-};
+use marinade_finance_offchain_sdk::anchor_lang::{prelude::Pubkey, AccountDeserialize};
 
 use marinade_finance_offchain_sdk::marinade_finance::{
-    stake_system::StakeRecord, validator_system::ValidatorRecord, State,
+    epoch_rewards::{EpochRewardRecord, EpochRewards},
+    pending_stake_deposit::PendingStakeDepositData,
+    queued_change::QueuedChangeData,
+    referral::ReferralStateData,
+    stake_system::StakeRecord,
+    ticket_account::TicketAccountData,
+    validator_system::ValidatorRecord,
+    State, ID,
 };
 use marinade_finance_offchain_sdk::solana_sdk::{clock::Clock, stake::state::StakeState};
-use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
 
 use crate::rpc_client_helpers::RpcClientHelpers;
 
@@ -44,15 +53,13 @@ impl RpcMarinade {
         let validator_list_account_data = self
             .client
             .get_account_data_retrying(self.state.validator_system.validator_list_address())?;
-        let validator_record_size = self.state.validator_system.validator_record_size() as usize;
 
         Ok((
             (0..self.state.validator_system.validator_count())
                 .map(|index| {
-                    let start = 8 + index as usize * validator_record_size;
-                    ValidatorRecord::deserialize(
-                        &mut &validator_list_account_data[start..(start + validator_record_size)],
-                    )
+                    self.state
+                        .validator_system
+                        .get(&validator_list_account_data, index)
                 })
                 .collect::<Result<Vec<_>, _>>()?,
             self.state
@@ -65,15 +72,9 @@ impl RpcMarinade {
         let stake_list_account_data = self
             .client
             .get_account_data_retrying(self.state.stake_system.stake_list_address())?;
-        let stake_record_size = self.state.stake_system.stake_record_size() as usize;
         Ok((
             (0..self.state.stake_system.stake_count())
-                .map(|index| {
-                    let start = 8 + index as usize * stake_record_size;
-                    StakeRecord::deserialize(
-                        &mut &stake_list_account_data[start..(start + stake_record_size)],
-                    )
-                })
+                .map(|index| self.state.stake_system.get(&stake_list_account_data, index))
                 .collect::<Result<Vec<_>, _>>()?,
             self.state
                 .stake_system
@@ -141,6 +142,228 @@ impl RpcMarinade {
         Ok((vec, stakes_capacity))
     }
 
+    /// ticket accounts of this instance with the given beneficiary
+    pub fn tickets(
+        &self,
+        beneficiary: &Pubkey,
+    ) -> anyhow::Result<Vec<(Pubkey, TicketAccountData)>> {
+        const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+        self.client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(TICKET_ACCOUNT_SPACE as u64),
+                        // state_address
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Binary(self.state.key.to_string()),
+                            encoding: None,
+                        }),
+                        // beneficiary
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8 + 32,
+                            bytes: MemcmpEncodedBytes::Binary(beneficiary.to_string()),
+                            encoding: None,
+                        }),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AccountDeserialize::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect()
+    }
+
+    /// fully claimed ticket accounts of this instance still holding lamports
+    pub fn claimed_tickets(&self) -> anyhow::Result<Vec<(Pubkey, TicketAccountData)>> {
+        const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+        let tickets = self
+            .client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(TICKET_ACCOUNT_SPACE as u64),
+                        // state_address
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Binary(self.state.key.to_string()),
+                            encoding: None,
+                        }),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AccountDeserialize::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<(Pubkey, TicketAccountData)>>>()?;
+        Ok(tickets
+            .into_iter()
+            .filter(|(_, ticket)| ticket.lamports_amount == 0)
+            .collect())
+    }
+
+    /// program owned accounts without data (duplication flags and blocklist entries of all instances)
+    pub fn flag_accounts(&self) -> anyhow::Result<Vec<Pubkey>> {
+        Ok(self
+            .client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![RpcFilterType::DataSize(0)]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect())
+    }
+
+    /// params changes queued for this instance
+    pub fn queued_changes(&self) -> anyhow::Result<Vec<(Pubkey, QueuedChangeData)>> {
+        self.client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(QueuedChangeData::SPACE as u64),
+                        // state_address
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Binary(self.state.key.to_string()),
+                            encoding: None,
+                        }),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AccountDeserialize::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn pending_stake_deposits(&self) -> anyhow::Result<Vec<(Pubkey, PendingStakeDepositData)>> {
+        self.client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(PendingStakeDepositData::SPACE as u64),
+                        // state_address
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Binary(self.state.key.to_string()),
+                            encoding: None,
+                        }),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AccountDeserialize::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn referrals(&self) -> anyhow::Result<Vec<(Pubkey, ReferralStateData)>> {
+        self.client
+            .get_program_accounts_with_config(
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(ReferralStateData::SPACE as u64),
+                        // state_address
+                        RpcFilterType::Memcmp(Memcmp {
+                            offset: 8,
+                            bytes: MemcmpEncodedBytes::Binary(self.state.key.to_string()),
+                            encoding: None,
+                        }),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: None,
+                        commitment: Some(self.client.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    with_context: None,
+                },
+            )?
+            .into_iter()
+            .map(|(address, account)| {
+                Ok((
+                    address,
+                    AccountDeserialize::try_deserialize(&mut account.data.as_slice())?,
+                ))
+            })
+            .collect()
+    }
+
+    pub fn find_referral(
+        &self,
+        referral_code: &str,
+    ) -> anyhow::Result<(Pubkey, ReferralStateData)> {
+        self.referrals()?
+            .into_iter()
+            .find(|(_, referral)| referral.code() == referral_code)
+            .ok_or_else(|| anyhow::anyhow!("Can not find referral {}", referral_code))
+    }
+
+    /// epoch reward records of this instance, the oldest first. None if not created yet
+    pub fn epoch_rewards(&self) -> anyhow::Result<Option<Vec<EpochRewardRecord>>> {
+        let address = EpochRewards::find_address(&self.state.key).0;
+        match self.client.get_account_retrying(&address)? {
+            Some(account) if account.owner == ID => {
+                let epoch_rewards = EpochRewards::load(&account.data, &self.state.key)?;
+                Ok(Some(epoch_rewards.records(&account.data)))
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub fn get_clock(&self) -> anyhow::Result<Clock> {
         Ok(bincode::deserialize(
             &self
@@ -2,14 +2,22 @@ use crate::{Command, Common};
 
 use anyhow::{bail, Result};
 use cli_common::{
-    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
-    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
-    transaction_helpers::TransactionBuilderHelpers, InputKeypair,
+    instruction_helpers::InstructionHelpers, marinade_finance::ticket_account::TicketAccountData,
+    rpc_client_helpers::RpcClientHelpers, rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder, transaction_helpers::TransactionBuilderHelpers,
+    InputKeypair,
 };
 use log::{error, info};
 
-use cli_common::solana_sdk::native_token::sol_to_lamports;
+use cli_common::solana_sdk::{
+    native_token::sol_to_lamports,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar::rent,
+};
 use cli_common::spl_associated_token_account::get_associated_token_address;
+use std::sync::Arc;
 
 use structopt::StructOpt;
 
@@ -58,6 +66,64 @@ impl Command for AddLiquidity {
 
 //------------------------------------
 
+#[derive(Debug, StructOpt)]
+pub struct AddLiquidityMsol {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(name = "mSOL-amount")]
+    amount: f64,
+}
+
+impl Command for AddLiquidityMsol {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        info!("Using fee payer {}", self.fee_payer);
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+
+        let user_msol_account =
+            get_associated_token_address(&self.fee_payer.as_pubkey(), &marinade.state.msol_mint);
+
+        // TODO: check balance
+        if marinade
+            .client
+            .get_account_retrying(&user_msol_account)?
+            .is_none()
+        {
+            error!("Can not find user mSOL account {}", user_msol_account);
+            bail!("Can not find user mSOL account {}", user_msol_account);
+        }
+
+        // find or create the associated (canonical) smart-lp token account for the user
+        let user_smart_lp_account = builder.get_or_create_associated_token_account(
+            marinade.client.clone(),
+            &self.fee_payer.as_pubkey(),
+            &marinade.state.liq_pool.lp_mint,
+            "smart-lp",
+        )?;
+
+        builder.add_liquidity_msol(
+            &marinade.state,
+            user_msol_account,
+            self.fee_payer.as_keypair(),
+            user_smart_lp_account,
+            sol_to_lamports(self.amount),
+        );
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        Ok(())
+    }
+}
+
+//------------------------------------
+
 #[derive(Debug, StructOpt)]
 pub struct RemoveLiquidity {
     #[structopt(
@@ -69,6 +135,10 @@ pub struct RemoveLiquidity {
 
     #[structopt(name = "LP-token-amount")]
     amount: f64,
+
+    /// ticket bought by the liq-pool to split when the SOL leg can not pay the receivable share
+    #[structopt(long = "pool-ticket")]
+    pool_ticket: Option<Pubkey>,
 }
 
 impl Command for RemoveLiquidity {
@@ -102,14 +172,42 @@ impl Command for RemoveLiquidity {
             "user mSOL",
         )?;
 
-        builder.remove_liquidity(
-            &marinade.state,
-            user_smart_lp_account,
-            self.fee_payer.as_keypair(),
-            self.fee_payer.as_pubkey(),
-            user_msol_account,
-            sol_to_lamports(self.amount),
-        );
+        if let Some(pool_ticket) = self.pool_ticket {
+            let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+            // Create a empty ticket account (transfer rent-exempt lamports)
+            const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+            let ticket_account = Arc::new(Keypair::new());
+            let ticket_address = ticket_account.pubkey();
+            builder
+                .create_account(
+                    ticket_account,
+                    TICKET_ACCOUNT_SPACE,
+                    &cli_common::marinade_finance::ID,
+                    &rent,
+                    "ticket-account",
+                )
+                .unwrap();
+            builder.remove_liquidity_with_ticket(
+                &marinade.state,
+                user_smart_lp_account,
+                self.fee_payer.as_keypair(),
+                self.fee_payer.as_pubkey(),
+                user_msol_account,
+                sol_to_lamports(self.amount),
+                pool_ticket,
+                ticket_address,
+            );
+            info!("Receivable share goes to ticket {}", ticket_address);
+        } else {
+            builder.remove_liquidity(
+                &marinade.state,
+                user_smart_lp_account,
+                self.fee_payer.as_keypair(),
+                self.fee_payer.as_pubkey(),
+                user_msol_account,
+                sol_to_lamports(self.amount),
+            );
+        }
 
         marinade
             .client
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, InputKeypair,
+};
+use log::info;
+
+use cli_common::solana_sdk::pubkey::Pubkey;
+use structopt::StructOpt;
+
+use crate::Command;
+
+use super::Common;
+
+#[derive(Debug, StructOpt)]
+pub struct ChangeBeneficiary {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(name = "ticket")]
+    ticket_account: Pubkey,
+
+    #[structopt(name = "new_beneficiary")]
+    new_beneficiary: Pubkey,
+}
+
+impl Command for ChangeBeneficiary {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        //
+        info!("Using fee payer {}", self.fee_payer);
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+
+        builder.change_beneficiary(
+            &marinade.state,
+            self.ticket_account,
+            self.fee_payer.as_keypair(), //current ticket beneficiary
+            self.new_beneficiary,
+        );
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        info!(
+            "Ticket {} transferred to {}",
+            self.ticket_account, self.new_beneficiary
+        );
+
+        Ok(())
+    }
+}
@@ -5,7 +5,7 @@ use cli_common::{
 };
 use log::info;
 
-use cli_common::solana_sdk::pubkey::Pubkey;
+use cli_common::solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey};
 use structopt::StructOpt;
 
 use crate::Command;
@@ -23,6 +23,9 @@ pub struct Claim {
 
     #[structopt(name = "ticket")]
     ticket_account: Pubkey,
+
+    #[structopt(long = "amount", help = "Claim only this amount of SOL, keep the rest in the ticket")]
+    sol_amount: Option<f64>,
 }
 
 impl Command for Claim {
@@ -36,12 +39,22 @@ impl Command for Claim {
 
         //let ticket: TicketAccountData = AccountDeserialize::try_deserialize( marinade.client.get_account_data(ticket_account).as_slice);
 
-        // Create a Claim instruction.
-        builder.claim(
-            &marinade.state,
-            self.ticket_account,
-            self.fee_payer.as_pubkey(), //ticket beneficiary
-        );
+        if let Some(sol_amount) = self.sol_amount {
+            // Create a PartialClaim instruction.
+            builder.partial_claim(
+                &marinade.state,
+                self.ticket_account,
+                self.fee_payer.as_pubkey(), //ticket beneficiary
+                sol_to_lamports(sol_amount),
+            );
+        } else {
+            // Create a Claim instruction.
+            builder.claim(
+                &marinade.state,
+                self.ticket_account,
+                self.fee_payer.as_pubkey(), //ticket beneficiary
+            );
+        }
 
         marinade
             .client
@@ -2,9 +2,14 @@ use crate::{Command, Common};
 
 use anyhow::{anyhow, bail, Result};
 use cli_common::{
-    instruction_helpers::InstructionHelpers, marinade_finance::stake_system::StakeSystemHelpers,
-    rpc_client_helpers::RpcClientHelpers, rpc_marinade::RpcMarinade,
-    transaction_builder::TransactionBuilder, transaction_helpers::TransactionBuilderHelpers,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{
+        pending_stake_deposit::PendingStakeDepositData, stake_system::StakeSystemHelpers,
+    },
+    rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder,
+    transaction_helpers::TransactionBuilderHelpers,
     InputKeypair, InputPubkey,
 };
 use log::{error, info};
@@ -17,6 +22,9 @@ use cli_common::solana_sdk::{
 
 use structopt::StructOpt;
 
+/// same as DepositStakeAccount::WAIT_EPOCHS in the program
+const WAIT_EPOCHS: u64 = 2;
+
 #[derive(Debug, StructOpt)]
 pub struct DepositStakeAccount {
     #[structopt(
@@ -31,6 +39,12 @@ pub struct DepositStakeAccount {
     stake_authority: Option<InputKeypair>, // fee payer by default
 
     rent_payer: Option<InputKeypair>,
+
+    #[structopt(
+        long,
+        help = "lockup custodian signature to deposit a stake with the lockup in force"
+    )]
+    custodian: Option<InputKeypair>,
 }
 
 impl Command for DepositStakeAccount {
@@ -77,16 +91,6 @@ impl Command for DepositStakeAccount {
                     error!("Stake {} is cooling down", &self.stake);
                     bail!("Stake {} is cooling down", &self.stake);
                 }
-                if stake.delegation.activation_epoch >= current_epoch {
-                    error!(
-                        "Stake {} is too young. Please wait at least 1 epoch",
-                        &self.stake
-                    );
-                    bail!(
-                        "Stake {} is too young. Please wait at least 1 epoch",
-                        &self.stake
-                    );
-                }
                 (meta, stake.delegation)
             }
             StakeState::RewardsPool => {
@@ -177,15 +181,44 @@ impl Command for DepositStakeAccount {
             }
         }
 
-        builder.deposit_stake_account(
-            &marinade.state,
-            self.stake.as_pubkey(),
-            input_authority,
-            user_msol_account,
-            validator_index,
-            delegation.voter_pubkey,
-            rent_payer,
-        );
+        let custodian = self.custodian.map(|custodian| {
+            info!("Using lockup custodian {}", custodian);
+            custodian.as_keypair()
+        });
+
+        if delegation.activation_epoch + WAIT_EPOCHS > current_epoch {
+            // mSOL for the delegation is minted by finish-stake-deposit after activation
+            let pending_deposit =
+                PendingStakeDepositData::find_address(&marinade.state.key, &self.stake.as_pubkey())
+                    .0;
+            info!(
+                "Stake {} is activating. Creating pending deposit {} finishable at epoch {}",
+                self.stake.as_pubkey(),
+                pending_deposit,
+                delegation.activation_epoch + WAIT_EPOCHS
+            );
+            builder.deposit_activating_stake_account(
+                &marinade.state,
+                self.stake.as_pubkey(),
+                input_authority,
+                user_msol_account,
+                validator_index,
+                delegation.voter_pubkey,
+                rent_payer,
+                custodian,
+            );
+        } else {
+            builder.deposit_stake_account(
+                &marinade.state,
+                self.stake.as_pubkey(),
+                input_authority,
+                user_msol_account,
+                validator_index,
+                delegation.voter_pubkey,
+                rent_payer,
+                custodian,
+            );
+        }
 
         marinade
             .client
@@ -0,0 +1,88 @@
+use anyhow::{anyhow, bail, Result};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, InputKeypair,
+};
+use log::{error, info};
+
+use cli_common::solana_sdk::pubkey::Pubkey;
+use structopt::StructOpt;
+
+use crate::Command;
+
+use super::Common;
+
+/// same as DepositStakeAccount::WAIT_EPOCHS in the program
+const WAIT_EPOCHS: u64 = 2;
+
+#[derive(Debug, StructOpt)]
+pub struct FinishStakeDeposit {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(
+        long,
+        help = "pending deposit account to finish (all activated ones by default)"
+    )]
+    pending_deposit: Option<Pubkey>,
+}
+
+impl Command for FinishStakeDeposit {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        info!("Using fee payer {}", self.fee_payer);
+
+        let current_epoch = marinade.get_clock()?.epoch;
+        let (validators, _) = marinade.validator_list()?;
+        let pending_deposits: Vec<_> = marinade
+            .pending_stake_deposits()?
+            .into_iter()
+            .filter(|(address, _)| {
+                self.pending_deposit
+                    .map_or(true, |pending_deposit| *address == pending_deposit)
+            })
+            .collect();
+        if pending_deposits.is_empty() {
+            error!("No pending stake deposits found");
+            bail!("No pending stake deposits found");
+        }
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+        for (address, pending_deposit) in pending_deposits {
+            if pending_deposit.activation_epoch + WAIT_EPOCHS > current_epoch {
+                info!(
+                    "Pending deposit {} of stake {} is finishable at epoch {}",
+                    address,
+                    pending_deposit.stake_account,
+                    pending_deposit.activation_epoch + WAIT_EPOCHS
+                );
+                continue;
+            }
+            let validator_index = validators
+                .iter()
+                .position(|validator| validator.validator_account == pending_deposit.validator_vote)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Can not find validator {} in marinade list",
+                        pending_deposit.validator_vote
+                    )
+                })?;
+            builder.finish_stake_deposit(
+                &marinade.state,
+                pending_deposit.stake_account,
+                pending_deposit.rent_payer,
+                pending_deposit.mint_to,
+                validator_index as u32,
+            );
+        }
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        Ok(())
+    }
+}
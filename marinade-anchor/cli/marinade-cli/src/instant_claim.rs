@@ -0,0 +1,49 @@
+use anyhow::Result;
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, InputKeypair,
+};
+use log::info;
+
+use cli_common::solana_sdk::pubkey::Pubkey;
+use structopt::StructOpt;
+
+use crate::Command;
+
+use super::Common;
+
+#[derive(Debug, StructOpt)]
+pub struct InstantClaim {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(name = "ticket")]
+    ticket_account: Pubkey,
+}
+
+impl Command for InstantClaim {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        //
+        info!("Using fee payer {}", self.fee_payer);
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+
+        // Sell the ticket to the liq-pool, paying the liquid-unstake fee
+        builder.instant_claim(
+            &marinade.state,
+            self.ticket_account,
+            self.fee_payer.as_keypair(), //ticket beneficiary
+            self.fee_payer.as_pubkey(),
+        );
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        Ok(())
+    }
+}
@@ -25,6 +25,12 @@ pub struct LiquidUnstake {
 
     #[structopt(name = "msol_amount")]
     msol_amount: f64,
+
+    #[structopt(long, help = "Referral code of the partner routing the unstake")]
+    referral_code: Option<String>,
+
+    #[structopt(long, default_value = "0", help = "Fail if receiving less SOL")]
+    min_sol_out: f64,
 }
 
 impl Command for LiquidUnstake {
@@ -46,13 +52,29 @@ impl Command for LiquidUnstake {
             bail!("Can not find user mSOL account {}", user_msol_account);
         }
 
-        builder.liquid_unstake(
-            &marinade.state,
-            user_msol_account,
-            self.fee_payer.as_keypair(),
-            self.fee_payer.as_pubkey(),
-            sol_to_lamports(self.msol_amount),
-        );
+        if let Some(referral_code) = &self.referral_code {
+            let (referral_state, referral) = marinade.find_referral(referral_code)?;
+            info!("Using referral {} ({})", referral_code, referral_state);
+            builder.liquid_unstake_with_referral(
+                &marinade.state,
+                user_msol_account,
+                self.fee_payer.as_keypair(),
+                self.fee_payer.as_pubkey(),
+                sol_to_lamports(self.msol_amount),
+                sol_to_lamports(self.min_sol_out),
+                referral_state,
+                referral.msol_token_account,
+            );
+        } else {
+            builder.liquid_unstake_with_min_out(
+                &marinade.state,
+                user_msol_account,
+                self.fee_payer.as_keypair(),
+                self.fee_payer.as_pubkey(),
+                sol_to_lamports(self.msol_amount),
+                sol_to_lamports(self.min_sol_out),
+            );
+        }
 
         marinade
             .client
@@ -17,20 +17,30 @@ use cli_common::solana_sdk::commitment_config::CommitmentConfig;
 use structopt::StructOpt;
 
 pub mod add_remove_liquidity;
+pub mod change_beneficiary;
 pub mod claim;
 pub mod deposit_stake_account;
+pub mod finish_stake_deposit;
+pub mod instant_claim;
 pub mod liquid_unstake;
 pub mod order_unstake;
 pub mod show;
+pub mod split_ticket;
 pub mod stake;
+pub mod withdraw_stake_account;
 
 use add_remove_liquidity::*;
+use change_beneficiary::ChangeBeneficiary;
 use claim::Claim;
 use deposit_stake_account::DepositStakeAccount;
+use finish_stake_deposit::FinishStakeDeposit;
+use instant_claim::InstantClaim;
 use liquid_unstake::LiquidUnstake;
 use order_unstake::OrderUnstake;
 use show::Show;
+use split_ticket::SplitTicket;
 use stake::Stake;
+use withdraw_stake_account::WithdrawStakeAccount;
 
 #[derive(Debug, StructOpt)]
 pub struct Common {
@@ -61,10 +71,16 @@ enum SmartPoolCommand {
     Stake,
     LiquidUnstake,
     AddLiquidity,
+    AddLiquidityMsol,
     RemoveLiquidity,
     DepositStakeAccount,
+    FinishStakeDeposit,
     OrderUnstake,
     Claim,
+    SplitTicket,
+    ChangeBeneficiary,
+    InstantClaim,
+    WithdrawStakeAccount,
 }
 
 #[derive(Debug, StructOpt)]
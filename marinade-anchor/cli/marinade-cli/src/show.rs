@@ -9,6 +9,7 @@ use cli_common::marinade_finance::{
     calc::proportional,
     liq_pool::LiqPoolHelpers,
     located::Located,
+    queued_change::ParamsChange,
     state::{State, StateHelpers},
     ticket_account::TicketAccountData,
 };
@@ -148,6 +149,70 @@ impl Command for Show {
                 lamports_to_sol(marinade.state.liq_pool.liquidity_sol_cap)
             );
         }
+        if marinade.state.liq_pool_epoch_outflow_cap > 0 {
+            info!(
+                "Liquidity epoch outflow CAPPED {} SOL, {} SOL went out in epoch {}",
+                lamports_to_sol(marinade.state.liq_pool_epoch_outflow_cap),
+                lamports_to_sol(marinade.state.liq_pool_epoch_outflow),
+                marinade.state.liq_pool_outflow_epoch
+            );
+        }
+        // show paused operations if any
+        if marinade.state.paused_operations != 0 {
+            println!(
+                "PAUSED operations: {}",
+                State::PAUSE_OPERATIONS
+                    .iter()
+                    .filter(|(flag, _)| marinade.state.paused_operations & flag != 0)
+                    .map(|(_, name)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        // show authorities waiting to be accepted
+        for (name, pending) in &[
+            ("admin authority", marinade.state.pending_admin_authority),
+            (
+                "validator manager authority",
+                marinade.state.pending_validator_manager_authority,
+            ),
+            (
+                "operational sol account",
+                marinade.state.pending_operational_sol_account,
+            ),
+        ] {
+            if *pending != Pubkey::default() {
+                println!("PENDING {}: {}", name, pending);
+            }
+        }
+        // show queued params changes if any
+        if marinade.state.params_change_delay_epochs > 0 {
+            println!(
+                "Params changes timelocked for {} epochs",
+                marinade.state.params_change_delay_epochs
+            );
+        }
+        for (address, queued_change) in marinade.queued_changes()? {
+            let change = match queued_change.change {
+                ParamsChange::ConfigMarinade(params) => format!("{:?}", params),
+                ParamsChange::SetLpParams {
+                    min_fee,
+                    max_fee,
+                    liquidity_target,
+                    fee_curve,
+                } => format!(
+                    "SetLpParams min_fee {}, max_fee {}, liquidity_target {} SOL, fee_curve {}",
+                    min_fee,
+                    max_fee,
+                    lamports_to_sol(liquidity_target),
+                    fee_curve
+                ),
+            };
+            println!(
+                "QUEUED change {} from epoch {}: {}",
+                address, queued_change.earliest_execution_epoch, change
+            );
+        }
         debug!(
             "slots_for_stake_delta {}, {} mins approx",
             marinade.state.stake_system.slots_for_stake_delta,
@@ -182,6 +247,15 @@ impl Command for Show {
             lamports_to_sol(marinade.state.stake_system.min_stake)
         );
         println!("reward_fee {}", marinade.state.reward_fee);
+        println!(
+            "withdraw_stake_account_fee {}",
+            marinade.state.withdraw_stake_account_fee
+        );
+        if marinade.state.max_commission > 0 {
+            println!("max_commission {}%", marinade.state.max_commission);
+        } else {
+            println!("max_commission none");
+        }
 
         println!(
             "mSOL supply {}",
@@ -203,6 +277,18 @@ impl Command for Show {
             marinade.state.msol_mint,
             marinade.state.msol_mint_authority()
         );
+        if let Some(epoch_rewards) = marinade.epoch_rewards()? {
+            for record in epoch_rewards.iter().rev().take(3) {
+                println!(
+                    "epoch {} rewards {} SOL treasury fees {} mSOL price {} SOL under control {} SOL",
+                    record.epoch,
+                    lamports_to_sol(record.rewards),
+                    lamports_to_sol(record.msol_fees),
+                    record.msol_price as f64 / State::PRICE_DENOMINATOR as f64,
+                    lamports_to_sol(record.total_lamports_under_control)
+                );
+            }
+        }
 
         println!("-- Liq-Pool ---------------");
         println!(
@@ -236,14 +322,22 @@ impl Command for Show {
             lamports_to_sol(sol_leg_account.lamports),
             sol_leg_pubkey
         );
+        println!(
+            "Tickets receivable {} SOL", //bought by InstantClaim, not claimed yet
+            lamports_to_sol(marinade.state.liq_pool_tickets_receivable)
+        );
         println!(
             "Liquidity Target: {}",
             lamports_to_sol(marinade.state.liq_pool.lp_liquidity_target)
         );
         println!(
             "Current-fee: {}",
-            marinade.state.liq_pool.linear_fee(sol_leg_account.lamports)
+            marinade
+                .state
+                .liq_pool
+                .fee(&marinade.state.liq_pool_fee_curve, sol_leg_account.lamports)
         );
+        println!("Fee curve: {}", marinade.state.liq_pool_fee_curve);
         println!(
             "Min-Max-Fee: {}-{}",
             marinade.state.liq_pool.lp_min_fee, marinade.state.liq_pool.lp_max_fee
@@ -540,6 +634,14 @@ impl Command for Show {
                 "Total staked: {} SOL",
                 lamports_to_sol(marinade.state.validator_system.total_active_balance),
             );
+            println!(
+                "Directed stake waiting: {} SOL",
+                lamports_to_sol(marinade.state.total_directed_stake),
+            );
+            println!(
+                "Validator stake floors: {} SOL",
+                lamports_to_sol(marinade.state.total_min_stake),
+            );
             println!(
                 "List account: {} with {}/{} validators",
                 marinade.state.validator_system.validator_list_address(),
@@ -563,7 +665,10 @@ impl Command for Show {
                     })
                     .collect();
 
-                if !self.list_all_validators && validator.active_balance == 0 {
+                if !self.list_all_validators
+                    && validator.active_balance == 0
+                    && validator.directed_stake_lamports == 0
+                {
                     continue;
                 }
 
@@ -579,6 +684,23 @@ impl Command for Show {
                         / marinade.state.validator_system.total_validator_score as f32,
                     validator_stakes.len()
                 );
+                if validator.min_stake > 0 || validator.max_stake > 0 {
+                    println!(
+                        "  stake bounds: min {} SOL, max {}",
+                        lamports_to_sol(validator.min_stake),
+                        if validator.max_stake > 0 {
+                            format!("{} SOL", lamports_to_sol(validator.max_stake))
+                        } else {
+                            "none".into()
+                        }
+                    );
+                }
+                if validator.directed_stake_lamports > 0 {
+                    println!(
+                        "  directed stake waiting: {} SOL",
+                        lamports_to_sol(validator.directed_stake_lamports)
+                    );
+                }
 
                 let mut validator_has_active_stake = false;
                 for stake in validator_stakes {
@@ -701,6 +823,14 @@ impl Command for Show {
                 "Total staked: {} SOL",
                 lamports_to_sol(marinade.state.validator_system.total_active_balance),
             );
+            println!(
+                "Directed stake waiting: {} SOL",
+                lamports_to_sol(marinade.state.total_directed_stake),
+            );
+            println!(
+                "Validator stake floors: {} SOL",
+                lamports_to_sol(marinade.state.total_min_stake),
+            );
             println!(
                 "List account: {} with {}/{} validators",
                 marinade.state.validator_system.validator_list_address(),
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use cli_common::{
+    instruction_helpers::InstructionHelpers, marinade_finance::ticket_account::TicketAccountData,
+    rpc_client_helpers::RpcClientHelpers, rpc_marinade::RpcMarinade,
+    transaction_builder::TransactionBuilder, InputKeypair,
+};
+use log::info;
+
+use std::sync::Arc;
+
+use cli_common::solana_sdk::{
+    native_token::sol_to_lamports,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar::rent,
+};
+use structopt::StructOpt;
+
+use crate::Command;
+
+use super::Common;
+
+#[derive(Debug, StructOpt)]
+pub struct SplitTicket {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(name = "ticket")]
+    ticket_account: Pubkey,
+
+    #[structopt(name = "sol_amount", help = "SOL amount to move into the new ticket")]
+    sol_amount: f64,
+}
+
+impl Command for SplitTicket {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        //
+        info!("Using fee payer {}", self.fee_payer);
+
+        let rent: Rent = bincode::deserialize(&marinade.client.get_account_data(&rent::id())?)?;
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+
+        // Create a empty ticket account (transfer rent-exempt lamports)
+        const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+        let new_ticket_account = Arc::new(Keypair::new());
+        let new_ticket_address = new_ticket_account.pubkey();
+        builder
+            .create_account(
+                new_ticket_account,
+                TICKET_ACCOUNT_SPACE,
+                &cli_common::marinade_finance::ID,
+                &rent,
+                "ticket-account",
+            )
+            .unwrap();
+
+        builder.split_ticket(
+            &marinade.state,
+            self.ticket_account,
+            self.fee_payer.as_keypair(), //ticket beneficiary
+            new_ticket_address,
+            sol_to_lamports(self.sol_amount),
+        );
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        info!("New ticket {} created", new_ticket_address);
+
+        Ok(())
+    }
+}
@@ -7,7 +7,8 @@ use cli_common::{
 };
 use log::info;
 
-use cli_common::solana_sdk::native_token::sol_to_lamports;
+use anyhow::anyhow;
+use cli_common::solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey};
 
 use structopt::StructOpt;
 
@@ -24,6 +25,26 @@ pub struct Stake {
 
     #[structopt(name = "amount")]
     amount: f64,
+
+    #[structopt(
+        long = "validator",
+        help = "Vote account of the validator to direct the new stake to"
+    )]
+    validator: Option<Pubkey>,
+
+    #[structopt(
+        long,
+        conflicts_with = "validator",
+        help = "Referral code of the partner routing the deposit"
+    )]
+    referral_code: Option<String>,
+
+    #[structopt(
+        long,
+        default_value = "0",
+        help = "Fail if receiving less mSOL"
+    )]
+    min_msol_out: f64,
 }
 
 impl Command for Stake {
@@ -41,12 +62,45 @@ impl Command for Stake {
             "user mSOL",
         )?;
 
-        builder.deposit(
-            &marinade.state,
-            self.fee_payer.as_keypair(), // TODO: choose different keypair from command line arg
-            user_msol_account,
-            sol_to_lamports(self.amount),
-        );
+        if let Some(validator_vote) = self.validator {
+            let (validator_list, _) = marinade.validator_list()?;
+            let validator_index = validator_list
+                .iter()
+                .position(|validator| validator.validator_account == validator_vote)
+                .ok_or_else(|| anyhow!("Validator {} is not in marinade list", validator_vote))?;
+            info!(
+                "Direct stake to validator {} index {}",
+                validator_vote, validator_index
+            );
+            builder.deposit_directed(
+                &marinade.state,
+                self.fee_payer.as_keypair(), // TODO: choose different keypair from command line arg
+                user_msol_account,
+                sol_to_lamports(self.amount),
+                sol_to_lamports(self.min_msol_out),
+                validator_index as u32,
+                validator_vote,
+            );
+        } else if let Some(referral_code) = &self.referral_code {
+            let (referral_state, _) = marinade.find_referral(referral_code)?;
+            info!("Using referral {} ({})", referral_code, referral_state);
+            builder.deposit_with_referral(
+                &marinade.state,
+                self.fee_payer.as_keypair(), // TODO: choose different keypair from command line arg
+                user_msol_account,
+                sol_to_lamports(self.amount),
+                sol_to_lamports(self.min_msol_out),
+                referral_state,
+            );
+        } else {
+            builder.deposit_with_min_out(
+                &marinade.state,
+                self.fee_payer.as_keypair(), // TODO: choose different keypair from command line arg
+                user_msol_account,
+                sol_to_lamports(self.amount),
+                sol_to_lamports(self.min_msol_out),
+            );
+        }
 
         marinade
             .client
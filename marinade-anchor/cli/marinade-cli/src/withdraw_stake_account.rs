@@ -0,0 +1,113 @@
+use anyhow::{anyhow, bail, Result};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder, InputKeypair,
+};
+use log::{error, info};
+
+use cli_common::spl_associated_token_account::get_associated_token_address;
+
+use std::sync::Arc;
+
+use cli_common::solana_sdk::{
+    native_token::{lamports_to_sol, sol_to_lamports},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use structopt::StructOpt;
+
+use crate::Command;
+
+use super::Common;
+
+#[derive(Debug, StructOpt)]
+pub struct WithdrawStakeAccount {
+    #[structopt(
+        short = "f",
+        env = "FEE_PAYER",
+        default_value = "~/.config/solana/id.json"
+    )]
+    fee_payer: InputKeypair,
+
+    #[structopt(name = "msol_amount")]
+    msol_amount: f64,
+
+    #[structopt(
+        long,
+        help = "marinade stake account to split from (the biggest active one by default)"
+    )]
+    from_stake: Option<Pubkey>,
+}
+
+impl Command for WithdrawStakeAccount {
+    fn process(self, _common: Common, marinade: RpcMarinade) -> Result<()> {
+        info!("Using fee payer {}", self.fee_payer);
+
+        // find the associated (canonical) msol token account for the user
+        let user_msol_account =
+            get_associated_token_address(&self.fee_payer.as_pubkey(), &marinade.state.msol_mint);
+        if marinade
+            .client
+            .get_account_retrying(&user_msol_account)?
+            .is_none()
+        {
+            error!("Can not find user mSOL account {}", user_msol_account);
+            bail!("Can not find user mSOL account {}", user_msol_account);
+        }
+
+        let (stakes, _) = marinade.stakes_info()?;
+        let (validators, _) = marinade.validator_list()?;
+        let stake = stakes
+            .iter()
+            .filter(|stake| {
+                self.from_stake
+                    .map_or(true, |from_stake| stake.record.stake_account == from_stake)
+            })
+            .filter(|stake| {
+                stake.stake.delegation().map_or(false, |delegation| {
+                    delegation.deactivation_epoch == std::u64::MAX
+                })
+            })
+            .max_by_key(|stake| stake.record.last_update_delegated_lamports)
+            .ok_or_else(|| anyhow!("Can not find active marinade stake account to split"))?;
+        let voter = stake.stake.delegation().unwrap().voter_pubkey;
+        let validator_index = validators
+            .iter()
+            .position(|validator| validator.validator_account == voter)
+            .ok_or_else(|| anyhow!("Can not find validator {} in marinade list", voter))?;
+        info!(
+            "Splitting from stake {} with {} SOL delegated to {}",
+            stake.record.stake_account,
+            lamports_to_sol(stake.record.last_update_delegated_lamports),
+            voter
+        );
+
+        let mut builder = TransactionBuilder::limited(self.fee_payer.as_keypair());
+
+        let split_stake_account = Arc::new(Keypair::new());
+        let split_stake_address = split_stake_account.pubkey();
+        builder.withdraw_stake_account(
+            &marinade.state,
+            user_msol_account,
+            self.fee_payer.as_keypair(),
+            stake.record.stake_account,
+            split_stake_account,
+            self.fee_payer.as_keypair(), // rent payer
+            stake.index,
+            validator_index as u32,
+            sol_to_lamports(self.msol_amount),
+        );
+
+        marinade
+            .client
+            .execute_transaction_sequence(builder.combined_sequence())?;
+
+        info!(
+            "Stake account {} is yours now, authorized to {}",
+            split_stake_address,
+            self.fee_payer.as_pubkey()
+        );
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::Common;
 
 use anyhow::bail;
+use cli_common::marinade_finance::{validator_system::ValidatorSystem, ID};
 use cli_common::solana_client::rpc_client::RpcClient;
 use cli_common::solana_sdk::{pubkey::Pubkey, signature::Signer, system_program};
 use cli_common::{
@@ -79,6 +80,15 @@ impl AddValidatorOptions {
                     warn!("Validator {} is already added", key);
                 }
             } else {
+                let blocked_flag = ValidatorSystem::find_blocked_flag(&marinade.state.key, &key).0;
+                if marinade
+                    .client
+                    .get_account_retrying(&blocked_flag)?
+                    .map_or(false, |account| account.owner == ID)
+                {
+                    warn!("Validator {} is blocked", key);
+                    return Ok(false);
+                }
                 if current_validators.len() + added_validator_count >= max_validators as usize {
                     warn!(
                         "Can not add validator {} because max validator count is reached",
@@ -0,0 +1,120 @@
+use crate::Common;
+
+use anyhow::bail;
+use cli_common::marinade_finance::{validator_system::ValidatorSystem, ID};
+use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::{pubkey::Pubkey, signature::Signer, system_program};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
+};
+use log::{error, info, warn};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct BlockValidatorsOptions {
+    validator_votes: Vec<Pubkey>,
+
+    #[structopt(long = "unblock", help = "Remove the validators from the blocklist")]
+    unblock: bool,
+
+    #[structopt(
+        long = "rent-receiver",
+        help = "Receives the blocklist entry rent on unblock (fee payer by default)"
+    )]
+    rent_receiver: Option<Pubkey>,
+}
+
+impl BlockValidatorsOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> anyhow::Result<()> {
+        let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+
+        let rent_payer = if let Some(rent_payer) = common.rent_payer {
+            info!("Use rent payer = {}", rent_payer);
+            rent_payer.as_keypair()
+        } else {
+            info!("Use fee payer as rent payer");
+            common.fee_payer.as_keypair()
+        };
+        if let Some(account) = marinade.client.get_account_retrying(&rent_payer.pubkey())? {
+            if account.owner != system_program::ID {
+                error!(
+                    "Rent payer {} must be a system account",
+                    rent_payer.pubkey()
+                );
+                bail!(
+                    "Rent payer {} must be a system account",
+                    rent_payer.pubkey()
+                );
+            }
+        }
+        let rent_receiver = self
+            .rent_receiver
+            .unwrap_or_else(|| common.fee_payer.as_pubkey());
+
+        let validator_manager_authority =
+            if let Some(validator_manager_authority) = common.validator_manager_authority {
+                info!(
+                    "Using validator manager authority {}",
+                    validator_manager_authority
+                );
+                validator_manager_authority.as_keypair()
+            } else {
+                info!("Using fee payer as validator manager authority");
+                common.fee_payer.as_keypair()
+            };
+
+        let (current_validators, _) = marinade.validator_list()?;
+
+        for validator_vote in self.validator_votes {
+            let blocked_flag =
+                ValidatorSystem::find_blocked_flag(&marinade.state.key, &validator_vote).0;
+            let is_blocked = marinade
+                .client
+                .get_account_retrying(&blocked_flag)?
+                .map_or(false, |account| account.owner == ID);
+            if self.unblock {
+                if !is_blocked {
+                    warn!("Validator {} is not blocked", validator_vote);
+                    continue;
+                }
+                info!("Unblock validator {}", validator_vote);
+                builder.unblock_validator(
+                    &marinade.state,
+                    validator_manager_authority.clone(),
+                    validator_vote,
+                    rent_receiver,
+                )?;
+            } else {
+                if is_blocked {
+                    warn!("Validator {} is already blocked", validator_vote);
+                    continue;
+                }
+                if current_validators
+                    .iter()
+                    .any(|validator| validator.validator_account == validator_vote)
+                {
+                    warn!(
+                        "Validator {} is in the list. Use remove-validators to remove it",
+                        validator_vote
+                    );
+                }
+                info!("Block validator {}", validator_vote);
+                builder.block_validator(
+                    &marinade.state,
+                    validator_manager_authority.clone(),
+                    validator_vote,
+                    rent_payer.clone(),
+                )?;
+            }
+        }
+
+        marinade
+            .client
+            .process_transaction_sequence(common.simulate, builder.combined_sequence())?;
+        Ok(())
+    }
+}
@@ -14,15 +14,19 @@ use std::{str::FromStr, sync::Arc};
 use structopt::StructOpt;
 
 pub mod add_validator;
+pub mod block_validators;
 pub mod config_validator_system;
 pub mod emergency_unstake;
 pub mod remove_validators;
+pub mod set_stake_bounds;
 pub mod update_scores;
 
 use add_validator::AddValidatorOptions;
+use block_validators::BlockValidatorsOptions;
 use config_validator_system::ConfigValidatorsOptions;
 use emergency_unstake::EmergencyUnstakeOptions;
 use remove_validators::RemoveValidatorsOptions;
+use set_stake_bounds::SetStakeBoundsOptions;
 use update_scores::UpdateScoresOptions;
 
 #[derive(Debug, StructOpt)]
@@ -73,6 +77,8 @@ enum MardminCommand {
     UpdateScores(UpdateScoresOptions),
     ConfigValidators(ConfigValidatorsOptions),
     EmergencyUnstake(EmergencyUnstakeOptions),
+    SetStakeBounds(SetStakeBoundsOptions),
+    BlockValidators(BlockValidatorsOptions),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -123,5 +129,7 @@ fn main() -> anyhow::Result<()> {
         MardminCommand::UpdateScores(options) => options.process(params.common, client, cluster),
         MardminCommand::ConfigValidators(options) => options.process(params.common, client),
         MardminCommand::EmergencyUnstake(options) => options.process(params.common, client),
+        MardminCommand::SetStakeBounds(options) => options.process(params.common, client),
+        MardminCommand::BlockValidators(options) => options.process(params.common, client),
     }?)
 }
@@ -0,0 +1,81 @@
+use crate::Common;
+
+use anyhow::anyhow;
+use cli_common::solana_client::rpc_client::RpcClient;
+use cli_common::solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey};
+use cli_common::{
+    instruction_helpers::InstructionHelpers, rpc_client_helpers::RpcClientHelpers,
+    rpc_marinade::RpcMarinade, transaction_builder::TransactionBuilder,
+};
+use log::{error, info};
+
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub struct SetStakeBoundsOptions {
+    validator_vote: Pubkey,
+
+    #[structopt(
+        long = "min-stake",
+        default_value = "0",
+        help = "Min SOL staked into the validator (0 = no floor)"
+    )]
+    min_stake: f64,
+
+    #[structopt(
+        long = "max-stake",
+        default_value = "0",
+        help = "Max SOL staked into the validator (0 = no cap)"
+    )]
+    max_stake: f64,
+}
+
+impl SetStakeBoundsOptions {
+    pub fn process(self, common: Common, client: Arc<RpcClient>) -> anyhow::Result<()> {
+        let mut builder = TransactionBuilder::limited(common.fee_payer.as_keypair());
+
+        let marinade = RpcMarinade::new(client, &common.instance.as_pubkey())?;
+
+        let validator_manager_authority =
+            if let Some(validator_manager_authority) = common.validator_manager_authority {
+                info!(
+                    "Using validator manager authority {}",
+                    validator_manager_authority
+                );
+                validator_manager_authority.as_keypair()
+            } else {
+                info!("Using fee payer as validator manager authority");
+                common.fee_payer.as_keypair()
+            };
+        let (validator_list, _) = marinade.validator_list()?;
+        let validator_index = validator_list
+            .iter()
+            .position(|validator| validator.validator_account == self.validator_vote)
+            .ok_or_else(|| {
+                error!("Unknown validator {}", self.validator_vote);
+                anyhow!("Unknown validator {}", self.validator_vote)
+            })?;
+
+        info!(
+            "Set validator {} stake bounds min {} SOL max {} SOL",
+            self.validator_vote, self.min_stake, self.max_stake
+        );
+        builder.set_validator_stake_bounds(
+            &marinade.state,
+            validator_manager_authority,
+            validator_index as u32,
+            self.validator_vote,
+            sol_to_lamports(self.min_stake),
+            sol_to_lamports(self.max_stake),
+        )?;
+
+        // send the tx
+        info!("sending transactions");
+        marinade
+            .client
+            .process_transaction_sequence(common.simulate, builder.combined_sequence())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::ID;
+
+pub mod create_epoch_rewards;
+
+/// Rewards accounted by the update instructions during one epoch.
+/// Zero-copy, must have no implicit padding
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EpochRewardRecord {
+    pub epoch: u64,
+    /// delegation growth of the updated stake accounts
+    pub rewards: u64,
+    /// mSOL minted to the treasury (reward_fee and extra lamports found in the stake accounts)
+    pub msol_fees: u64,
+    /// after the last update of the epoch
+    pub msol_price: u64,
+    pub total_lamports_under_control: u64,
+}
+
+unsafe impl Zeroable for EpochRewardRecord {}
+unsafe impl Pod for EpochRewardRecord {}
+
+/// Header of the epoch rewards account (a PDA of the state), one record per epoch
+/// in a ring buffer of CAPACITY records after it. Anyone can create it and then
+/// update_active/update_deactivated append to it
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EpochRewards {
+    pub state_address: Pubkey, // instance of marinade state this account belongs to
+    /// records written since the creation, the oldest ones are overwritten after CAPACITY
+    pub record_count: u64,
+}
+
+unsafe impl Zeroable for EpochRewards {}
+unsafe impl Pod for EpochRewards {}
+
+impl EpochRewards {
+    pub const DISCRIMINATOR: &'static [u8; 8] = b"epochrwd";
+    pub const SEED: &'static [u8] = b"epoch_rewards";
+    /// fits the 10KiB limit of accounts created by CPI
+    pub const CAPACITY: u64 = 250;
+    const RECORDS_START: usize = 8 + std::mem::size_of::<Self>();
+    pub const SPACE: usize =
+        Self::RECORDS_START + Self::CAPACITY as usize * std::mem::size_of::<EpochRewardRecord>();
+
+    pub fn find_address(state: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[&state.to_bytes()[..32], Self::SEED], &ID)
+    }
+
+    pub fn init(data: &mut [u8], state: &Pubkey) -> ProgramResult {
+        if data.len() != Self::SPACE {
+            msg!(
+                "Epoch rewards account must have {} bytes. Got {}",
+                Self::SPACE,
+                data.len()
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if data[0..8] != [0; 8] {
+            msg!("Epoch rewards account is already initialized");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        data[0..8].copy_from_slice(Self::DISCRIMINATOR);
+        Self {
+            state_address: *state,
+            record_count: 0,
+        }
+        .save(data);
+        Ok(())
+    }
+
+    pub fn load(data: &[u8], state: &Pubkey) -> Result<Self, ProgramError> {
+        if data.len() != Self::SPACE || &data[0..8] != Self::DISCRIMINATOR {
+            msg!("Wrong epoch rewards account");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut header = Self::zeroed();
+        bytemuck::bytes_of_mut(&mut header).copy_from_slice(&data[8..Self::RECORDS_START]);
+        if &header.state_address != state {
+            msg!(
+                "Epoch rewards has wrong marinade instance {}",
+                header.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(header)
+    }
+
+    fn save(&self, data: &mut [u8]) {
+        data[8..Self::RECORDS_START].copy_from_slice(bytemuck::bytes_of(self));
+    }
+
+    fn record_start(record_number: u64) -> usize {
+        Self::RECORDS_START
+            + (record_number % Self::CAPACITY) as usize * std::mem::size_of::<EpochRewardRecord>()
+    }
+
+    fn get(&self, data: &[u8], record_number: u64) -> EpochRewardRecord {
+        let start = Self::record_start(record_number);
+        let mut record = EpochRewardRecord::zeroed();
+        bytemuck::bytes_of_mut(&mut record)
+            .copy_from_slice(&data[start..start + std::mem::size_of::<EpochRewardRecord>()]);
+        record
+    }
+
+    fn set(&self, data: &mut [u8], record_number: u64, record: &EpochRewardRecord) {
+        let start = Self::record_start(record_number);
+        data[start..start + std::mem::size_of::<EpochRewardRecord>()]
+            .copy_from_slice(bytemuck::bytes_of(record));
+    }
+
+    /// kept records, the oldest first
+    pub fn records(&self, data: &[u8]) -> Vec<EpochRewardRecord> {
+        (self.record_count.saturating_sub(Self::CAPACITY)..self.record_count)
+            .map(|record_number| self.get(data, record_number))
+            .collect()
+    }
+
+    /// Adds the rewards of an update to the record of the epoch (starting a new one
+    /// in the first update of the epoch). Price and total are the values after the update
+    pub fn add(
+        &mut self,
+        data: &mut [u8],
+        epoch: u64,
+        rewards: u64,
+        msol_fees: u64,
+        msol_price: u64,
+        total_lamports_under_control: u64,
+    ) {
+        let last = if self.record_count > 0 {
+            Some(self.get(data, self.record_count - 1))
+        } else {
+            None
+        };
+        let mut record = match last {
+            Some(last) if last.epoch == epoch => last,
+            _ => {
+                self.record_count += 1;
+                EpochRewardRecord {
+                    epoch,
+                    ..EpochRewardRecord::default()
+                }
+            }
+        };
+        record.rewards = record.rewards.saturating_add(rewards);
+        record.msol_fees = record.msol_fees.saturating_add(msol_fees);
+        record.msol_price = msol_price;
+        record.total_lamports_under_control = total_lamports_under_control;
+        self.set(data, self.record_count - 1, &record);
+        self.save(data);
+    }
+}
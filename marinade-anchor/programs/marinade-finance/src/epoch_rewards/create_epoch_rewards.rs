@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{
+    checks::{check_address, check_owner_program},
+    create_pda_account,
+    epoch_rewards::EpochRewards,
+    CreateEpochRewards, ID,
+};
+
+impl<'info> CreateEpochRewards<'info> {
+    // fn create_epoch_rewards()
+    pub fn process(&mut self) -> ProgramResult {
+        let state_address = *self.state.to_account_info().key;
+        let (epoch_rewards, bump_seed) = EpochRewards::find_address(&state_address);
+        check_address(self.epoch_rewards.key, &epoch_rewards, "epoch_rewards")?;
+        check_owner_program(&self.epoch_rewards, &system_program::ID, "epoch_rewards")?;
+        check_owner_program(&self.rent_payer, &system_program::ID, "rent_payer")?;
+        let lamports = self.rent.minimum_balance(EpochRewards::SPACE);
+        if self.rent_payer.lamports() < lamports {
+            msg!("Rent payer must have at least {} lamports", lamports);
+            return Err(ProgramError::InsufficientFunds);
+        }
+        check_address(
+            self.system_program.key,
+            &system_program::ID,
+            "system_program",
+        )?;
+
+        msg!("Create epoch rewards {}", epoch_rewards);
+        // the address may have been funded in advance to prevent the creation
+        create_pda_account(
+            &self.rent_payer,
+            &self.epoch_rewards,
+            lamports,
+            EpochRewards::SPACE,
+            &ID,
+            &self.system_program,
+            &[
+                &state_address.to_bytes()[..32],
+                EpochRewards::SEED,
+                &[bump_seed],
+            ],
+        )?;
+        EpochRewards::init(&mut self.epoch_rewards.data.borrow_mut(), &state_address)
+    }
+}
@@ -42,7 +42,7 @@ pub enum CommonError {
 
     // #[error("B51A Wrong stake deposit authority {got}. Must be {expected}")]
     // WrongStakeDepositAuthority { got: Pubkey, expected: Pubkey },
-    #[msg("B3AA You can't deposit a stake-account with lockup")]
+    #[msg("B3AA You can't deposit a stake-account with lockup in force without the custodian signature")]
     AccountWithLockup = 45694,
 
     #[msg("2000 Number too low")]
@@ -68,9 +68,36 @@ pub enum CommonError {
     #[msg("1105 Wrong Ticket Beneficiary")]
     WrongBeneficiary = 4057,
 
+    #[msg("1106 Invalid fee curve")]
+    InvalidFeeCurve = 4058,
+
+    #[msg("1107 Params change is timelocked. Queue it")]
+    ParamsChangeTimelocked = 4059,
+
+    #[msg("1108 Queued change not due. Wait more epochs")]
+    QueuedChangeNotDue = 4060,
+
+    #[msg("1109 Slippage exceeded. Output is less than the requested minimum")]
+    SlippageExceeded = 4061,
+
+    #[msg("110A Liquidity pool outflow cap for this epoch reached. Wait for the next epoch")]
+    EpochOutflowCapReached = 4062,
+
+    #[msg("110B Liquidity pool SOL leg reached the liquidity target. Nothing to rebalance")]
+    LiquidityTargetReached = 4063,
+
+    #[msg("1200 Operation paused by admin")]
+    OperationPaused = 4308,
+
     #[msg("1199 Insufficient Liquidity in the Liquidity Pool")]
     InsufficientLiquidity = 4205,
 
     #[msg("BAD1 Invalid validator")]
     InvalidValidator = 47525,
+
+    #[msg("BAD2 Validator is blocked")]
+    ValidatorBlocked = 47526,
+
+    #[msg("BAD3 Validator commission is too high")]
+    ValidatorCommissionTooHigh = 47527,
 }
@@ -0,0 +1,216 @@
+//! Typed events logged by the state-changing instructions.
+//! Every event has the marinade instance, the stored mSOL price (State::PRICE_DENOMINATOR based)
+//! and the epoch. Decoders live in the offchain SDK (events module)
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::Sysvar as _;
+
+/// epoch for the instructions without the clock sysvar account
+pub fn current_epoch() -> Result<u64, ProgramError> {
+    Ok(Clock::get()?.epoch)
+}
+
+#[event]
+pub struct DepositEvent {
+    pub state: Pubkey,
+    pub sol_owner: Pubkey,
+    pub mint_to: Pubkey,
+    pub lamports: u64,
+    // part of the order filled from the liq-pool mSOL leg
+    pub msol_swapped: u64,
+    pub sol_swapped: u64,
+    // rest of the order goes to the reserve
+    pub msol_minted: u64,
+    // Pubkey::default() when not routed by an active referral
+    pub referral: Pubkey,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct DepositStakeAccountEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub mint_to: Pubkey,
+    pub delegated_lamports: u64,
+    // undelegated lamports moved to the reserve
+    pub extra_lamports: u64,
+    pub msol_minted: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct PendingStakeDepositEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub pending_deposit: Pubkey,
+    pub validator_vote: Pubkey,
+    pub mint_to: Pubkey,
+    // mSOL for it is minted by FinishStakeDeposit
+    pub delegated_lamports: u64,
+    pub extra_lamports: u64,
+    pub msol_minted: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct LiquidUnstakeEvent {
+    pub state: Pubkey,
+    pub msol_owner: Pubkey,
+    pub transfer_sol_to: Pubkey,
+    pub msol_amount: u64,
+    pub msol_fee: u64,
+    pub treasury_msol_cut: u64,
+    // Pubkey::default() when not routed by an active referral
+    pub referral: Pubkey,
+    // part of the treasury cut paid to the referral partner
+    pub referral_msol_cut: u64,
+    pub lamports: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct OrderUnstakeEvent {
+    pub state: Pubkey,
+    pub ticket: Pubkey,
+    pub beneficiary: Pubkey,
+    pub msol_burned: u64,
+    pub lamports: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct ClaimEvent {
+    pub state: Pubkey,
+    pub ticket: Pubkey,
+    pub beneficiary: Pubkey,
+    pub lamports: u64,
+    // left in the ticket after a partial claim
+    pub ticket_lamports_left: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct AddLiquidityEvent {
+    pub state: Pubkey,
+    pub sol_owner: Pubkey,
+    pub lamports: u64,
+    pub lp_minted: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct AddLiquidityMsolEvent {
+    pub state: Pubkey,
+    pub msol_owner: Pubkey,
+    pub msol_amount: u64,
+    pub msol_value: u64,
+    pub lp_minted: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct RemoveLiquidityEvent {
+    pub state: Pubkey,
+    pub lp_owner: Pubkey,
+    pub lp_burned: u64,
+    pub lamports_out: u64,
+    // part of the receivable share paid as a split of a pool ticket
+    pub ticket_lamports_out: u64,
+    pub msol_out: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct StakeReserveEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub lamports: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct DeactivateStakeEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    // Pubkey::default() when the whole stake account is deactivated
+    pub split_stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub lamports: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct MergeStakesEvent {
+    pub state: Pubkey,
+    pub destination_stake: Pubkey,
+    pub source_stake: Pubkey,
+    pub validator_vote: Pubkey,
+    pub destination_delegation: u64,
+    pub source_delegation: u64,
+    // source rent-exempt lamports delegated by the merge
+    pub extra_delegated: u64,
+    // source rent-exempt lamports moved to the operational sol account
+    pub returned_stake_rent: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct WithdrawStakeAccountEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub split_stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub beneficiary: Pubkey,
+    pub msol_burned: u64,
+    pub msol_fee: u64,
+    pub lamports: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct UpdateActiveEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub delegation_change: i64,
+    pub rewards: u64,
+    pub msol_fees: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+/// Delegated stake went down since the last update. The loss is shared by all mSOL holders
+#[event]
+pub struct SlashedEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub slashed: u64,
+    pub old_msol_price: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
+
+#[event]
+pub struct UpdateDeactivatedEvent {
+    pub state: Pubkey,
+    pub stake_account: Pubkey,
+    pub lamports_to_reserve: u64,
+    pub operational_sol_account_fee: u64,
+    pub msol_price: u64,
+    pub epoch: u64,
+}
@@ -1,8 +1,16 @@
 #![cfg_attr(not(debug_assertions), deny(warnings))]
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::{invoke, invoke_signed},
+    system_instruction,
+};
 use anchor_spl::token::{Mint, TokenAccount};
 use error::CommonError;
+use liq_pool::FeeCurve;
+use pending_stake_deposit::PendingStakeDepositData;
+use queued_change::{ParamsChange, QueuedChangeData};
+use referral::ReferralStateData;
 use stake_wrapper::StakeWrapper;
 use std::{
     convert::{TryFrom, TryInto},
@@ -14,10 +22,15 @@ use ticket_account::TicketAccountData;
 
 pub mod calc;
 pub mod checks;
+pub mod epoch_rewards;
 pub mod error;
+pub mod events;
 pub mod liq_pool;
 pub mod list;
 pub mod located;
+pub mod pending_stake_deposit;
+pub mod queued_change;
+pub mod referral;
 pub mod stake_system;
 pub mod stake_wrapper;
 pub mod state;
@@ -52,7 +65,43 @@ fn test_id() {
     assert!(check_id(&id()));
 }
 
+/// Creates the PDA account owned by owner. Unlike system_instruction::create_account
+/// it works when somebody has already sent lamports to the address:
+/// the balance is topped up to lamports, then space is allocated and the owner is assigned
+pub fn create_pda_account<'info>(
+    rent_payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    lamports: u64,
+    space: usize,
+    owner: &Pubkey,
+    system_program: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let missing_lamports = lamports.saturating_sub(account.lamports());
+    if missing_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(rent_payer.key, account.key, missing_lamports),
+            &[
+                system_program.clone(),
+                rent_payer.clone(),
+                account.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[system_program.clone(), account.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[system_program.clone(), account.clone()],
+        &[seeds],
+    )
+}
+
 pub const MAX_REWARD_FEE: u32 = 1_000; //basis points, 10% max reward fee
+pub const MAX_WITHDRAW_STAKE_ACCOUNT_FEE: u32 = 1_000; //basis points, 10% max withdraw stake account fee
 
 fn check_context<T>(ctx: &Context<T>) -> ProgramResult {
     if !check_id(ctx.program_id) {
@@ -66,6 +115,64 @@ fn check_context<T>(ctx: &Context<T>) -> ProgramResult {
     Ok(())
 }
 
+/// same as check_context, but also accepts the optional referral accounts (see ReferralStateData)
+fn check_context_with_referral<T>(ctx: &Context<T>, referral_accounts: usize) -> ProgramResult {
+    if !check_id(ctx.program_id) {
+        return Err(CommonError::InvalidProgramId.into());
+    }
+    let remaining_accounts = ctx.remaining_accounts.len();
+    if remaining_accounts != 0 && remaining_accounts != referral_accounts {
+        msg!(
+            "Expected {} referral accounts, got {}",
+            referral_accounts,
+            remaining_accounts
+        );
+        return Err(CommonError::UnexpectedAccount.into());
+    }
+
+    Ok(())
+}
+
+/// same as check_context, but also accepts the optional pool ticket accounts of RemoveLiquidity
+fn check_context_with_pool_ticket<T>(ctx: &Context<T>) -> ProgramResult {
+    if !check_id(ctx.program_id) {
+        return Err(CommonError::InvalidProgramId.into());
+    }
+    let remaining_accounts = ctx.remaining_accounts.len();
+    if remaining_accounts != 0 && remaining_accounts != 2 {
+        msg!(
+            "Expected pool ticket and new ticket accounts, got {} accounts",
+            remaining_accounts
+        );
+        return Err(CommonError::UnexpectedAccount.into());
+    }
+
+    Ok(())
+}
+
+/// same as check_context, but also accepts the stake accounts of a batch update
+/// (all but the first one, which is passed as stake_account)
+fn check_context_with_batch<T>(ctx: &Context<T>, batch_len: usize) -> ProgramResult {
+    if !check_id(ctx.program_id) {
+        return Err(CommonError::InvalidProgramId.into());
+    }
+    if batch_len == 0 {
+        msg!("Empty batch");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let remaining_accounts = ctx.remaining_accounts.len();
+    if remaining_accounts != batch_len - 1 {
+        msg!(
+            "Expected {} more stake accounts, got {}",
+            batch_len - 1,
+            remaining_accounts
+        );
+        return Err(CommonError::UnexpectedAccount.into());
+    }
+
+    Ok(())
+}
+
 //-----------------------------------------------------
 #[program]
 pub mod marinade_finance {
@@ -95,6 +202,19 @@ pub mod marinade_finance {
         ctx.accounts.process(data)
     }
 
+    pub fn propose_authority(
+        ctx: Context<ProposeAuthority>,
+        data: ProposeAuthorityData,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(data)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     pub fn add_validator(ctx: Context<AddValidator>, score: u32) -> ProgramResult {
         check_context(&ctx)?;
         ctx.accounts.process(score)
@@ -119,6 +239,18 @@ pub mod marinade_finance {
         ctx.accounts.process(index, validator_vote, score)
     }
 
+    pub fn set_validator_stake_bounds(
+        ctx: Context<SetValidatorStakeBounds>,
+        index: u32,
+        validator_vote: Pubkey,
+        min_stake: u64,
+        max_stake: u64,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts
+            .process(index, validator_vote, min_stake, max_stake)
+    }
+
     pub fn config_validator_system(
         ctx: Context<ConfigValidatorSystem>,
         extra_runs: u32,
@@ -127,10 +259,47 @@ pub mod marinade_finance {
         ctx.accounts.process(extra_runs)
     }
 
+    /// Blocked validators can not be added by add_validator or auto-added by deposit_stake_account
+    pub fn block_validator(ctx: Context<BlockValidator>, validator_vote: Pubkey) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_vote)
+    }
+
+    pub fn unblock_validator(
+        ctx: Context<UnblockValidator>,
+        validator_vote: Pubkey,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_vote)
+    }
+
     // deposit AKA stake, AKA deposit_sol
     pub fn deposit(ctx: Context<Deposit>, lamports: u64) -> ProgramResult {
+        check_context_with_referral(&ctx, 1)?;
+        ctx.accounts
+            .process(lamports, 0, ctx.remaining_accounts.first())
+    }
+
+    // deposit failing when it mints less than min_msol_out
+    pub fn deposit_with_min_out(
+        ctx: Context<Deposit>,
+        lamports: u64,
+        min_msol_out: u64,
+    ) -> ProgramResult {
+        check_context_with_referral(&ctx, 1)?;
+        ctx.accounts
+            .process(lamports, min_msol_out, ctx.remaining_accounts.first())
+    }
+
+    pub fn deposit_directed(
+        ctx: Context<DepositDirected>,
+        lamports: u64,
+        min_msol_out: u64,
+        validator_index: u32,
+    ) -> ProgramResult {
         check_context(&ctx)?;
-        ctx.accounts.process(lamports)
+        ctx.accounts
+            .process(lamports, min_msol_out, validator_index)
     }
 
     // SPL stake pool like
@@ -142,9 +311,36 @@ pub mod marinade_finance {
         ctx.accounts.process(validator_index)
     }
 
-    pub fn liquid_unstake(ctx: Context<LiquidUnstake>, msol_amount: u64) -> ProgramResult {
+    pub fn deposit_activating_stake_account(
+        ctx: Context<DepositActivatingStakeAccount>,
+        validator_index: u32,
+    ) -> ProgramResult {
         check_context(&ctx)?;
-        ctx.accounts.process(msol_amount)
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn finish_stake_deposit(
+        ctx: Context<FinishStakeDeposit>,
+        validator_index: u32,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_index)
+    }
+
+    pub fn liquid_unstake(ctx: Context<LiquidUnstake>, msol_amount: u64) -> ProgramResult {
+        check_context_with_referral(&ctx, 2)?;
+        ctx.accounts.process(msol_amount, 0, ctx.remaining_accounts)
+    }
+
+    // liquid_unstake failing when it pays less than min_lamports_out
+    pub fn liquid_unstake_with_min_out(
+        ctx: Context<LiquidUnstake>,
+        msol_amount: u64,
+        min_lamports_out: u64,
+    ) -> ProgramResult {
+        check_context_with_referral(&ctx, 2)?;
+        ctx.accounts
+            .process(msol_amount, min_lamports_out, ctx.remaining_accounts)
     }
 
     pub fn add_liquidity(ctx: Context<AddLiquidity>, lamports: u64) -> ProgramResult {
@@ -152,9 +348,21 @@ pub mod marinade_finance {
         ctx.accounts.process(lamports)
     }
 
+    pub fn add_liquidity_msol(ctx: Context<AddLiquidityMsol>, msol_amount: u64) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(msol_amount)
+    }
+
+    /// optional remaining accounts: a ticket owned by the SOL leg and a new ticket account.
+    /// Needed when the SOL leg can not pay the LP share of the tickets receivable
     pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, tokens: u64) -> ProgramResult {
+        check_context_with_pool_ticket(&ctx)?;
+        ctx.accounts.process(tokens, ctx.remaining_accounts)
+    }
+
+    pub fn rebalance_liq_pool(ctx: Context<RebalanceLiqPool>, msol_amount: u64) -> ProgramResult {
         check_context(&ctx)?;
-        ctx.accounts.process(tokens)
+        ctx.accounts.process(msol_amount)
     }
 
     pub fn set_lp_params(
@@ -162,9 +370,11 @@ pub mod marinade_finance {
         min_fee: Fee,
         max_fee: Fee,
         liquidity_target: u64,
+        fee_curve: FeeCurve,
     ) -> ProgramResult {
         check_context(&ctx)?;
-        ctx.accounts.process(min_fee, max_fee, liquidity_target)
+        ctx.accounts
+            .process(min_fee, max_fee, liquidity_target, fee_curve)
     }
 
     pub fn config_marinade(
@@ -175,6 +385,49 @@ pub mod marinade_finance {
         ctx.accounts.process(params)
     }
 
+    pub fn queue_params_change(
+        ctx: Context<QueueParamsChange>,
+        change: ParamsChange,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(change)
+    }
+
+    pub fn execute_params_change(ctx: Context<ExecuteParamsChange>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn cancel_params_change(ctx: Context<CancelParamsChange>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn create_referral(
+        ctx: Context<CreateReferral>,
+        referral_code: String,
+        treasury_cut_share: Fee,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(referral_code, treasury_cut_share)
+    }
+
+    pub fn update_referral(
+        ctx: Context<UpdateReferral>,
+        pause: Option<bool>,
+        treasury_cut_share: Option<Fee>,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(pause, treasury_cut_share)
+    }
+
+    /// moves stake_list or validator_list into a bigger account by max_copy_count items per call.
+    /// The list can not be modified until the move is finished
+    pub fn grow_list(ctx: Context<GrowList>, max_copy_count: u32) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(max_copy_count)
+    }
+
     //-------------------------------------------------------------------------------------
     // WIP Instructions, wil be part of devnet-MVP-2 beta-test release at marinade.finance
     //-------------------------------------------------------------------------------------
@@ -195,6 +448,26 @@ pub mod marinade_finance {
         ctx.accounts.process()
     }
 
+    pub fn partial_claim(ctx: Context<Claim>, lamports: u64) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process_partial(lamports)
+    }
+
+    pub fn split_ticket(ctx: Context<SplitTicket>, new_ticket_lamports: u64) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(new_ticket_lamports)
+    }
+
+    pub fn change_beneficiary(ctx: Context<ChangeBeneficiary>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
+    pub fn instant_claim(ctx: Context<InstantClaim>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     pub fn stake_reserve(ctx: Context<StakeReserve>, validator_index: u32) -> ProgramResult {
         check_context(&ctx)?;
         ctx.accounts.process(validator_index)
@@ -213,6 +486,33 @@ pub mod marinade_finance {
         ctx.accounts.process(stake_index)
     }
 
+    /// update_active for many stake accounts. Treasury fees are minted once
+    pub fn update_active_batch(
+        ctx: Context<UpdateActive>,
+        stake_indexes: Vec<u32>,
+        validator_indexes: Vec<u32>,
+    ) -> ProgramResult {
+        check_context_with_batch(&ctx, stake_indexes.len())?;
+        ctx.accounts
+            .process_batch(&stake_indexes, &validator_indexes, ctx.remaining_accounts)
+    }
+
+    /// update_deactivated for many stake accounts. Treasury fees are minted once
+    pub fn update_deactivated_batch(
+        ctx: Context<UpdateDeactivated>,
+        stake_indexes: Vec<u32>,
+    ) -> ProgramResult {
+        check_context_with_batch(&ctx, stake_indexes.len())?;
+        ctx.accounts
+            .process_batch(&stake_indexes, ctx.remaining_accounts)
+    }
+
+    /// Permissionless. Creates the epoch rewards ring buffer the update instructions append to
+    pub fn create_epoch_rewards(ctx: Context<CreateEpochRewards>) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process()
+    }
+
     pub fn deactivate_stake(
         ctx: Context<DeactivateStake>,
         stake_index: u32,
@@ -231,6 +531,17 @@ pub mod marinade_finance {
         ctx.accounts.process(stake_index, validator_index)
     }
 
+    pub fn withdraw_stake_account(
+        ctx: Context<WithdrawStakeAccount>,
+        stake_index: u32,
+        validator_index: u32,
+        msol_amount: u64,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts
+            .process(stake_index, validator_index, msol_amount)
+    }
+
     pub fn merge_stakes(
         ctx: Context<MergeStakes>,
         destination_stake_index: u32,
@@ -241,6 +552,16 @@ pub mod marinade_finance {
         ctx.accounts
             .process(destination_stake_index, source_stake_index, validator_index)
     }
+
+    /// Permissionless. Closes a duplication flag of a removed validator (validator_vote is Some)
+    /// or a fully claimed ticket (None) and moves its rent to the operational_sol_account
+    pub fn reclaim_rent(
+        ctx: Context<ReclaimRent>,
+        validator_vote: Option<Pubkey>,
+    ) -> ProgramResult {
+        check_context(&ctx)?;
+        ctx.accounts.process(validator_vote)
+    }
 }
 
 #[cfg(not(feature = "no-entrypoint"))]
@@ -371,6 +692,8 @@ pub struct ChangeAuthority<'info> {
     pub admin_authority: AccountInfo<'info>,
 }
 
+// admin, validator_manager and operational_sol_account are refused,
+// they change with propose_authority/accept_authority
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct ChangeAuthorityData {
     pub admin: Option<Pubkey>,
@@ -379,6 +702,29 @@ pub struct ChangeAuthorityData {
     pub treasury_msol_account: Option<Pubkey>,
 }
 
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct ProposeAuthorityData {
+    pub admin: Option<Pubkey>,
+    pub validator_manager: Option<Pubkey>,
+    pub operational_sol_account: Option<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub new_authority: AccountInfo<'info>,
+}
+
 //-----------------------------------------------------
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
@@ -419,6 +765,33 @@ pub struct AddLiquidity<'info> {
 }
 //-----------------------------------------------------
 #[derive(Accounts)]
+pub struct AddLiquidityMsol<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+
+    #[account(mut)]
+    pub lp_mint: CpiAccount<'info, Mint>,
+
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub liq_pool_msol_leg: CpiAccount<'info, TokenAccount>,
+
+    // to be able to compute current SOL value in liq_pool
+    pub liq_pool_sol_leg_pda: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub get_msol_from: CpiAccount<'info, TokenAccount>,
+    #[account(signer)]
+    pub get_msol_from_authority: AccountInfo<'info>, //get_msol_from owner or delegate_authority
+
+    #[account(mut)]
+    pub mint_to: CpiAccount<'info, TokenAccount>,
+
+    pub token_program: AccountInfo<'info>,
+}
+//-----------------------------------------------------
+#[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
     #[account(mut)]
     pub state: ProgramAccount<'info, State>,
@@ -450,6 +823,28 @@ pub struct RemoveLiquidity<'info> {
 }
 //-----------------------------------------------------
 #[derive(Accounts)]
+pub struct RebalanceLiqPool<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(mut)]
+    pub msol_mint: CpiAccount<'info, Mint>,
+
+    // legs
+    pub liq_pool_sol_leg_pda: AccountInfo<'info>,
+    #[account(mut)]
+    pub liq_pool_msol_leg: CpiAccount<'info, TokenAccount>,
+    pub liq_pool_msol_leg_authority: AccountInfo<'info>,
+
+    // Note: Ticket beneficiary is liq_pool_sol_leg_pda
+    #[account(zero, rent_exempt = enforce)]
+    pub new_ticket_account: ProgramAccount<'info, TicketAccountData>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub token_program: AccountInfo<'info>,
+}
+//-----------------------------------------------------
+#[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
     pub state: ProgramAccount<'info, State>,
@@ -479,6 +874,16 @@ pub struct Deposit<'info> {
     pub token_program: AccountInfo<'info>,
 }
 
+//-----------------------------------------------------
+#[derive(Accounts)]
+pub struct DepositDirected<'info> {
+    pub deposit: Deposit<'info>,
+
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    pub validator_vote: AccountInfo<'info>,
+}
+
 //-----------------------------------------------------
 #[derive(Accounts)]
 pub struct DepositStakeAccount<'info> {
@@ -494,8 +899,14 @@ pub struct DepositStakeAccount<'info> {
     pub stake_account: CpiAccount<'info, StakeWrapper>,
     #[account(signer)]
     pub stake_authority: AccountInfo<'info>,
+    // must sign only to deposit a stake with the lockup in force. Any account otherwise
+    pub custodian: AccountInfo<'info>,
     #[account(mut)]
     pub duplication_flag: AccountInfo<'info>,
+    // checked when the validator is auto-added
+    pub blocked_flag: AccountInfo<'info>,
+    // the stake is delegated to. Its commission is checked
+    pub validator_vote: AccountInfo<'info>,
     #[account(mut, signer)]
     pub rent_payer: AccountInfo<'info>,
 
@@ -506,14 +917,71 @@ pub struct DepositStakeAccount<'info> {
 
     pub msol_mint_authority: AccountInfo<'info>,
 
+    // receives the undelegated lamports of the stake account
+    #[account(mut)]
+    pub reserve_pda: AccountInfo<'info>,
+
     pub clock: Sysvar<'info, Clock>,
     pub rent: Sysvar<'info, Rent>,
+    pub stake_history: AccountInfo<'info>, // have no CPU budget to parse Sysvar<'info, StakeHistory>,
 
     pub system_program: AccountInfo<'info>,
     pub token_program: AccountInfo<'info>,
     pub stake_program: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct DepositActivatingStakeAccount<'info> {
+    pub deposit: DepositStakeAccount<'info>,
+
+    // PendingStakeDepositData::find_address, created by the instruction
+    #[account(mut)]
+    pub pending_deposit: AccountInfo<'info>,
+}
+
+impl<'info> Deref for DepositActivatingStakeAccount<'info> {
+    type Target = DepositStakeAccount<'info>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.deposit
+    }
+}
+
+impl<'info> DerefMut for DepositActivatingStakeAccount<'info> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.deposit
+    }
+}
+
+#[derive(Accounts)]
+pub struct FinishStakeDeposit<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    #[account(mut)]
+    pub stake_list: AccountInfo<'info>,
+
+    pub stake_account: CpiAccount<'info, StakeWrapper>,
+    #[account(mut)]
+    pub pending_deposit: ProgramAccount<'info, PendingStakeDepositData>,
+    // gets pending_deposit lamports back
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub msol_mint: CpiAccount<'info, Mint>,
+    #[account(mut)]
+    pub mint_to: CpiAccount<'info, TokenAccount>,
+
+    pub msol_mint_authority: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+
+    pub token_program: AccountInfo<'info>,
+}
+
 //-----------------------------------------------------
 #[derive(Accounts)]
 pub struct LiquidUnstake<'info> {
@@ -555,6 +1023,7 @@ pub struct AddValidator<'info> {
     pub validator_vote: AccountInfo<'info>,
     #[account(mut)]
     pub duplication_flag: AccountInfo<'info>,
+    pub blocked_flag: AccountInfo<'info>,
     #[account(mut, signer)]
     pub rent_payer: AccountInfo<'info>,
 
@@ -590,6 +1059,15 @@ pub struct SetValidatorScore<'info> {
     pub validator_list: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetValidatorStakeBounds<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub manager_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ConfigValidatorSystem<'info> {
     #[account(mut)]
@@ -598,6 +1076,31 @@ pub struct ConfigValidatorSystem<'info> {
     pub manager_authority: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct BlockValidator<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub manager_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub blocked_flag: AccountInfo<'info>,
+    #[account(mut, signer)]
+    pub rent_payer: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnblockValidator<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub manager_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub blocked_flag: AccountInfo<'info>,
+    #[account(mut)]
+    pub rent_receiver: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct OrderUnstake<'info> {
     #[account(mut)]
@@ -638,6 +1141,53 @@ pub struct Claim<'info> {
     pub system_program: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SplitTicket<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+
+    #[account(mut)]
+    pub ticket_account: ProgramAccount<'info, TicketAccountData>,
+    #[account(signer)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(zero, rent_exempt = enforce)]
+    pub new_ticket_account: ProgramAccount<'info, TicketAccountData>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeBeneficiary<'info> {
+    pub state: ProgramAccount<'info, State>,
+
+    #[account(mut)]
+    pub ticket_account: ProgramAccount<'info, TicketAccountData>,
+    #[account(signer)]
+    pub beneficiary: AccountInfo<'info>,
+
+    pub new_beneficiary: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InstantClaim<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+
+    #[account(mut)]
+    pub liq_pool_sol_leg_pda: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub ticket_account: ProgramAccount<'info, TicketAccountData>,
+    #[account(signer)]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub transfer_sol_to: AccountInfo<'info>,
+
+    pub system_program: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct StakeReserve<'info> {
     #[account(mut)]
@@ -686,6 +1236,10 @@ pub struct UpdateCommon<'info> {
 
     pub stake_program: AccountInfo<'info>,
     pub token_program: AccountInfo<'info>,
+
+    // rewards of the epoch are recorded here once created with CreateEpochRewards
+    #[account(mut)]
+    pub epoch_rewards: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -733,6 +1287,18 @@ impl<'info> DerefMut for UpdateDeactivated<'info> {
     }
 }
 
+#[derive(Accounts)]
+pub struct CreateEpochRewards<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(mut)]
+    pub epoch_rewards: AccountInfo<'info>,
+    #[account(mut, signer)]
+    pub rent_payer: AccountInfo<'info>,
+
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetLpParams<'info> {
     #[account(mut)]
@@ -751,6 +1317,11 @@ pub struct ConfigMarinadeParams {
     pub staking_sol_cap: Option<u64>,
     pub liquidity_sol_cap: Option<u64>,
     pub auto_add_validator_enabled: Option<bool>,
+    pub paused_operations: Option<u32>,
+    pub params_change_delay_epochs: Option<u64>,
+    pub withdraw_stake_account_fee: Option<Fee>,
+    pub max_commission: Option<u8>,
+    pub liquidity_epoch_outflow_cap: Option<u64>,
 }
 
 #[derive(Accounts)]
@@ -761,6 +1332,85 @@ pub struct ConfigMarinade<'info> {
     pub admin_authority: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct QueueParamsChange<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+
+    #[account(zero, rent_exempt = enforce)]
+    pub queued_change: ProgramAccount<'info, QueuedChangeData>,
+    // gets queued_change lamports back on execute or cancel
+    pub rent_payer: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteParamsChange<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(mut)]
+    pub queued_change: ProgramAccount<'info, QueuedChangeData>,
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct CancelParamsChange<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub queued_change: ProgramAccount<'info, QueuedChangeData>,
+    #[account(mut)]
+    pub rent_payer: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateReferral<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+
+    #[account(zero, rent_exempt = enforce)]
+    pub referral_state: ProgramAccount<'info, ReferralStateData>,
+    pub partner_account: AccountInfo<'info>,
+    pub msol_token_account: CpiAccount<'info, TokenAccount>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReferral<'info> {
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub referral_state: ProgramAccount<'info, ReferralStateData>,
+}
+
+#[derive(Accounts)]
+pub struct GrowList<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(signer)]
+    pub admin_authority: AccountInfo<'info>,
+    // current stake_list or validator_list
+    #[account(mut)]
+    pub list: AccountInfo<'info>,
+    // zeroed program owned account
+    #[account(mut)]
+    pub new_list: AccountInfo<'info>,
+    // gets the rent of the current list when the move is finished
+    #[account(mut)]
+    pub rent_receiver: AccountInfo<'info>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
 #[derive(Accounts)]
 pub struct DeactivateStake<'info> {
     #[account(mut)]
@@ -829,3 +1479,50 @@ pub struct MergeStakes<'info> {
 
     pub stake_program: AccountInfo<'info>,
 }
+
+#[derive(Accounts)]
+pub struct WithdrawStakeAccount<'info> {
+    #[account(mut)]
+    pub state: ProgramAccount<'info, State>,
+    #[account(mut)]
+    pub msol_mint: CpiAccount<'info, Mint>,
+
+    // Note: split stake authorities are given to burn_msol_from.owner
+    #[account(mut)]
+    pub burn_msol_from: CpiAccount<'info, TokenAccount>,
+    #[account(signer)]
+    pub burn_msol_authority: AccountInfo<'info>, //burn_msol_from owner or delegate_authority
+    #[account(mut)]
+    pub treasury_msol_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub validator_list: AccountInfo<'info>,
+    #[account(mut)]
+    pub stake_list: AccountInfo<'info>,
+    #[account(mut)]
+    pub stake_account: CpiAccount<'info, StakeWrapper>,
+    pub stake_deposit_authority: AccountInfo<'info>,
+    pub stake_withdraw_authority: AccountInfo<'info>,
+    #[account(mut, signer)]
+    pub split_stake_account: AccountInfo<'info>,
+    #[account(mut, signer)]
+    pub split_stake_rent_payer: AccountInfo<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub stake_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimRent<'info> {
+    pub state: ProgramAccount<'info, State>,
+    pub validator_list: AccountInfo<'info>,
+    // stale duplication flag or claimed ticket
+    #[account(mut)]
+    pub orphaned_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub operational_sol_account: AccountInfo<'info>,
+}
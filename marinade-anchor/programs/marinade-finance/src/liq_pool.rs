@@ -1,11 +1,177 @@
-use crate::{calc::proportional, checks::check_address, located::Located, Fee, State, ID};
+use crate::{
+    calc::proportional, checks::check_address, error::CommonError, located::Located, Fee, State,
+    ID,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::native_token::{lamports_to_sol, sol_to_lamports};
+use std::{fmt::Display, str::FromStr};
 
 pub mod add_liquidity;
+pub mod add_liquidity_msol;
 pub mod initialize;
+pub mod rebalance;
 pub mod remove_liquidity;
 pub mod set_lp_params;
 
+pub const MAX_FEE_CURVE_POINTS: usize = 8;
+
+/// Piecewise-linear fee curve breakpoint: `fee` is charged when `liquidity` lamports are left in the pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct FeeCurvePoint {
+    pub liquidity: u64,
+    pub fee: Fee,
+}
+
+impl Display for FeeCurvePoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} SOL:{}", lamports_to_sol(self.liquidity), self.fee)
+    }
+}
+
+/// "liquidity_in_SOL:fee_in_percent", e.g. "1000:1.5"
+impl FromStr for FeeCurvePoint {
+    type Err = CommonError; // TODO: better error
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let liquidity = parts.next().ok_or(CommonError::InvalidFeeCurve)?;
+        let fee = parts.next().ok_or(CommonError::InvalidFeeCurve)?;
+        Ok(Self {
+            liquidity: sol_to_lamports(
+                liquidity
+                    .parse()
+                    .map_err(|_| CommonError::CalculationFailure)?,
+            ),
+            fee: fee.parse()?,
+        })
+    }
+}
+
+/// Liquid-unstake fee curve. Fixed size (all kinds share the same fields) because it lives inside State
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct FeeCurve {
+    pub kind: u8,
+    /// used points for PIECEWISE, sorted by liquidity
+    pub points_count: u8,
+    pub points: [FeeCurvePoint; MAX_FEE_CURVE_POINTS],
+    /// lamports of liquidity halving the (max - min) fee delta for EXPONENTIAL
+    pub half_life: u64,
+}
+
+impl FeeCurve {
+    /// lp_max_fee -> lp_min_fee linear up to lp_liquidity_target (default)
+    pub const LINEAR: u8 = 0;
+    /// lp_max_fee -> points... -> lp_min_fee at lp_liquidity_target, linear between them
+    pub const PIECEWISE: u8 = 1;
+    /// lp_min_fee + (lp_max_fee - lp_min_fee) / 2^(liquidity / half_life). lp_liquidity_target is not used
+    pub const EXPONENTIAL: u8 = 2;
+
+    pub fn linear() -> Self {
+        Self::default()
+    }
+
+    pub fn piecewise(points: &[FeeCurvePoint]) -> Result<Self, CommonError> {
+        if points.is_empty() || points.len() > MAX_FEE_CURVE_POINTS {
+            return Err(CommonError::InvalidFeeCurve);
+        }
+        let mut result = Self {
+            kind: Self::PIECEWISE,
+            points_count: points.len() as u8,
+            ..Self::default()
+        };
+        result.points[..points.len()].copy_from_slice(points);
+        Ok(result)
+    }
+
+    pub fn exponential(half_life: u64) -> Self {
+        Self {
+            kind: Self::EXPONENTIAL,
+            half_life,
+            ..Self::default()
+        }
+    }
+
+    pub fn used_points(&self) -> &[FeeCurvePoint] {
+        &self.points[..(self.points_count as usize).min(MAX_FEE_CURVE_POINTS)]
+    }
+
+    pub fn check(
+        &self,
+        min_fee: Fee,
+        max_fee: Fee,
+        liquidity_target: u64,
+    ) -> Result<(), CommonError> {
+        match self.kind {
+            Self::LINEAR => {
+                if self.points_count != 0 || self.half_life != 0 {
+                    msg!("Linear fee curve must have no params");
+                    return Err(CommonError::InvalidFeeCurve);
+                }
+            }
+            Self::PIECEWISE => {
+                if self.points_count == 0 || self.points_count as usize > MAX_FEE_CURVE_POINTS {
+                    msg!(
+                        "Fee curve must have 1..={} points",
+                        MAX_FEE_CURVE_POINTS
+                    );
+                    return Err(CommonError::InvalidFeeCurve);
+                }
+                // the curve goes from (0, max_fee) to (liquidity_target, min_fee) and never grows
+                let mut prev = FeeCurvePoint {
+                    liquidity: 0,
+                    fee: max_fee,
+                };
+                for point in self.used_points() {
+                    if point.liquidity <= prev.liquidity
+                        || point.liquidity >= liquidity_target
+                        || point.fee > prev.fee
+                        || point.fee < min_fee
+                    {
+                        msg!("Wrong fee curve point {}", point);
+                        return Err(CommonError::InvalidFeeCurve);
+                    }
+                    prev = *point;
+                }
+            }
+            Self::EXPONENTIAL => {
+                if self.half_life == 0 || self.points_count != 0 {
+                    msg!("Exponential fee curve needs half life > 0 and no points");
+                    return Err(CommonError::InvalidFeeCurve);
+                }
+            }
+            _ => {
+                msg!("Unknown fee curve kind {}", self.kind);
+                return Err(CommonError::InvalidFeeCurve);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for FeeCurve {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            Self::LINEAR => write!(f, "linear"),
+            Self::PIECEWISE => {
+                write!(f, "piecewise [")?;
+                for (i, point) in self.used_points().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", point)?;
+                }
+                write!(f, "]")
+            }
+            Self::EXPONENTIAL => write!(
+                f,
+                "exponential (half life {} SOL)",
+                lamports_to_sol(self.half_life)
+            ),
+            kind => write!(f, "unknown ({})", kind),
+        }
+    }
+}
+
 #[derive(Clone, AnchorSerialize, AnchorDeserialize, Debug)]
 pub struct LiqPool {
     pub lp_mint: Pubkey,
@@ -72,6 +238,31 @@ impl LiqPool {
             .saturating_sub(self.lp_min_fee.basis_points)
     }
 
+    ///compute the liquid-unstake fee based on liquidity amount (left after the unstake) using the fee curve (State.liq_pool_fee_curve)
+    pub fn fee(&self, fee_curve: &FeeCurve, lamports: u64) -> Fee {
+        match fee_curve.kind {
+            FeeCurve::PIECEWISE => self.piecewise_fee(fee_curve, lamports),
+            FeeCurve::EXPONENTIAL => self.exponential_fee(fee_curve, lamports),
+            _ => self.linear_fee(lamports),
+        }
+    }
+
+    ///fee for liquid-unstaking remove_lamports out of available_lamports,
+    ///based on the liquidity left *after* the user takes the sol
+    pub fn liquid_unstake_fee(
+        &self,
+        fee_curve: &FeeCurve,
+        remove_lamports: u64,
+        available_lamports: u64,
+    ) -> Fee {
+        if remove_lamports >= available_lamports {
+            // user is removing all liquidity
+            self.lp_max_fee
+        } else {
+            self.fee(fee_curve, available_lamports - remove_lamports)
+        }
+    }
+
     ///compute a linear fee based on liquidity amount, it goes from fee(0)=max -> fee(x>=target)=min
     pub fn linear_fee(&self, lamports: u64) -> Fee {
         if lamports >= self.lp_liquidity_target {
@@ -85,6 +276,49 @@ impl LiqPool {
         }
     }
 
+    ///fee(0)=max -> fee(point.liquidity)=point.fee... -> fee(x>=target)=min, linear between points
+    fn piecewise_fee(&self, fee_curve: &FeeCurve, lamports: u64) -> Fee {
+        let mut prev = FeeCurvePoint {
+            liquidity: 0,
+            fee: self.lp_max_fee,
+        };
+        let last = FeeCurvePoint {
+            liquidity: self.lp_liquidity_target,
+            fee: self.lp_min_fee,
+        };
+        for point in fee_curve.used_points().iter().chain(std::iter::once(&last)) {
+            if lamports < point.liquidity {
+                return Fee {
+                    basis_points: prev.fee.basis_points
+                        - proportional(
+                            prev.fee.basis_points.saturating_sub(point.fee.basis_points) as u64,
+                            lamports - prev.liquidity,
+                            point.liquidity - prev.liquidity,
+                        )
+                        .unwrap() as u32,
+                };
+            }
+            prev = *point;
+        }
+        self.lp_min_fee
+    }
+
+    ///fee delta over min halves every half_life lamports (linear between halvings)
+    fn exponential_fee(&self, fee_curve: &FeeCurve, lamports: u64) -> Fee {
+        let half_life = fee_curve.half_life.max(1);
+        let halvings = lamports / half_life;
+        if halvings >= 32 {
+            return self.lp_min_fee;
+        }
+        let upper = self.delta() >> halvings;
+        let lower = upper >> 1;
+        let extra = upper
+            - proportional((upper - lower) as u64, lamports % half_life, half_life).unwrap() as u32;
+        Fee {
+            basis_points: self.lp_min_fee.basis_points + extra,
+        }
+    }
+
     pub fn on_lp_mint(&mut self, amount: u64) {
         self.lp_supply = self
             .lp_supply
@@ -211,3 +445,118 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+
+    use crate::{
+        liq_pool::{FeeCurve, FeeCurvePoint, LiqPool},
+        Fee,
+    };
+
+    fn test_pool() -> LiqPool {
+        LiqPool {
+            lp_mint: Pubkey::default(),
+            lp_mint_authority_bump_seed: 0,
+            sol_leg_bump_seed: 0,
+            msol_leg_authority_bump_seed: 0,
+            msol_leg: Pubkey::default(),
+            lp_liquidity_target: 10_000,
+            lp_max_fee: Fee::from_basis_points(300),
+            lp_min_fee: Fee::from_basis_points(30),
+            treasury_cut: Fee::from_basis_points(2500),
+            lp_supply: 0,
+            lent_from_sol_leg: 0,
+            liquidity_sol_cap: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn test_linear_curve() {
+        let fee_curve = FeeCurve::linear();
+        let pool = test_pool();
+        assert_eq!(pool.fee(&fee_curve, 0), pool.lp_max_fee);
+        assert_eq!(pool.fee(&fee_curve, 5_000), Fee::from_basis_points(165));
+        assert_eq!(pool.fee(&fee_curve, 10_000), pool.lp_min_fee);
+        assert_eq!(pool.fee(&fee_curve, 20_000), pool.lp_min_fee);
+    }
+
+    #[test]
+    fn test_piecewise_curve() {
+        let points = [
+            FeeCurvePoint {
+                liquidity: 1_000,
+                fee: Fee::from_basis_points(100),
+            },
+            FeeCurvePoint {
+                liquidity: 4_000,
+                fee: Fee::from_basis_points(40),
+            },
+        ];
+        let fee_curve = FeeCurve::piecewise(&points).unwrap();
+        let pool = test_pool();
+        fee_curve
+            .check(pool.lp_min_fee, pool.lp_max_fee, pool.lp_liquidity_target)
+            .unwrap();
+        assert_eq!(pool.fee(&fee_curve, 0), pool.lp_max_fee);
+        assert_eq!(pool.fee(&fee_curve, 500), Fee::from_basis_points(200));
+        assert_eq!(pool.fee(&fee_curve, 1_000), Fee::from_basis_points(100));
+        assert_eq!(pool.fee(&fee_curve, 2_500), Fee::from_basis_points(70));
+        assert_eq!(pool.fee(&fee_curve, 4_000), Fee::from_basis_points(40));
+        assert_eq!(pool.fee(&fee_curve, 7_000), Fee::from_basis_points(35));
+        assert_eq!(pool.fee(&fee_curve, 10_000), pool.lp_min_fee);
+
+        // fee must not grow with liquidity
+        let wrong = FeeCurve::piecewise(&[points[1], points[0]]).unwrap();
+        assert!(wrong
+            .check(pool.lp_min_fee, pool.lp_max_fee, pool.lp_liquidity_target)
+            .is_err());
+        // points must be under the liquidity target
+        assert!(fee_curve
+            .check(pool.lp_min_fee, pool.lp_max_fee, 4_000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_exponential_curve() {
+        let fee_curve = FeeCurve::exponential(1_000);
+        let pool = test_pool();
+        fee_curve
+            .check(pool.lp_min_fee, pool.lp_max_fee, pool.lp_liquidity_target)
+            .unwrap();
+        assert_eq!(pool.fee(&fee_curve, 0), pool.lp_max_fee);
+        assert_eq!(
+            pool.fee(&fee_curve, 1_000),
+            Fee::from_basis_points(30 + 135)
+        );
+        assert_eq!(pool.fee(&fee_curve, 2_000), Fee::from_basis_points(30 + 67));
+        assert_eq!(pool.fee(&fee_curve, 100_000), pool.lp_min_fee);
+        assert!(FeeCurve::exponential(0)
+            .check(pool.lp_min_fee, pool.lp_max_fee, pool.lp_liquidity_target)
+            .is_err());
+    }
+
+    #[test]
+    fn test_liquid_unstake_fee() {
+        let fee_curve = FeeCurve::linear();
+        let pool = test_pool();
+        // fee for the liquidity left after the unstake
+        assert_eq!(
+            pool.liquid_unstake_fee(&fee_curve, 5_000, 15_000),
+            pool.lp_min_fee
+        );
+        assert_eq!(
+            pool.liquid_unstake_fee(&fee_curve, 5_000, 10_000),
+            Fee::from_basis_points(165)
+        );
+        assert_eq!(
+            pool.liquid_unstake_fee(&fee_curve, 10_000, 10_000),
+            pool.lp_max_fee
+        );
+        assert_eq!(
+            pool.liquid_unstake_fee(&fee_curve, 20_000, 10_000),
+            pool.lp_max_fee
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::AddLiquidity;
+use crate::{
+    events::{current_epoch, AddLiquidityEvent},
+    AddLiquidity, State,
+};
 
 use super::LiqPoolHelpers;
 use crate::{calc::shares_from_value, checks::*};
@@ -28,6 +31,7 @@ impl<'info> AddLiquidity<'info> {
 
     // fn add_liquidity()
     pub fn process(&mut self, lamports: u64) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_ADD_LIQUIDITY)?;
         msg!("add-liq pre check");
         check_min_amount(lamports, self.state.min_deposit, "add_liquidity")?;
         self.state
@@ -76,11 +80,14 @@ impl<'info> AddLiquidity<'info> {
             .state
             .calc_lamports_from_msol_amount(self.liq_pool_msol_leg.amount)
             .expect("msol_leg_value");
-        let total_liq_pool_value = sol_leg_lamports + msol_leg_value;
+        // tickets bought by the pool are worth their full amount once claimed
+        let total_liq_pool_value =
+            sol_leg_lamports + msol_leg_value + self.state.liq_pool_tickets_receivable;
         msg!(
-            "liq_pool SOL:{}, liq_pool mSOL value:{} liq_pool_value:{}",
+            "liq_pool SOL:{}, liq_pool mSOL value:{}, tickets receivable:{} liq_pool_value:{}",
             sol_leg_lamports,
             msol_leg_value,
+            self.state.liq_pool_tickets_receivable,
             total_liq_pool_value
         );
 
@@ -124,6 +131,14 @@ impl<'info> AddLiquidity<'info> {
         })?;
         self.state.liq_pool.on_lp_mint(shares_for_user);
 
+        emit!(AddLiquidityEvent {
+            state: *self.state.to_account_info().key,
+            sol_owner: *self.transfer_from.key,
+            lamports,
+            lp_minted: shares_for_user,
+            msol_price: self.state.msol_price,
+            epoch: current_epoch()?,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,164 @@
+use crate::{
+    events::{current_epoch, AddLiquidityMsolEvent},
+    AddLiquidityMsol, State,
+};
+
+use super::LiqPoolHelpers;
+use crate::{calc::shares_from_value, checks::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{mint_to, transfer, MintTo, Transfer};
+
+impl<'info> AddLiquidityMsol<'info> {
+    fn check_get_msol_from(&self, msol_amount: u64) -> ProgramResult {
+        check_token_mint(&self.get_msol_from, self.state.msol_mint, "get_msol_from")?;
+        // if delegated, check delegated amount
+        if *self.get_msol_from_authority.key == self.get_msol_from.owner {
+            if self.get_msol_from.amount < msol_amount {
+                msg!(
+                    "Requested to add {} mSOL lamports liquidity but have only {}",
+                    msol_amount,
+                    self.get_msol_from.amount
+                );
+                return Err(ProgramError::InsufficientFunds);
+            }
+        } else if self
+            .get_msol_from
+            .delegate
+            .contains(self.get_msol_from_authority.key)
+        {
+            // delegated_amount & delegate must be set on the user's msol account before
+            if self.get_msol_from.delegated_amount < msol_amount {
+                msg!(
+                    "Delegated {} mSOL lamports. Requested {}",
+                    self.get_msol_from.delegated_amount,
+                    msol_amount
+                );
+                return Err(ProgramError::InsufficientFunds);
+            }
+        } else {
+            msg!(
+                "Token must be delegated to {}",
+                self.get_msol_from_authority.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    fn check_mint_to(&self) -> ProgramResult {
+        check_token_mint(&self.mint_to, self.state.liq_pool.lp_mint, "mint_to")?;
+        Ok(())
+    }
+
+    // fn add_liquidity_msol()
+    pub fn process(&mut self, msol_amount: u64) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_ADD_LIQUIDITY)?;
+        self.state
+            .liq_pool
+            .check_lp_mint(self.lp_mint.to_account_info().key)?;
+        self.state
+            .check_lp_mint_authority(self.lp_mint_authority.key)?;
+        self.state
+            .liq_pool
+            .check_liq_pool_msol_leg(self.liq_pool_msol_leg.to_account_info().key)?;
+        self.state
+            .check_liq_pool_sol_leg_pda(self.liq_pool_sol_leg_pda.key)?;
+        self.check_get_msol_from(msol_amount)?;
+        self.check_mint_to()?;
+        check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+
+        // Update virtual lp_supply by real one
+        if self.lp_mint.supply > self.state.liq_pool.lp_supply {
+            msg!("Someone minted lp tokens without our permission or bug found");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.state.liq_pool.lp_supply = self.lp_mint.supply;
+
+        //compute current liq-pool total value BEFORE adding user's deposit
+        let sol_leg_lamports = self
+            .liq_pool_sol_leg_pda
+            .lamports()
+            .checked_sub(self.state.rent_exempt_for_token_acc)
+            .expect("sol_leg_lamports");
+
+        // mSOL is valued after the fee of liquid-unstaking it right now,
+        // so adding mSOL and removing liquidity can not bypass the liquid-unstake fee
+        let msol_fee = self
+            .state
+            .liq_pool
+            .liquid_unstake_fee(
+                &self.state.liq_pool_fee_curve,
+                self.state.calc_lamports_from_msol_amount(msol_amount)?,
+                sol_leg_lamports,
+            )
+            .apply(msol_amount);
+        let msol_value = self
+            .state
+            .calc_lamports_from_msol_amount(msol_amount - msol_fee)?;
+        check_min_amount(msol_value, self.state.min_deposit, "add_liquidity_msol")?;
+
+        let msol_leg_value = self
+            .state
+            .calc_lamports_from_msol_amount(self.liq_pool_msol_leg.amount)
+            .expect("msol_leg_value");
+        // tickets bought by the pool are worth their full amount once claimed
+        let total_liq_pool_value =
+            sol_leg_lamports + msol_leg_value + self.state.liq_pool_tickets_receivable;
+        msg!(
+            "liq_pool SOL:{}, liq_pool mSOL value:{}, tickets receivable:{} liq_pool_value:{}",
+            sol_leg_lamports,
+            msol_leg_value,
+            self.state.liq_pool_tickets_receivable,
+            total_liq_pool_value
+        );
+
+        let shares_for_user = shares_from_value(
+            msol_value,
+            total_liq_pool_value,
+            self.state.liq_pool.lp_supply,
+        )?;
+
+        msg!("LP for user {}", shares_for_user);
+
+        //transfer mSOL into liq-pool mSOL leg
+        transfer(
+            CpiContext::new(
+                self.token_program.clone(),
+                Transfer {
+                    from: self.get_msol_from.to_account_info(),
+                    to: self.liq_pool_msol_leg.to_account_info(),
+                    authority: self.get_msol_from_authority.clone(),
+                },
+            ),
+            msol_amount,
+        )?;
+
+        //mint liq-pool shares (mSOL-SOL-LP tokens) for the user
+        self.state.with_lp_mint_authority_seeds(|mint_seeds| {
+            mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    MintTo {
+                        mint: self.lp_mint.to_account_info(),
+                        to: self.mint_to.to_account_info(),
+                        authority: self.lp_mint_authority.clone(),
+                    },
+                    &[mint_seeds],
+                ),
+                shares_for_user,
+            )
+        })?;
+        self.state.liq_pool.on_lp_mint(shares_for_user);
+
+        emit!(AddLiquidityMsolEvent {
+            state: *self.state.to_account_info().key,
+            msol_owner: self.get_msol_from.owner,
+            msol_amount,
+            msol_value,
+            lp_minted: shares_for_user,
+            msol_price: self.state.msol_price,
+            epoch: current_epoch()?,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{
+    checks::{check_address, check_min_amount, check_owner_program},
+    liq_pool::LiqPoolHelpers,
+    CommonError, RebalanceLiqPool, State,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn};
+
+/// RebalanceLiqPool instruction (permissionless crank): delayed-unstakes mSOL accumulated in the liq-pool mSOL leg.
+/// Only allowed while the SOL leg (with receivable tickets) is below lp_liquidity_target, the amount is capped at the shortfall.
+/// The mSOL is burned and a Ticket-account owned by the SOL leg is created.
+/// The ticket is booked as receivable until anyone Claims it into the SOL leg, so LP value does not change
+impl<'info> RebalanceLiqPool<'info> {
+    fn check_new_ticket_account(&self) -> ProgramResult {
+        // ticket account program-owner must be marinade (TODO: I think it was checked by anchor already)
+        check_owner_program(
+            &self.new_ticket_account,
+            &crate::ID, //owner-program should be marinade
+            "new_ticket_account",
+        )?;
+
+        // should be uninitialized - checked by anchor
+        // should be rent-exempt - checked by anchor
+        Ok(())
+    }
+
+    // fn rebalance_liq_pool()
+    pub fn process(&mut self, msol_amount: u64) -> ProgramResult {
+        // the pool delayed-unstakes same as OrderUnstake
+        self.state.check_not_paused(State::PAUSE_ORDER_UNSTAKE)?;
+        check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+        self.check_new_ticket_account()?;
+        self.state
+            .check_msol_mint(self.msol_mint.to_account_info().key)?;
+        self.state
+            .check_liq_pool_sol_leg_pda(self.liq_pool_sol_leg_pda.key)?;
+        self.state
+            .liq_pool
+            .check_liq_pool_msol_leg(self.liq_pool_msol_leg.to_account_info().key)?;
+        self.state
+            .check_liq_pool_msol_leg_authority(self.liq_pool_msol_leg_authority.key)?;
+
+        if msol_amount > self.liq_pool_msol_leg.amount {
+            msg!(
+                "Requested to rebalance {} mSOL but liq-pool has only {}",
+                msol_amount,
+                self.liq_pool_msol_leg.amount
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // only refill the SOL leg up to the liquidity target, counting the tickets already receivable
+        let sol_leg_lamports = self
+            .liq_pool_sol_leg_pda
+            .lamports()
+            .saturating_sub(self.state.rent_exempt_for_token_acc)
+            .saturating_add(self.state.liq_pool_tickets_receivable);
+        if sol_leg_lamports >= self.state.liq_pool.lp_liquidity_target {
+            msg!(
+                "SOL leg {} (with receivable) is at the liquidity target {}",
+                sol_leg_lamports,
+                self.state.liq_pool.lp_liquidity_target
+            );
+            return Err(CommonError::LiquidityTargetReached.into());
+        }
+        let shortfall_msol = self
+            .state
+            .calc_msol_from_lamports(self.state.liq_pool.lp_liquidity_target - sol_leg_lamports)?;
+        let msol_amount = if msol_amount > shortfall_msol {
+            msg!("Rebalance capped to the {} mSOL shortfall", shortfall_msol);
+            shortfall_msol
+        } else {
+            msol_amount
+        };
+
+        let lamports_amount = self.state.calc_lamports_from_msol_amount(msol_amount)?;
+        check_min_amount(lamports_amount, self.state.min_withdraw, "rebalance SOL")?;
+
+        // circulating_ticket_balance +
+        self.state.circulating_ticket_balance = self
+            .state
+            .circulating_ticket_balance
+            .checked_add(lamports_amount)
+            .expect("circulating_ticket_balance overflow");
+        self.state.circulating_ticket_count += 1;
+
+        //burn mSOL from the liq-pool mSOL leg
+        self.state
+            .with_liq_pool_msol_leg_authority_seeds(|msol_seeds| {
+                burn(
+                    CpiContext::new_with_signer(
+                        self.token_program.clone(),
+                        Burn {
+                            mint: self.msol_mint.to_account_info(),
+                            to: self.liq_pool_msol_leg.to_account_info(),
+                            authority: self.liq_pool_msol_leg_authority.clone(),
+                        },
+                        &[msol_seeds],
+                    ),
+                    msol_amount,
+                )
+            })?;
+        self.state.on_msol_burn(msol_amount);
+
+        //initialize new_ticket_account, owned by the liq-pool SOL leg
+        self.new_ticket_account.state_address = *self.state.to_account_info().key;
+        self.new_ticket_account.beneficiary = *self.liq_pool_sol_leg_pda.key;
+        self.new_ticket_account.lamports_amount = lamports_amount;
+        // same due epoch rule as OrderUnstake
+        self.new_ticket_account.created_epoch = self.clock.epoch
+            + if self.clock.epoch == self.state.stake_system.last_stake_delta_epoch {
+                1
+            } else {
+                0
+            };
+        // mSOL leg value moves to receivable
+        self.state.on_liq_pool_ticket_bought(lamports_amount);
+
+        Ok(())
+    }
+}
@@ -1,11 +1,15 @@
 use crate::{
     calc::proportional,
     checks::{check_address, check_min_amount, check_owner_program, check_token_mint},
+    events::{current_epoch, RemoveLiquidityEvent},
     liq_pool::LiqPoolHelpers,
-    RemoveLiquidity,
+    ticket_account::TicketAccountData,
+    CommonError, RemoveLiquidity,
 };
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke_signed, system_instruction, system_program};
+use anchor_lang::solana_program::{
+    program::invoke_signed, system_instruction, system_program, sysvar::Sysvar as _,
+};
 use anchor_spl::token::{burn, transfer, Burn, Transfer};
 
 impl<'info> RemoveLiquidity<'info> {
@@ -64,7 +68,91 @@ impl<'info> RemoveLiquidity<'info> {
         Ok(())
     }
 
-    pub fn process(&mut self, tokens: u64) -> ProgramResult {
+    /// Moves `lamports` of a ticket owned by the SOL leg into new_ticket owned by the LP
+    /// (or hands over the whole ticket). The LP gets the SOL at the same due epoch as the pool would
+    fn split_pool_ticket(
+        &mut self,
+        pool_ticket: &AccountInfo<'info>,
+        new_ticket: &AccountInfo<'info>,
+        lamports: u64,
+    ) -> ProgramResult {
+        check_owner_program(pool_ticket, &crate::ID, "pool_ticket")?;
+        check_owner_program(new_ticket, &crate::ID, "new_ticket")?;
+        if !pool_ticket.is_writable || !new_ticket.is_writable {
+            msg!("Ticket accounts must be writable");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut ticket: TicketAccountData =
+            AccountDeserialize::try_deserialize(&mut pool_ticket.data.borrow().as_ref())?;
+        if &ticket.state_address != self.state.to_account_info().key {
+            msg!(
+                "Ticket has wrong marinade instance {}",
+                ticket.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if ticket.beneficiary != *self.liq_pool_sol_leg_pda.key {
+            msg!("Ticket {} is not owned by the liq-pool", pool_ticket.key);
+            return Err(CommonError::WrongBeneficiary.into());
+        }
+        if lamports > ticket.lamports_amount {
+            msg!(
+                "Receivable share {} needs a pool ticket bigger than {}",
+                lamports,
+                ticket.lamports_amount
+            );
+            return Err(CommonError::InsufficientLiquidity.into());
+        }
+
+        if lamports == ticket.lamports_amount {
+            // the whole ticket goes to the LP, new_ticket is not used
+            ticket.beneficiary = *self.transfer_sol_to.key;
+        } else {
+            // both parts must be claimable on their own
+            check_min_amount(lamports, self.state.min_withdraw, "new ticket SOL")?;
+            check_min_amount(
+                ticket.lamports_amount - lamports,
+                self.state.min_withdraw,
+                "remaining pool ticket SOL",
+            )?;
+            // new ticket must be uninitialized and rent exempt
+            if new_ticket.data.borrow().iter().any(|byte| *byte != 0) {
+                msg!("New ticket {} is already in use", new_ticket.key);
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            if !Rent::get()?.is_exempt(new_ticket.lamports(), new_ticket.data_len()) {
+                msg!("New ticket {} is not rent exempt", new_ticket.key);
+                return Err(ProgramError::AccountNotRentExempt);
+            }
+
+            ticket.lamports_amount -= lamports;
+            let new_ticket_data = TicketAccountData {
+                state_address: ticket.state_address,
+                beneficiary: *self.transfer_sol_to.key,
+                lamports_amount: lamports,
+                created_epoch: ticket.created_epoch,
+            };
+            let mut data = new_ticket.data.borrow_mut();
+            let mut data: &mut [u8] = &mut data;
+            new_ticket_data.try_serialize(&mut data)?;
+            self.state.circulating_ticket_count += 1;
+        }
+        {
+            let mut data = pool_ticket.data.borrow_mut();
+            let mut data: &mut [u8] = &mut data;
+            ticket.try_serialize(&mut data)?;
+        }
+
+        // circulating_ticket_balance is unchanged, the LP part is no longer receivable by the pool
+        self.state.on_liq_pool_ticket_claimed(lamports);
+        Ok(())
+    }
+
+    pub fn process(
+        &mut self,
+        tokens: u64,
+        pool_ticket_accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
         msg!("rem-liq pre check");
         self.state
             .liq_pool
@@ -99,14 +187,39 @@ impl<'info> RemoveLiquidity<'info> {
 
         msg!("mSOL-SOL-LP total supply:{}", self.lp_mint.supply);
 
-        let sol_out_amount = proportional(
+        let sol_leg_lamports = self
+            .liq_pool_sol_leg_pda
+            .lamports()
+            .checked_sub(self.state.rent_exempt_for_token_acc)
+            .unwrap();
+        // the LP share of tickets bought by the pool (still receivable) is paid in SOL
+        let mut sol_out_amount = proportional(
             tokens,
-            self.liq_pool_sol_leg_pda
-                .lamports()
-                .checked_sub(self.state.rent_exempt_for_token_acc)
-                .unwrap(),
+            sol_leg_lamports + self.state.liq_pool_tickets_receivable,
             self.state.liq_pool.lp_supply, // Use virtual amount
         )?;
+        let mut ticket_out_amount = 0;
+        if sol_out_amount > sol_leg_lamports {
+            // not enough SOL: pay the LP share of the SOL leg and split a pool ticket for the receivable share
+            let ticket_accounts = if let [pool_ticket, new_ticket] = pool_ticket_accounts {
+                (pool_ticket, new_ticket)
+            } else {
+                msg!(
+                    "Requested {} SOL but liq-pool has only {}. Pass a pool ticket to get the receivable share as a ticket",
+                    sol_out_amount,
+                    sol_leg_lamports
+                );
+                return Err(CommonError::InsufficientLiquidity.into());
+            };
+            let leg_share = proportional(
+                tokens,
+                sol_leg_lamports,
+                self.state.liq_pool.lp_supply, // Use virtual amount
+            )?;
+            ticket_out_amount = sol_out_amount - leg_share;
+            sol_out_amount = leg_share;
+            self.split_pool_ticket(ticket_accounts.0, ticket_accounts.1, ticket_out_amount)?;
+        }
         let msol_out_amount = proportional(
             tokens,
             self.liq_pool_msol_leg.amount,
@@ -115,6 +228,8 @@ impl<'info> RemoveLiquidity<'info> {
 
         check_min_amount(
             sol_out_amount
+                .checked_add(ticket_out_amount)
+                .expect("lamports overflow")
                 .checked_add(
                     self.state
                         .calc_lamports_from_msol_amount(msol_out_amount)
@@ -125,8 +240,9 @@ impl<'info> RemoveLiquidity<'info> {
             "removed liquidity",
         )?;
         msg!(
-            "SOL out amount:{}, mSOL out amount:{}",
+            "SOL out amount:{}, ticket out amount:{}, mSOL out amount:{}",
             sol_out_amount,
+            ticket_out_amount,
             msol_out_amount
         );
 
@@ -182,6 +298,16 @@ impl<'info> RemoveLiquidity<'info> {
         self.state.liq_pool.on_lp_burn(tokens);
 
         msg!("end instruction rem-liq");
+        emit!(RemoveLiquidityEvent {
+            state: *self.state.to_account_info().key,
+            lp_owner: self.burn_from.owner,
+            lp_burned: tokens,
+            lamports_out: sol_out_amount,
+            ticket_lamports_out: ticket_out_amount,
+            msol_out: msol_out_amount,
+            msol_price: self.state.msol_price,
+            epoch: current_epoch()?,
+        });
         Ok(())
     }
 }
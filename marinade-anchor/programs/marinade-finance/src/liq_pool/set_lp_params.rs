@@ -1,9 +1,28 @@
-use crate::{error::CommonError, Fee, SetLpParams};
+use crate::{
+    error::CommonError,
+    liq_pool::{FeeCurve, LiqPool},
+    Fee, SetLpParams, State,
+};
 use anchor_lang::prelude::ProgramResult;
 use anchor_lang::solana_program::native_token::sol_to_lamports;
 
 impl<'info> SetLpParams<'info> {
-    fn check_fees(&self, min_fee: Fee, max_fee: Fee) -> ProgramResult {
+    pub fn process(
+        &mut self,
+        min_fee: Fee,
+        max_fee: Fee,
+        liquidity_target: u64,
+        fee_curve: FeeCurve,
+    ) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        self.state.check_not_timelocked()?;
+        self.state
+            .set_lp_params(min_fee, max_fee, liquidity_target, fee_curve)
+    }
+}
+
+impl LiqPool {
+    fn check_fees(min_fee: Fee, max_fee: Fee) -> ProgramResult {
         min_fee.check()?;
         max_fee.check()?;
         //hard-limit, max liquid unstake-fee of 10%
@@ -16,22 +35,33 @@ impl<'info> SetLpParams<'info> {
         Ok(())
     }
 
-    fn check_liquidity_target(&self, liquidity_target: u64) -> ProgramResult {
+    fn check_liquidity_target(liquidity_target: u64) -> ProgramResult {
         if liquidity_target < sol_to_lamports(50.0) {
             Err(CommonError::LiquidityTargetTooLow.into())
         } else {
             Ok(())
         }
     }
+}
 
-    pub fn process(&mut self, min_fee: Fee, max_fee: Fee, liquidity_target: u64) -> ProgramResult {
-        self.state.check_admin_authority(self.admin_authority.key)?;
-        self.check_fees(min_fee, max_fee)?;
-        self.check_liquidity_target(liquidity_target)?;
+impl State {
+    /// Used by SetLpParams and ExecuteParamsChange
+    pub fn set_lp_params(
+        &mut self,
+        min_fee: Fee,
+        max_fee: Fee,
+        liquidity_target: u64,
+        fee_curve: FeeCurve,
+    ) -> ProgramResult {
+        LiqPool::check_fees(min_fee, max_fee)?;
+        LiqPool::check_liquidity_target(liquidity_target)?;
+        // curve points must lie between the new min/max fees and below the new target
+        fee_curve.check(min_fee, max_fee, liquidity_target)?;
 
-        self.state.liq_pool.lp_min_fee = min_fee;
-        self.state.liq_pool.lp_max_fee = max_fee;
-        self.state.liq_pool.lp_liquidity_target = liquidity_target;
+        self.liq_pool.lp_min_fee = min_fee;
+        self.liq_pool.lp_max_fee = max_fee;
+        self.liq_pool.lp_liquidity_target = liquidity_target;
+        self.liq_pool_fee_curve = fee_curve;
         Ok(())
     }
 }
@@ -1,10 +1,9 @@
-use std::io::Cursor;
-
 use anchor_lang::prelude::*;
 use borsh::BorshSchema;
+use bytemuck::Pod;
 use std::convert::TryFrom;
 
-use crate::error::CommonError;
+use crate::{checks::check_address, error::CommonError};
 
 #[derive(Default, Clone, AnchorSerialize, AnchorDeserialize, BorshSchema, Debug)]
 pub struct List {
@@ -67,10 +66,10 @@ impl List {
         Ok(())
     }
 
-    /*
     pub fn check_account<'info>(
         &self,
         account: &AccountInfo<'info>,
+        discriminator: &[u8; 8],
         list_name: &str,
     ) -> ProgramResult {
         check_address(account.key, &self.account, list_name)?;
@@ -83,18 +82,18 @@ impl List {
             return Err(ProgramError::AccountDataTooSmall);
         }
 
-        if data[0..8] != D::discriminator() {
+        if data[0..8] != *discriminator {
             msg!(
                 "{} account must have discriminator {:?}. Got {:?}",
                 list_name,
-                D::discriminator(),
+                discriminator,
                 &data[0..8]
             );
             return Err(ProgramError::InvalidAccountData);
         }
 
         Ok(())
-    }*/
+    }
 
     pub fn item_size(&self) -> u32 {
         self.item_size
@@ -123,12 +122,23 @@ impl List {
         .unwrap_or(std::u32::MAX)) // for zst element (why you are using it in list?)
     }
 
-    pub fn get<I: AnchorDeserialize>(
-        &self,
-        data: &[u8],
-        index: u32,
-        list_name: &str,
-    ) -> Result<I, ProgramError> {
+    // items are stored with their in-memory (Pod) layout, so reading and writing is a plain copy.
+    // Lists with a smaller item_size keep an older layout and must be migrated first
+    fn check_item_size<I: Pod>(&self, list_name: &str) -> ProgramResult {
+        if (self.item_size() as usize) < std::mem::size_of::<I>() {
+            msg!(
+                "list {} items have {} bytes, {} needed",
+                list_name,
+                self.item_size(),
+                std::mem::size_of::<I>()
+            );
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(())
+    }
+
+    pub fn get<I: Pod>(&self, data: &[u8], index: u32, list_name: &str) -> Result<I, ProgramError> {
+        self.check_item_size::<I>(list_name)?;
         if index >= self.len() {
             msg!(
                 "list {} index out of bounds ({}/{})",
@@ -139,11 +149,13 @@ impl List {
             return Err(ProgramError::InvalidArgument);
         }
         let start = 8 + (index * self.item_size()) as usize;
-        I::deserialize(&mut &data[start..(start + self.item_size() as usize)])
-            .map_err(|err| ProgramError::BorshIoError(err.to_string()))
+        let mut item = I::zeroed();
+        bytemuck::bytes_of_mut(&mut item)
+            .copy_from_slice(&data[start..(start + std::mem::size_of::<I>())]);
+        Ok(item)
     }
 
-    pub fn set<I: AnchorSerialize>(
+    pub fn set<I: Pod>(
         &self,
         data: &mut [u8],
         index: u32,
@@ -154,6 +166,7 @@ impl List {
             msg!("Can not modify list {} while changing list's account");
             return Err(ProgramError::InvalidAccountData);
         }
+        self.check_item_size::<I>(list_name)?;
         if index >= self.len() {
             msg!(
                 "list {} index out of bounds ({}/{})",
@@ -165,22 +178,17 @@ impl List {
         }
 
         let start = 8 + (index * self.item_size()) as usize;
-        let mut cursor = Cursor::new(&mut data[start..(start + self.item_size() as usize)]);
-        item.serialize(&mut cursor)?;
+        data[start..(start + std::mem::size_of::<I>())].copy_from_slice(bytemuck::bytes_of(&item));
 
         Ok(())
     }
 
-    pub fn push<I: AnchorSerialize>(
-        &mut self,
-        data: &mut [u8],
-        item: I,
-        list_name: &str,
-    ) -> ProgramResult {
+    pub fn push<I: Pod>(&mut self, data: &mut [u8], item: I, list_name: &str) -> ProgramResult {
         if self.new_account != Pubkey::default() {
             msg!("Can not modify list {} while changing list's account");
             return Err(ProgramError::InvalidAccountData);
         }
+        self.check_item_size::<I>(list_name)?;
         let capacity = self.capacity(data.len())?;
         if self.len() >= capacity {
             msg!("list {} with capacity {} is full", list_name, capacity);
@@ -188,8 +196,7 @@ impl List {
         }
 
         let start = 8 + (self.len() * self.item_size()) as usize;
-        let mut cursor = Cursor::new(&mut data[start..(start + self.item_size() as usize)]);
-        item.serialize(&mut cursor)?;
+        data[start..(start + std::mem::size_of::<I>())].copy_from_slice(bytemuck::bytes_of(&item));
 
         self.count += 1;
 
@@ -225,16 +232,74 @@ impl List {
         Ok(())
     }
 
-    /*
+    /// Copies up to max_copy_count items into new_account (must be zeroed),
+    /// switches the list to it and returns true when all the items are copied.
+    /// The list can not be modified until the change is finished
     pub fn change_account<'info>(
         &mut self,
         old_account: &AccountInfo<'info>,
         new_account: &AccountInfo<'info>,
+        discriminator: &[u8; 8],
+        max_copy_count: u32,
+        list_name: &str,
+    ) -> Result<bool, ProgramError> {
+        let item_size = self.item_size();
+        self.copy_into(
+            old_account,
+            new_account,
+            discriminator,
+            discriminator,
+            item_size,
+            max_copy_count,
+            |old_item, new_item| {
+                new_item.copy_from_slice(old_item);
+                Ok(())
+            },
+            list_name,
+        )
+    }
+
+    /// Same as change_account for a list stored with an older item layout
+    /// (marked by legacy_discriminator): every item is rewritten by convert
+    /// into new_item_size bytes and the new account gets discriminator
+    #[allow(clippy::too_many_arguments)]
+    pub fn migrate_account<'info>(
+        &mut self,
+        old_account: &AccountInfo<'info>,
+        new_account: &AccountInfo<'info>,
+        legacy_discriminator: &[u8; 8],
+        discriminator: &[u8; 8],
+        new_item_size: u32,
+        max_copy_count: u32,
+        convert: impl Fn(&[u8], &mut [u8]) -> ProgramResult,
+        list_name: &str,
+    ) -> Result<bool, ProgramError> {
+        self.copy_into(
+            old_account,
+            new_account,
+            legacy_discriminator,
+            discriminator,
+            new_item_size,
+            max_copy_count,
+            convert,
+            list_name,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn copy_into<'info>(
+        &mut self,
+        old_account: &AccountInfo<'info>,
+        new_account: &AccountInfo<'info>,
+        old_discriminator: &[u8; 8],
+        new_discriminator: &[u8; 8],
+        new_item_size: u32,
         max_copy_count: u32,
+        convert: impl Fn(&[u8], &mut [u8]) -> ProgramResult,
         list_name: &str,
     ) -> Result<bool, ProgramError> {
-        self.check_account(old_account, list_name)?;
-        let data_size = 8 + (self.len() * self.item_size()) as usize;
+        self.check_account(old_account, old_discriminator, list_name)?;
+        let data_size = 8 + (self.len() * new_item_size) as usize;
         let mut new_data = new_account.data.borrow_mut();
         if self.new_account != *new_account.key {
             if self.new_account != Pubkey::default() {
@@ -254,7 +319,7 @@ impl List {
                 );
                 return Err(ProgramError::AccountDataTooSmall);
             }
-            self.init_account(new_account, list_name)?;
+            self.init_account(new_discriminator, &mut new_data, list_name)?;
 
             self.new_account = *new_account.key;
             self.copied_count = 0;
@@ -262,19 +327,26 @@ impl List {
 
         let copy_count = max_copy_count.min(self.len() - self.copied_count);
 
-        let start = 8 + (self.copied_count * self.item_size()) as usize;
-        let stop = start + (self.item_size() * copy_count) as usize;
-        new_data[start..stop].copy_from_slice(&old_account.data.borrow()[start..stop]);
+        let old_data = old_account.data.borrow();
+        for index in self.copied_count..self.copied_count + copy_count {
+            let old_start = 8 + (index * self.item_size()) as usize;
+            let new_start = 8 + (index * new_item_size) as usize;
+            convert(
+                &old_data[old_start..old_start + self.item_size() as usize],
+                &mut new_data[new_start..new_start + new_item_size as usize],
+            )?;
+        }
         self.copied_count += copy_count;
         if self.copied_count == self.len() {
             self.account = self.new_account;
+            self.item_size = new_item_size;
             self.new_account = Pubkey::default();
             self.copied_count = 0;
             Ok(true)
         } else {
             Ok(false)
         }
-    }*/
+    }
 
     /*
     pub fn iter<'a, 'info>(
@@ -356,7 +428,7 @@ where
 mod tests {
     use std::collections::BTreeSet;
 
-    use anchor_lang::prelude::{ProgramError, ProgramResult, Pubkey};
+    use anchor_lang::prelude::{AccountInfo, ProgramError, ProgramResult, Pubkey};
 
     use crate::list::List;
 
@@ -399,4 +471,156 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_change_account() -> ProgramResult {
+        const COUNT: usize = 10;
+        const NEW_CAPACITY: usize = 25;
+        let discriminator = &[1, 2, 3, 4, 5, 6, 7, 8];
+        let owner = Pubkey::new_unique();
+        let old_key = Pubkey::new_unique();
+        let mut old_lamports = 0;
+        let mut old_data = [0; 8 + COUNT * 4];
+        let new_key = Pubkey::new_unique();
+        let mut new_lamports = 0;
+        let mut new_data = [0; 8 + NEW_CAPACITY * 4];
+
+        let mut list = List::new(discriminator, 4u32, old_key, &mut old_data, "test_list")?;
+        for i in 0..COUNT {
+            list.push::<u32>(&mut old_data, 100 + i as u32, "test_list")?;
+        }
+        let old_account = AccountInfo::new(
+            &old_key,
+            false,
+            true,
+            &mut old_lamports,
+            &mut old_data,
+            &owner,
+            false,
+            0,
+        );
+        let new_account = AccountInfo::new(
+            &new_key,
+            false,
+            true,
+            &mut new_lamports,
+            &mut new_data,
+            &owner,
+            false,
+            0,
+        );
+
+        // copied by chunks
+        assert!(!list.change_account(&old_account, &new_account, discriminator, 4, "test_list")?);
+        assert!(list.is_changing_account());
+        assert!(list
+            .push::<u32>(&mut old_account.data.borrow_mut(), 0, "test_list")
+            .is_err());
+        assert!(!list.change_account(&old_account, &new_account, discriminator, 4, "test_list")?);
+        assert!(list.change_account(&old_account, &new_account, discriminator, 4, "test_list")?);
+        assert!(!list.is_changing_account());
+        assert_eq!(list.account, new_key);
+
+        let new_data = new_account.data.borrow();
+        assert_eq!(&new_data[0..8], discriminator);
+        assert_eq!(list.capacity(new_data.len())?, NEW_CAPACITY as u32);
+        for i in 0..COUNT {
+            assert_eq!(
+                list.get::<u32>(&new_data, i as u32, "test_list")?,
+                100 + i as u32
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_account() -> ProgramResult {
+        const COUNT: usize = 10;
+        let legacy_discriminator = &[1, 2, 3, 4, 5, 6, 7, 8];
+        let discriminator = &[8, 7, 6, 5, 4, 3, 2, 1];
+        let owner = Pubkey::new_unique();
+        let old_key = Pubkey::new_unique();
+        let mut old_lamports = 0;
+        // u32 items with 2 bytes of additional space
+        let mut old_data = [0; 8 + COUNT * 6];
+        let new_key = Pubkey::new_unique();
+        let mut new_lamports = 0;
+        let mut new_data = [0; 8 + COUNT * 8];
+
+        let mut list = List::new(
+            legacy_discriminator,
+            6u32,
+            old_key,
+            &mut old_data,
+            "test_list",
+        )?;
+        for i in 0..COUNT {
+            list.push::<u32>(&mut old_data, 100 + i as u32, "test_list")?;
+        }
+        // items bigger than item_size can not be read or written
+        assert!(list.get::<u64>(&old_data, 0, "test_list").is_err());
+        assert!(list.set::<u64>(&mut old_data, 0, 0, "test_list").is_err());
+        assert!(list.push::<u64>(&mut old_data, 0, "test_list").is_err());
+
+        let old_account = AccountInfo::new(
+            &old_key,
+            false,
+            true,
+            &mut old_lamports,
+            &mut old_data,
+            &owner,
+            false,
+            0,
+        );
+        let new_account = AccountInfo::new(
+            &new_key,
+            false,
+            true,
+            &mut new_lamports,
+            &mut new_data,
+            &owner,
+            false,
+            0,
+        );
+        // u32 items become u64 items
+        let convert = |old_item: &[u8], new_item: &mut [u8]| {
+            let mut value = [0; 4];
+            value.copy_from_slice(&old_item[..4]);
+            new_item.copy_from_slice(&(u32::from_le_bytes(value) as u64 * 3).to_le_bytes());
+            Ok(())
+        };
+        assert!(!list.migrate_account(
+            &old_account,
+            &new_account,
+            legacy_discriminator,
+            discriminator,
+            8,
+            6,
+            convert,
+            "test_list"
+        )?);
+        assert_eq!(list.item_size(), 6);
+        assert!(list.migrate_account(
+            &old_account,
+            &new_account,
+            legacy_discriminator,
+            discriminator,
+            8,
+            6,
+            convert,
+            "test_list"
+        )?);
+        assert_eq!(list.account, new_key);
+        assert_eq!(list.item_size(), 8);
+
+        let new_data = new_account.data.borrow();
+        assert_eq!(&new_data[0..8], discriminator);
+        for i in 0..COUNT {
+            assert_eq!(
+                list.get::<u64>(&new_data, i as u32, "test_list")?,
+                (100 + i as u64) * 3
+            );
+        }
+        Ok(())
+    }
 }
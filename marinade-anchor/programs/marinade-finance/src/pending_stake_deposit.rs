@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::{checks::check_address, ID};
+
+/// Activating stake account taken by DepositActivatingStakeAccount.
+/// mSOL is minted by FinishStakeDeposit (anyone can call it) once the stake is activated.
+/// The address is a PDA of the stake account, so a stake account can be pending (and listed by
+/// FinishStakeDeposit) only once
+#[account]
+#[derive(Debug)]
+pub struct PendingStakeDepositData {
+    pub state_address: Pubkey, // instance of marinade state this deposit belongs to
+    pub stake_account: Pubkey,
+    pub validator_vote: Pubkey,
+    pub mint_to: Pubkey,    // mSOL token account of the depositor
+    pub rent_payer: Pubkey, // receives the account lamports when finished
+    pub activation_epoch: u64,
+}
+
+impl PendingStakeDepositData {
+    pub const SEED: &'static [u8] = b"pending_deposit";
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 32 + 32 + 8;
+
+    pub fn find_address(state: &Pubkey, stake_account: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                Self::SEED,
+                &stake_account.to_bytes()[..32],
+            ],
+            &ID,
+        )
+    }
+
+    pub fn check(
+        &self,
+        state: &Pubkey,
+        stake_account: &Pubkey,
+        mint_to: &Pubkey,
+        rent_payer: &Pubkey,
+    ) -> ProgramResult {
+        if self.state_address == Pubkey::default() {
+            msg!("Pending stake deposit is already closed");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &self.state_address != state {
+            msg!(
+                "Pending stake deposit has wrong marinade instance {}",
+                self.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        check_address(stake_account, &self.stake_account, "stake_account")?;
+        check_address(mint_to, &self.mint_to, "mint_to")?;
+        check_address(rent_payer, &self.rent_payer, "rent_payer")
+    }
+
+    /// move all pending-deposit account lamports to the rent payer,
+    /// the account will be deleted because is no longer rent-exempt.
+    /// Anchor writes the data back on exit and the account could get the rent back in the same tx,
+    /// so the state address is cleared to make check() refuse it
+    pub fn close<'info>(
+        pending_deposit: &mut ProgramAccount<'info, Self>,
+        rent_payer: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        pending_deposit.state_address = Pubkey::default();
+        let pending_deposit = pending_deposit.to_account_info();
+        let rent_payer_starting_lamports = rent_payer.lamports();
+        **rent_payer.lamports.borrow_mut() = rent_payer_starting_lamports
+            .checked_add(pending_deposit.lamports())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        **pending_deposit.lamports.borrow_mut() = 0;
+        Ok(())
+    }
+}
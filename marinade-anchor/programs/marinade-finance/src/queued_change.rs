@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{checks::check_address, liq_pool::FeeCurve, ConfigMarinadeParams, Fee};
+
+/// Params change queued by the admin. Anyone can execute it from earliest_execution_epoch
+#[account]
+#[derive(Debug)]
+pub struct QueuedChangeData {
+    pub state_address: Pubkey, // instance of marinade state this change belongs to
+    pub rent_payer: Pubkey,    // receives the account lamports when executed or cancelled
+    pub earliest_execution_epoch: u64,
+    pub change_version: u8, // QueuedChangeData::CHANGE_VERSION when queued
+    pub change: ParamsChange,
+}
+
+impl QueuedChangeData {
+    /// serialized ParamsChange must fit here, with room for new params
+    pub const MAX_CHANGE_LEN: usize = 256;
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 1 + Self::MAX_CHANGE_LEN;
+    /// Appending Option fields to ConfigMarinadeParams keeps the version:
+    /// the zero padding of older changes reads as None.
+    /// Any other change of the ParamsChange layout must bump it
+    pub const CHANGE_VERSION: u8 = 1;
+
+    pub fn check(&self, state: &Pubkey, rent_payer: &Pubkey) -> ProgramResult {
+        if self.state_address == Pubkey::default() {
+            msg!("Queued change is already closed");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if &self.state_address != state {
+            msg!(
+                "Queued change has wrong marinade instance {}",
+                self.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        check_address(rent_payer, &self.rent_payer, "rent_payer")
+    }
+
+    pub fn check_change_version(&self) -> ProgramResult {
+        if self.change_version != Self::CHANGE_VERSION {
+            msg!(
+                "Queued change version {} is outdated, expected {}. Cancel it and queue again",
+                self.change_version,
+                Self::CHANGE_VERSION
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// move all queued-change account lamports to the rent payer,
+    /// the account will be deleted because is no longer rent-exempt.
+    /// Anchor writes the data back on exit and the account could get the rent back in the same tx,
+    /// so the state address is cleared to make check() refuse it
+    pub fn close<'info>(
+        queued_change: &mut ProgramAccount<'info, Self>,
+        rent_payer: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        queued_change.state_address = Pubkey::default();
+        let queued_change = queued_change.to_account_info();
+        let rent_payer_starting_lamports = rent_payer.lamports();
+        **rent_payer.lamports.borrow_mut() = rent_payer_starting_lamports
+            .checked_add(queued_change.lamports())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        **queued_change.lamports.borrow_mut() = 0;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum ParamsChange {
+    ConfigMarinade(ConfigMarinadeParams),
+    SetLpParams {
+        min_fee: Fee,
+        max_fee: Fee,
+        liquidity_target: u64,
+        fee_curve: FeeCurve,
+    },
+}
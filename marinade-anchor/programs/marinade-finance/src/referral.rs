@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use crate::{checks::check_owner_program, Fee, ID};
+
+pub mod create_referral;
+pub mod update_referral;
+
+/// Partner account created by the admin. Deposit and LiquidUnstake take it as an optional remaining account
+/// to account the routed volume. LiquidUnstake also needs the partner mSOL account after it
+/// to pay treasury_cut_share of the treasury cut there
+#[account]
+#[derive(Debug)]
+pub struct ReferralStateData {
+    pub state_address: Pubkey, // instance of marinade state this referral belongs to
+    pub referral_code: [u8; ReferralStateData::CODE_LEN], // zero padded
+    pub partner_account: Pubkey,
+    pub msol_token_account: Pubkey, // gets the fee share
+    pub treasury_cut_share: Fee,
+    pub pause: u8, // 1 when paused by the admin_authority, 0 otherwise
+
+    // routed volume
+    pub deposit_sol_amount: u64,
+    pub deposit_sol_operations: u64,
+    pub liq_unstake_msol_amount: u64,
+    pub liq_unstake_operations: u64,
+    pub msol_fees_paid: u64,
+}
+
+impl ReferralStateData {
+    pub const CODE_LEN: usize = 16;
+    pub const SPACE: usize = 8 + 32 + Self::CODE_LEN + 32 + 32 + 4 + 1 + 8 * 5;
+
+    pub fn referral_code(code: &str) -> Option<[u8; Self::CODE_LEN]> {
+        if code.is_empty() || code.len() > Self::CODE_LEN {
+            return None;
+        }
+        let mut result = [0; Self::CODE_LEN];
+        result[..code.len()].copy_from_slice(code.as_bytes());
+        Some(result)
+    }
+
+    pub fn code(&self) -> String {
+        String::from_utf8_lossy(&self.referral_code)
+            .trim_end_matches('\0')
+            .to_string()
+    }
+
+    /// Reads the referral passed in the remaining accounts
+    pub fn load(referral_state: &AccountInfo, state: &Pubkey) -> Result<Self, ProgramError> {
+        check_owner_program(referral_state, &ID, "referral_state")?;
+        if !referral_state.is_writable {
+            msg!("Referral state {} must be writable", referral_state.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let referral: Self =
+            AccountDeserialize::try_deserialize(&mut referral_state.data.borrow().as_ref())?;
+        if &referral.state_address != state {
+            msg!(
+                "Referral has wrong marinade instance {}",
+                referral.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(referral)
+    }
+
+    pub fn save(&self, referral_state: &AccountInfo) -> ProgramResult {
+        let mut data = referral_state.data.borrow_mut();
+        let mut data: &mut [u8] = &mut data;
+        self.try_serialize(&mut data)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause != 0
+    }
+
+    pub fn on_deposit(&mut self, lamports: u64) {
+        self.deposit_sol_amount = self.deposit_sol_amount.saturating_add(lamports);
+        self.deposit_sol_operations = self.deposit_sol_operations.saturating_add(1);
+    }
+
+    pub fn on_liquid_unstake(&mut self, msol_amount: u64, msol_fee_paid: u64) {
+        self.liq_unstake_msol_amount = self.liq_unstake_msol_amount.saturating_add(msol_amount);
+        self.liq_unstake_operations = self.liq_unstake_operations.saturating_add(1);
+        self.msol_fees_paid = self.msol_fees_paid.saturating_add(msol_fee_paid);
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{checks::check_token_mint, referral::ReferralStateData, CreateReferral, Fee};
+
+impl<'info> CreateReferral<'info> {
+    // fn create_referral()
+    pub fn process(&mut self, referral_code: String, treasury_cut_share: Fee) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        check_token_mint(
+            &self.msol_token_account,
+            self.state.msol_mint,
+            "msol_token_account",
+        )?;
+        treasury_cut_share.check()?;
+        let code = ReferralStateData::referral_code(&referral_code).ok_or_else(|| {
+            msg!(
+                "Referral code must have 1..={} bytes",
+                ReferralStateData::CODE_LEN
+            );
+            ProgramError::InvalidArgument
+        })?;
+
+        msg!(
+            "Create referral {} for partner {} with {} of the treasury cut",
+            referral_code,
+            self.partner_account.key,
+            treasury_cut_share
+        );
+        self.referral_state.state_address = *self.state.to_account_info().key;
+        self.referral_state.referral_code = code;
+        self.referral_state.partner_account = *self.partner_account.key;
+        self.referral_state.msol_token_account = *self.msol_token_account.to_account_info().key;
+        self.referral_state.treasury_cut_share = treasury_cut_share;
+        self.referral_state.pause = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{Fee, UpdateReferral};
+
+impl<'info> UpdateReferral<'info> {
+    // fn update_referral()
+    pub fn process(
+        &mut self,
+        pause: Option<bool>,
+        treasury_cut_share: Option<Fee>,
+    ) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        if &self.referral_state.state_address != self.state.to_account_info().key {
+            msg!(
+                "Referral has wrong marinade instance {}",
+                self.referral_state.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if let Some(pause) = pause {
+            msg!("Referral {} pause {}", self.referral_state.code(), pause);
+            self.referral_state.pause = pause as u8;
+        }
+        if let Some(treasury_cut_share) = treasury_cut_share {
+            treasury_cut_share.check()?;
+            msg!(
+                "Referral {} treasury cut share {}",
+                self.referral_state.code(),
+                treasury_cut_share
+            );
+            self.referral_state.treasury_cut_share = treasury_cut_share;
+        }
+        Ok(())
+    }
+}
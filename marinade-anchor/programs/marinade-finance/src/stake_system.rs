@@ -1,23 +1,46 @@
-use crate::{checks::check_address, list::List, located::Located, State, ID};
+use crate::{
+    checks::{check_address, check_owner_program},
+    list::List,
+    located::Located,
+    State, ID,
+};
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::clock::Epoch;
+use anchor_lang::solana_program::{
+    clock::Epoch,
+    program::invoke,
+    stake::{self, state::StakeState},
+    system_instruction, system_program,
+};
+use bytemuck::{Pod, Zeroable};
 
 pub mod deactivate_stake;
 pub mod deposit_stake_account;
 pub mod emergency_unstake;
+pub mod finish_stake_deposit;
 pub mod merge;
 pub mod stake_reserve;
+pub mod withdraw_stake_account;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+/// Zero-copy stake list item. Must have no implicit padding
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct StakeRecord {
     pub stake_account: Pubkey,
     pub last_update_delegated_lamports: u64,
     pub last_update_epoch: u64,
     pub is_emergency_unstaking: u8, // 1 for cooling down after emergency unstake, 0 otherwise
+    pub reserved: [u8; 7],
 }
 
+unsafe impl Zeroable for StakeRecord {}
+unsafe impl Pod for StakeRecord {}
+
 impl StakeRecord {
-    pub const DISCRIMINATOR: &'static [u8; 8] = b"staker__";
+    pub const DISCRIMINATOR: &'static [u8; 8] = b"staker_2";
+    /// Discriminator of the lists created before the zero-copy layout
+    pub const LEGACY_DISCRIMINATOR: &'static [u8; 8] = b"staker__";
+    /// Legacy record size without the additional record space
+    pub const LEGACY_SIZE: usize = 49;
 
     pub fn new(stake_account: &Pubkey, delegated_lamports: u64, clock: &Clock) -> Self {
         Self {
@@ -25,8 +48,29 @@ impl StakeRecord {
             last_update_delegated_lamports: delegated_lamports,
             last_update_epoch: clock.epoch,
             is_emergency_unstaking: 0,
+            reserved: [0; 7],
         }
     }
+
+    /// Rewrites a record stored with the Borsh layout used before the zero-copy one
+    /// (the first LEGACY_SIZE bytes of the item) into the current layout.
+    /// Used by grow_list to migrate the list
+    pub fn migrate_legacy(old_item: &[u8], new_item: &mut [u8]) -> ProgramResult {
+        if old_item.len() < Self::LEGACY_SIZE {
+            msg!(
+                "Legacy stake record has {} bytes, {} expected",
+                old_item.len(),
+                Self::LEGACY_SIZE
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // the legacy layout is a prefix of the current one
+        for byte in new_item.iter_mut() {
+            *byte = 0;
+        }
+        new_item[..Self::LEGACY_SIZE].copy_from_slice(&old_item[..Self::LEGACY_SIZE]);
+        Ok(())
+    }
 }
 
 #[derive(Clone, AnchorSerialize, AnchorDeserialize, Debug)]
@@ -56,7 +100,7 @@ impl StakeSystem {
 
     pub fn bytes_for_list(count: u32, additional_record_space: u32) -> u32 {
         List::bytes_for(
-            StakeRecord::default().try_to_vec().unwrap().len() as u32 + additional_record_space,
+            std::mem::size_of::<StakeRecord>() as u32 + additional_record_space,
             count,
         )
     }
@@ -88,7 +132,7 @@ impl StakeSystem {
     ) -> Result<Self, ProgramError> {
         let stake_list = List::new(
             StakeRecord::DISCRIMINATOR,
-            StakeRecord::default().try_to_vec().unwrap().len() as u32 + additional_record_space,
+            std::mem::size_of::<StakeRecord>() as u32 + additional_record_space,
             stake_list_account,
             stake_list_data,
             "stake_list",
@@ -151,7 +195,14 @@ impl StakeSystem {
 
     pub fn check_stake_list<'info>(&self, stake_list: &AccountInfo<'info>) -> ProgramResult {
         check_address(stake_list.key, self.stake_list_address(), "stake_list")?;
-        if &stake_list.data.borrow().as_ref()[0..8] != StakeRecord::DISCRIMINATOR {
+        let stake_list_data = stake_list.data.borrow();
+        let discriminator = &stake_list_data[0..8];
+        // lists created before the zero-copy layout keep the legacy record layout
+        if discriminator == StakeRecord::LEGACY_DISCRIMINATOR {
+            msg!("Stake list has the legacy record layout. Migrate it with grow_list");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if discriminator != StakeRecord::DISCRIMINATOR {
             msg!("Wrong stake list account discriminator");
             return Err(ProgramError::InvalidAccountData);
         }
@@ -159,6 +210,73 @@ impl StakeSystem {
     }
 }
 
+/// Makes split_stake_account ready to be a stake::instruction::split destination.
+/// Used by DeactivateStake and WithdrawStakeAccount
+pub fn prepare_split_stake_account<'info>(
+    split_stake_account: &AccountInfo<'info>,
+    split_stake_rent_payer: &AccountInfo<'info>,
+    rent: &Rent,
+    system_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    let stake_accout_len = std::mem::size_of::<StakeState>();
+    if split_stake_account.owner == &system_program::ID {
+        // empty account
+        invoke(
+            &system_instruction::create_account(
+                split_stake_rent_payer.key,
+                split_stake_account.key,
+                rent.minimum_balance(stake_accout_len),
+                stake_accout_len as u64,
+                &stake::program::ID,
+            ),
+            &[
+                system_program.clone(),
+                split_stake_rent_payer.clone(),
+                split_stake_account.clone(),
+            ],
+        )?;
+    } else {
+        // ready unitialized stake (needed for testing because solana_program_test does not support system_instruction::create_account)
+        check_owner_program(
+            split_stake_account,
+            &stake::program::ID,
+            "split_stake_account",
+        )?;
+        if split_stake_account.data_len() < stake_accout_len {
+            msg!(
+                "Split stake account {} must have at least {} bytes (got {})",
+                split_stake_account.key,
+                stake_accout_len,
+                split_stake_account.data_len()
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !rent.is_exempt(
+            split_stake_account.lamports(),
+            split_stake_account.data_len(),
+        ) {
+            msg!(
+                "Split stake account {} must be rent-exempt",
+                split_stake_account.key
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+        match bincode::deserialize(&split_stake_account.data.as_ref().borrow())
+            .map_err(|err| ProgramError::BorshIoError(err.to_string()))?
+        {
+            StakeState::Uninitialized => (),
+            _ => {
+                msg!(
+                    "Split stake {} must be uninitialized",
+                    split_stake_account.key
+                );
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+    }
+    Ok(())
+}
+
 pub trait StakeSystemHelpers {
     fn stake_withdraw_authority(&self) -> Pubkey;
     fn with_stake_withdraw_authority_seeds<R, F: FnOnce(&[&[u8]]) -> R>(&self, f: F) -> R;
@@ -1,12 +1,15 @@
-use crate::{checks::check_owner_program, stake_system::StakeSystemHelpers};
+use crate::{
+    checks::check_owner_program,
+    events::DeactivateStakeEvent,
+    stake_system::{prepare_split_stake_account, StakeSystemHelpers},
+};
 use std::convert::TryFrom;
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
     program::{invoke, invoke_signed},
-    stake::program as stake_program,
     stake::{self, state::StakeState},
-    system_instruction, system_program,
+    system_program,
 };
 
 use crate::{checks::check_address, state::StateHelpers, DeactivateStake};
@@ -112,7 +115,6 @@ impl<'info> DeactivateStake<'info> {
         // compute target for this particular validator (total_stake_target * score/total_score)
         let validator_stake_target = self
             .state
-            .validator_system
             .validator_stake_target(&validator, total_stake_target)?;
 
         // compute how much we should unstake from this validator
@@ -141,7 +143,27 @@ impl<'info> DeactivateStake<'info> {
             },
         );
 
-        let unstaked_amount = if stake_account_target < 2 * self.state.stake_system.min_stake {
+        // whole stake deactivation must not take the validator under its min_stake floor
+        // while the stake can fund all the floors
+        if stake_account_target < 2 * self.state.stake_system.min_stake
+            && self.state.floors_fundable(total_stake_target)
+            && validator
+                .active_balance
+                .saturating_sub(stake.last_update_delegated_lamports)
+                < validator.min_stake
+        {
+            msg!(
+                "Can not deactivate whole stake {}. Validator {} must keep min stake {}",
+                stake.stake_account,
+                validator.validator_account,
+                validator.min_stake
+            );
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+
+        let (unstaked_amount, split_stake_account) = if stake_account_target
+            < 2 * self.state.stake_system.min_stake
+        {
             // unstake all if what will remain in the account is < twice min_stake
             msg!("Deactivate whole stake {}", stake.stake_account);
             // Do not check and set validator.last_stake_delta_epoch here because it is possible to run
@@ -195,7 +217,7 @@ impl<'info> DeactivateStake<'info> {
                 }
             }
 
-            stake.last_update_delegated_lamports
+            (stake.last_update_delegated_lamports, Pubkey::default())
         } else {
             // we must perform partial unstake
             // Update validator.last_stake_delta_epoch for split-stakes only because probably we need to unstake multiple whole stakes for the same validator
@@ -236,62 +258,12 @@ impl<'info> DeactivateStake<'info> {
                 &self.clock,
             )?;
 
-            let stake_accout_len = std::mem::size_of::<StakeState>();
-            if self.split_stake_account.owner == &system_program::ID {
-                // empty account
-                invoke(
-                    &system_instruction::create_account(
-                        self.split_stake_rent_payer.key,
-                        self.split_stake_account.key,
-                        self.rent.minimum_balance(stake_accout_len),
-                        stake_accout_len as u64,
-                        &stake_program::ID,
-                    ),
-                    &[
-                        self.system_program.clone(),
-                        self.split_stake_rent_payer.clone(),
-                        self.split_stake_account.clone(),
-                    ],
-                )?;
-            } else {
-                // ready unitialized stake (needed for testing because solana_program_test does not support system_instruction::create_account)
-                check_owner_program(
-                    &self.split_stake_account,
-                    &stake::program::ID,
-                    "split_stake_account",
-                )?;
-                if self.split_stake_account.data_len() < stake_accout_len {
-                    msg!(
-                        "Split stake account {} must have at least {} bytes (got {})",
-                        self.split_stake_account.key,
-                        stake_accout_len,
-                        self.split_stake_account.data_len()
-                    );
-                    return Err(ProgramError::InvalidAccountData);
-                }
-                if !self.rent.is_exempt(
-                    self.split_stake_account.lamports(),
-                    self.split_stake_account.data_len(),
-                ) {
-                    msg!(
-                        "Split stake account {} must be rent-exempt",
-                        self.split_stake_account.key
-                    );
-                    return Err(ProgramError::InsufficientFunds);
-                }
-                match bincode::deserialize(&self.split_stake_account.data.as_ref().borrow())
-                    .map_err(|err| ProgramError::BorshIoError(err.to_string()))?
-                {
-                    StakeState::Uninitialized => (),
-                    _ => {
-                        msg!(
-                            "Split stake {} must be uninitialized",
-                            self.split_stake_account.key
-                        );
-                        return Err(ProgramError::InvalidAccountData);
-                    }
-                }
-            }
+            prepare_split_stake_account(
+                &self.split_stake_account,
+                &self.split_stake_rent_payer,
+                &self.rent,
+                &self.system_program,
+            )?;
 
             self.state.with_stake_deposit_authority_seeds(|seeds| {
                 let split_instruction = stake::instruction::split(
@@ -330,7 +302,7 @@ impl<'info> DeactivateStake<'info> {
             })?;
 
             stake.last_update_delegated_lamports -= split_amount;
-            split_amount
+            (split_amount, *self.split_stake_account.key)
         };
         // we now consider amount no longer "active" for this specific validator
         validator.active_balance = validator.active_balance.saturating_sub(unstaked_amount);
@@ -363,6 +335,15 @@ impl<'info> DeactivateStake<'info> {
             validator,
         )?;
 
+        emit!(DeactivateStakeEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: stake.stake_account,
+            split_stake_account,
+            validator_vote: validator.validator_account,
+            lamports: unstaked_amount,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
         Ok(())
     }
 }
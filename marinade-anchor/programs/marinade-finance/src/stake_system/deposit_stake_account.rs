@@ -3,7 +3,7 @@ use anchor_lang::solana_program::stake::instruction::LockupArgs;
 use anchor_lang::solana_program::{
     program::{invoke, invoke_signed},
     stake,
-    stake::state::StakeAuthorize,
+    stake::state::{Delegation, StakeAuthorize},
     system_instruction, system_program,
 };
 use anchor_spl::token::{mint_to, MintTo};
@@ -11,15 +11,23 @@ use anchor_spl::token::{mint_to, MintTo};
 use crate::error::CommonError;
 use crate::{
     checks::{check_address, check_owner_program, check_token_mint},
+    create_pda_account,
+    events::{DepositStakeAccountEvent, PendingStakeDepositEvent},
+    pending_stake_deposit::PendingStakeDepositData,
     stake_system::StakeSystemHelpers,
     state::StateHelpers,
-    DepositStakeAccount, ID,
+    validator_system::ValidatorSystem,
+    DepositActivatingStakeAccount, DepositStakeAccount, State, ID,
 };
 
 impl<'info> DepositStakeAccount<'info> {
     pub const WAIT_EPOCHS: u64 = 2;
-    // fn deposit_stake_account()
-    pub fn process(&mut self, validator_index: u32) -> ProgramResult {
+
+    /// Checks shared by DepositStakeAccount and DepositActivatingStakeAccount.
+    /// Returns the delegation and the undelegated lamports above the rent-exempt reserve
+    fn check_deposit(&self) -> Result<(Delegation, u64), ProgramError> {
+        self.state
+            .check_not_paused(State::PAUSE_DEPOSIT_STAKE_ACCOUNT)?;
         self.state
             .validator_system
             .check_validator_list(&self.validator_list)?;
@@ -28,6 +36,7 @@ impl<'info> DepositStakeAccount<'info> {
             .check_msol_mint(self.msol_mint.to_account_info().key)?;
         self.state
             .check_msol_mint_authority(self.msol_mint_authority.key)?;
+        self.state.check_reserve_address(self.reserve_pda.key)?;
         check_owner_program(&self.stake_account, &stake::program::ID, "stake")?;
         check_token_mint(&self.mint_to, self.state.msol_mint, "mint_to")?;
         check_owner_program(&self.rent_payer, &system_program::ID, "rent_payer")?;
@@ -61,6 +70,13 @@ impl<'info> DepositStakeAccount<'info> {
             ProgramError::InvalidAccountData
         })?;
 
+        check_address(
+            self.validator_vote.key,
+            &delegation.voter_pubkey,
+            "validator_vote",
+        )?;
+        self.state.check_commission(&self.validator_vote)?;
+
         if delegation.deactivation_epoch != std::u64::MAX {
             msg!(
                 "Deposited stake {} must not be cooling down",
@@ -69,23 +85,6 @@ impl<'info> DepositStakeAccount<'info> {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        if self.clock.epoch
-            < delegation
-                .activation_epoch
-                .checked_add(Self::WAIT_EPOCHS)
-                .unwrap()
-        {
-            msg!(
-                "Deposited stake {} is not activated yet. Wait for #{} epoch",
-                self.stake_account.to_account_info().key,
-                delegation
-                    .activation_epoch
-                    .checked_add(Self::WAIT_EPOCHS)
-                    .unwrap()
-            );
-            return Err(ProgramError::InvalidAccountData);
-        }
-
         if delegation.stake < self.state.stake_system.min_stake {
             msg!(
                 "Deposited stake {} has low amount of lamports {}. Need at least {}",
@@ -96,26 +95,65 @@ impl<'info> DepositStakeAccount<'info> {
             return Err(ProgramError::InsufficientFunds);
         }
 
-        if self.stake_account.to_account_info().lamports()
-            > delegation.stake + self.stake_account.meta().unwrap().rent_exempt_reserve
-        {
-            msg!(
-                "Stake account has {} extra lamports. Please withdraw it and try again",
-                self.stake_account.to_account_info().lamports()
-                    - (delegation.stake + self.stake_account.meta().unwrap().rent_exempt_reserve)
+        // extra rent or tips sent to the stake account are credited through the reserve
+        let extra_lamports = self
+            .stake_account
+            .to_account_info()
+            .lamports()
+            .saturating_sub(
+                delegation.stake + self.stake_account.meta().unwrap().rent_exempt_reserve,
             );
-            return Err(ProgramError::Custom(6212));
-        }
 
-        self.state.check_staking_cap(delegation.stake)?;
+        self.state
+            .check_staking_cap(delegation.stake + extra_lamports)?;
 
         let lockup = self.stake_account.lockup().unwrap();
-        // Check Lockup
-        if lockup.is_in_force(&self.clock, None) {
-            msg!("Can not deposit stake account with lockup");
+        // Check Lockup. Expired lockups are accepted, active ones need the custodian signature
+        if lockup.is_in_force(&self.clock, self.lockup_custodian()) {
+            msg!(
+                "Can not deposit stake account with lockup until epoch {} and timestamp {} without custodian {} signature",
+                lockup.epoch,
+                lockup.unix_timestamp,
+                lockup.custodian
+            );
             return Err(CommonError::AccountWithLockup.into());
         }
 
+        Ok((delegation, extra_lamports))
+    }
+
+    /// custodian key if it signed the transaction
+    fn lockup_custodian(&self) -> Option<&Pubkey> {
+        if self.custodian.is_signer {
+            Some(self.custodian.key)
+        } else {
+            None
+        }
+    }
+
+    /// lockup in force must be cleared and ignored by the stake program with the custodian signature
+    fn is_lockup_in_force(&self) -> bool {
+        self.stake_account
+            .lockup()
+            .unwrap()
+            .is_in_force(&self.clock, None)
+    }
+
+    fn is_activated(&self, delegation: &Delegation) -> bool {
+        self.clock.epoch
+            >= delegation
+                .activation_epoch
+                .checked_add(Self::WAIT_EPOCHS)
+                .unwrap()
+    }
+
+    /// Finds the validator (auto adding it if needed) and adds `lamports` to its active balance
+    fn add_to_validator(
+        &mut self,
+        validator_index: u32,
+        validator_vote: Pubkey,
+        lamports: u64,
+    ) -> ProgramResult {
         if validator_index == self.state.validator_system.validator_count() {
             if self.state.validator_system.auto_add_validator_enabled == 0 {
                 return Err(CommonError::InvalidValidator.into());
@@ -125,6 +163,11 @@ impl<'info> DepositStakeAccount<'info> {
                 &system_program::ID,
                 "duplication_flag",
             )?;
+            ValidatorSystem::check_not_blocked(
+                &self.blocked_flag,
+                self.state.to_account_info().key,
+                &validator_vote,
+            )?;
             if !self.rent.is_exempt(self.rent_payer.lamports(), 0) {
                 msg!(
                     "Rent payer must have at least {} lamports",
@@ -136,9 +179,9 @@ impl<'info> DepositStakeAccount<'info> {
             let state_address = *self.state.to_account_info().key;
             self.state.validator_system.add_with_balance(
                 &mut self.validator_list.data.as_ref().borrow_mut(),
-                delegation.voter_pubkey,
+                validator_vote,
                 0,
-                delegation.stake,
+                lamports,
                 &state_address,
                 self.duplication_flag.key,
             )?;
@@ -174,17 +217,17 @@ impl<'info> DepositStakeAccount<'info> {
                 .validator_system
                 .get(&self.validator_list.data.as_ref().borrow(), validator_index)?;
 
-            if delegation.voter_pubkey != validator.validator_account {
+            if validator_vote != validator.validator_account {
                 msg!(
                 "Deposited stake {} is delegated to {} but must be delegated to validator {}. Probably validator list is changed",
-                self.stake_account.to_account_info().key, delegation.voter_pubkey, validator.validator_account
+                self.stake_account.to_account_info().key, validator_vote, validator.validator_account
                 );
                 return Err(CommonError::InvalidValidator.into());
             }
 
             validator.active_balance = validator
                 .active_balance
-                .checked_add(delegation.stake)
+                .checked_add(lamports)
                 .ok_or(CommonError::CalculationFailure)?;
             self.state.validator_system.set(
                 &mut self.validator_list.data.as_ref().borrow_mut(),
@@ -192,7 +235,48 @@ impl<'info> DepositStakeAccount<'info> {
                 validator,
             )?;
         }
+        Ok(())
+    }
 
+    /// Moves undelegated lamports to the reserve. Must run while the depositor is still the withdrawer
+    fn withdraw_extra_to_reserve(&mut self, extra_lamports: u64) -> ProgramResult {
+        if extra_lamports > 0 {
+            msg!(
+                "Credit {} extra lamports from stake {}",
+                extra_lamports,
+                self.stake_account.to_account_info().key
+            );
+            let custodian = if self.is_lockup_in_force() {
+                Some(self.custodian.key)
+            } else {
+                None
+            };
+            invoke(
+                &stake::instruction::withdraw(
+                    self.stake_account.to_account_info().key,
+                    self.stake_authority.key,
+                    self.reserve_pda.key,
+                    extra_lamports,
+                    custodian,
+                ),
+                &[
+                    self.stake_program.clone(),
+                    self.stake_account.to_account_info(),
+                    self.reserve_pda.clone(),
+                    self.clock.to_account_info(),
+                    self.stake_history.clone(),
+                    self.stake_authority.clone(),
+                    self.custodian.clone(),
+                ],
+            )?;
+            self.state.on_transfer_to_reserve(extra_lamports);
+        }
+        Ok(())
+    }
+
+    /// Clears the old lockup and gives the staker and withdrawer authorities to marinade
+    fn take_stake_authorities(&mut self) -> ProgramResult {
+        let lockup = self.stake_account.lockup().unwrap();
         {
             let new_staker = self.state.stake_deposit_authority();
             let old_staker = self.stake_account.meta().unwrap().authorized.staker;
@@ -205,8 +289,13 @@ impl<'info> DepositStakeAccount<'info> {
                 return Err(ProgramError::InvalidAccountData);
             }
 
-            // Clean old lockup
+            // Clean old lockup. Only the custodian can do it while the lockup is in force
             if lockup.custodian != Pubkey::default() {
+                let lockup_authority = if self.is_lockup_in_force() {
+                    self.custodian.clone()
+                } else {
+                    self.stake_authority.clone()
+                };
                 invoke(
                     &stake::instruction::set_lockup(
                         &self.stake_account.key(),
@@ -215,12 +304,12 @@ impl<'info> DepositStakeAccount<'info> {
                             epoch: Some(0),
                             custodian: Some(Pubkey::default()),
                         },
-                        self.stake_authority.key,
+                        lockup_authority.key,
                     ),
                     &[
                         self.stake_program.clone(),
                         self.stake_account.to_account_info(),
-                        self.stake_authority.clone(),
+                        lockup_authority,
                     ],
                 )?;
             }
@@ -270,6 +359,54 @@ impl<'info> DepositStakeAccount<'info> {
                 ],
             )?;
         }
+        Ok(())
+    }
+
+    fn mint_msol(&mut self, msol_amount: u64) -> ProgramResult {
+        if msol_amount > 0 {
+            self.state.with_msol_mint_authority_seeds(|mint_seeds| {
+                mint_to(
+                    CpiContext::new_with_signer(
+                        self.token_program.clone(),
+                        MintTo {
+                            mint: self.msol_mint.to_account_info(),
+                            to: self.mint_to.to_account_info(),
+                            authority: self.msol_mint_authority.clone(),
+                        },
+                        &[mint_seeds],
+                    ),
+                    msol_amount,
+                )
+            })?;
+            self.state.on_msol_mint(msol_amount);
+        }
+        Ok(())
+    }
+
+    // fn deposit_stake_account()
+    pub fn process(&mut self, validator_index: u32) -> ProgramResult {
+        let (delegation, extra_lamports) = self.check_deposit()?;
+
+        if !self.is_activated(&delegation) {
+            msg!(
+                "Deposited stake {} is not activated yet. Wait for #{} epoch or use DepositActivatingStakeAccount",
+                self.stake_account.to_account_info().key,
+                delegation
+                    .activation_epoch
+                    .checked_add(Self::WAIT_EPOCHS)
+                    .unwrap()
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // at the price before the reserve gets the extra lamports
+        let msol_to_mint = self
+            .state
+            .calc_msol_from_lamports(delegation.stake + extra_lamports)?;
+
+        self.add_to_validator(validator_index, delegation.voter_pubkey, delegation.stake)?;
+        self.withdraw_extra_to_reserve(extra_lamports)?;
+        self.take_stake_authorities()?;
 
         self.state.stake_system.add(
             &mut self.stake_list.data.as_ref().borrow_mut(),
@@ -278,23 +415,7 @@ impl<'info> DepositStakeAccount<'info> {
             &self.clock,
         )?;
 
-        let msol_to_mint = self.state.calc_msol_from_lamports(delegation.stake)?;
-
-        self.state.with_msol_mint_authority_seeds(|mint_seeds| {
-            mint_to(
-                CpiContext::new_with_signer(
-                    self.token_program.clone(),
-                    MintTo {
-                        mint: self.msol_mint.to_account_info(),
-                        to: self.mint_to.to_account_info(),
-                        authority: self.msol_mint_authority.clone(),
-                    },
-                    &[mint_seeds],
-                ),
-                msol_to_mint,
-            )
-        })?;
-        self.state.on_msol_mint(msol_to_mint);
+        self.mint_msol(msol_to_mint)?;
 
         self.state.validator_system.total_active_balance = self
             .state
@@ -303,6 +424,93 @@ impl<'info> DepositStakeAccount<'info> {
             .checked_add(delegation.stake)
             .ok_or(CommonError::CalculationFailure)?;
 
+        emit!(DepositStakeAccountEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: *self.stake_account.to_account_info().key,
+            validator_vote: delegation.voter_pubkey,
+            mint_to: *self.mint_to.to_account_info().key,
+            delegated_lamports: delegation.stake,
+            extra_lamports,
+            msol_minted: msol_to_mint,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> DepositActivatingStakeAccount<'info> {
+    // fn deposit_activating_stake_account()
+    pub fn process(&mut self, validator_index: u32) -> ProgramResult {
+        let (delegation, extra_lamports) = self.check_deposit()?;
+
+        if self.is_activated(&delegation) {
+            msg!(
+                "Deposited stake {} is activated. Use DepositStakeAccount",
+                self.stake_account.to_account_info().key
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let state_address = *self.state.to_account_info().key;
+        let stake_account = *self.stake_account.to_account_info().key;
+        let (pending_deposit, bump_seed) =
+            PendingStakeDepositData::find_address(&state_address, &stake_account);
+        check_address(
+            self.pending_deposit.key,
+            &pending_deposit,
+            "pending_deposit",
+        )?;
+
+        // only the extra lamports are credited now, the delegation waits for FinishStakeDeposit
+        let msol_to_mint = self.state.calc_msol_from_lamports(extra_lamports)?;
+
+        self.add_to_validator(validator_index, delegation.voter_pubkey, 0)?;
+        self.withdraw_extra_to_reserve(extra_lamports)?;
+        self.take_stake_authorities()?;
+        self.mint_msol(msol_to_mint)?;
+
+        // the address may have been funded in advance to prevent the creation
+        create_pda_account(
+            &self.rent_payer,
+            &self.pending_deposit,
+            self.rent.minimum_balance(PendingStakeDepositData::SPACE),
+            PendingStakeDepositData::SPACE,
+            &ID,
+            &self.system_program,
+            &[
+                &state_address.to_bytes()[..32],
+                PendingStakeDepositData::SEED,
+                &stake_account.to_bytes()[..32],
+                &[bump_seed],
+            ],
+        )?;
+        let pending_deposit_data = PendingStakeDepositData {
+            state_address,
+            stake_account,
+            validator_vote: delegation.voter_pubkey,
+            mint_to: *self.mint_to.to_account_info().key,
+            rent_payer: *self.rent_payer.key,
+            activation_epoch: delegation.activation_epoch,
+        };
+        {
+            let mut data = self.pending_deposit.data.borrow_mut();
+            let mut data: &mut [u8] = &mut data;
+            pending_deposit_data.try_serialize(&mut data)?;
+        }
+
+        emit!(PendingStakeDepositEvent {
+            state: state_address,
+            stake_account,
+            pending_deposit,
+            validator_vote: delegation.voter_pubkey,
+            mint_to: *self.mint_to.to_account_info().key,
+            delegated_lamports: delegation.stake,
+            extra_lamports,
+            msol_minted: msol_to_mint,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::stake;
+use anchor_spl::token::{mint_to, MintTo};
+
+use crate::{
+    checks::{check_address, check_owner_program, check_token_mint},
+    error::CommonError,
+    events::DepositStakeAccountEvent,
+    pending_stake_deposit::PendingStakeDepositData,
+    stake_system::StakeSystemHelpers,
+    DepositStakeAccount, FinishStakeDeposit, State,
+};
+
+/// Permissionless crank: mints mSOL for a pending stake deposit once its stake is activated
+impl<'info> FinishStakeDeposit<'info> {
+    // fn finish_stake_deposit()
+    pub fn process(&mut self, validator_index: u32) -> ProgramResult {
+        self.state
+            .check_not_paused(State::PAUSE_DEPOSIT_STAKE_ACCOUNT)?;
+        self.state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+        self.state.stake_system.check_stake_list(&self.stake_list)?;
+        self.state
+            .check_msol_mint(self.msol_mint.to_account_info().key)?;
+        self.state
+            .check_msol_mint_authority(self.msol_mint_authority.key)?;
+        check_owner_program(&self.stake_account, &stake::program::ID, "stake")?;
+        check_token_mint(&self.mint_to, self.state.msol_mint, "mint_to")?;
+        check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+        self.pending_deposit.check(
+            self.state.to_account_info().key,
+            self.stake_account.to_account_info().key,
+            self.mint_to.to_account_info().key,
+            self.rent_payer.key,
+        )?;
+
+        let activated_epoch = self
+            .pending_deposit
+            .activation_epoch
+            .checked_add(DepositStakeAccount::WAIT_EPOCHS)
+            .unwrap();
+        if self.clock.epoch < activated_epoch {
+            msg!(
+                "Stake {} is not activated yet. Wait for #{} epoch",
+                self.stake_account.to_account_info().key,
+                activated_epoch
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // marinade is the staker since the deposit so the delegation can not be changed
+        let delegation = self.stake_account.delegation().unwrap();
+
+        let mut validator = self
+            .state
+            .validator_system
+            .get(&self.validator_list.data.as_ref().borrow(), validator_index)?;
+        if validator.validator_account != self.pending_deposit.validator_vote {
+            msg!(
+                "Pending stake {} is delegated to {} but must point to validator {}. Probably validator list is changed",
+                self.stake_account.to_account_info().key,
+                self.pending_deposit.validator_vote,
+                validator.validator_account
+            );
+            return Err(CommonError::InvalidValidator.into());
+        }
+
+        let msol_to_mint = self.state.calc_msol_from_lamports(delegation.stake)?;
+
+        validator.active_balance = validator
+            .active_balance
+            .checked_add(delegation.stake)
+            .ok_or(CommonError::CalculationFailure)?;
+        self.state.validator_system.set(
+            &mut self.validator_list.data.as_ref().borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        self.state.stake_system.add(
+            &mut self.stake_list.data.as_ref().borrow_mut(),
+            self.stake_account.to_account_info().key,
+            delegation.stake,
+            &self.clock,
+        )?;
+
+        self.state.with_msol_mint_authority_seeds(|mint_seeds| {
+            mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    MintTo {
+                        mint: self.msol_mint.to_account_info(),
+                        to: self.mint_to.to_account_info(),
+                        authority: self.msol_mint_authority.clone(),
+                    },
+                    &[mint_seeds],
+                ),
+                msol_to_mint,
+            )
+        })?;
+        self.state.on_msol_mint(msol_to_mint);
+
+        self.state.validator_system.total_active_balance = self
+            .state
+            .validator_system
+            .total_active_balance
+            .checked_add(delegation.stake)
+            .ok_or(CommonError::CalculationFailure)?;
+
+        PendingStakeDepositData::close(&mut self.pending_deposit, &self.rent_payer)?;
+
+        emit!(DepositStakeAccountEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: *self.stake_account.to_account_info().key,
+            validator_vote: validator.validator_account,
+            mint_to: *self.mint_to.to_account_info().key,
+            delegated_lamports: delegation.stake,
+            extra_lamports: 0,
+            msol_minted: msol_to_mint,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ use anchor_lang::solana_program::{
 use crate::{
     checks::{check_address, check_owner_program},
     error::CommonError,
+    events::MergeStakesEvent,
     stake_system::StakeSystemHelpers,
     MergeStakes,
 };
@@ -213,6 +214,19 @@ impl<'info> MergeStakes<'info> {
                 extra_delegated
             );
         }
+
+        emit!(MergeStakesEvent {
+            state: *self.state.to_account_info().key,
+            destination_stake: *self.destination_stake.to_account_info().key,
+            source_stake: *self.source_stake.to_account_info().key,
+            validator_vote: validator.validator_account,
+            destination_delegation: destination_delegation.stake,
+            source_delegation: source_delegation.stake,
+            extra_delegated,
+            returned_stake_rent,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
         Ok(())
     }
 }
@@ -1,10 +1,12 @@
 use crate::{
     checks::{check_address, check_owner_program},
     error::CommonError,
+    events::StakeReserveEvent,
     stake_system::StakeSystemHelpers,
     stake_wrapper::StakeWrapper,
     state::StateHelpers,
-    StakeReserve,
+    validator_system::ValidatorSystem,
+    StakeReserve, State,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
@@ -39,6 +41,7 @@ impl<'info> StakeReserve<'info> {
     /// stakes from available delta-stake in data.validator_index
     /// pub fn stake_reserve()
     pub fn process(&mut self, validator_index: u32) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_STAKE_RESERVE)?;
         sol_log_compute_units();
         msg!("Stake reserve");
         self.state
@@ -93,11 +96,6 @@ impl<'info> StakeReserve<'info> {
             return Ok(()); // Not an error. Don't fail other instructions in tx
         }
         let stake_delta = u64::try_from(stake_delta).expect("Stake delta overflow");
-        let total_stake_target = self
-            .state
-            .validator_system
-            .total_active_balance
-            .saturating_add(stake_delta);
 
         let mut validator = self
             .state
@@ -109,23 +107,27 @@ impl<'info> StakeReserve<'info> {
             &validator.validator_account,
             "validator_vote",
         )?;
+        // the commission could be raised after the last score update
+        let commission = ValidatorSystem::vote_commission(&self.validator_vote)?;
+        if self.state.max_commission > 0 && commission > self.state.max_commission {
+            msg!(
+                "Validator {} commission {}% is over max commission {}%. Please stake into another validator",
+                validator.validator_account,
+                commission,
+                self.state.max_commission
+            );
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
 
-        if validator.last_stake_delta_epoch == self.clock.epoch {
-            // check if we have some extra stake runs allowed
-            if self.state.stake_system.extra_stake_delta_runs == 0 {
-                msg!(
-                    "Double delta stake command for validator {} in epoch {}",
-                    validator.validator_account,
-                    self.clock.epoch
-                );
-                return Ok(()); // Not an error. Don't fail other instructions in tx
-            } else {
-                // some extra runs allowed. Use one
-                self.state.stake_system.extra_stake_delta_runs -= 1;
-            }
-        } else {
-            // first stake in this epoch
-            validator.last_stake_delta_epoch = self.clock.epoch;
+        // an extra run is used only if this command stakes something
+        let extra_stake_delta_run = validator.last_stake_delta_epoch == self.clock.epoch;
+        if extra_stake_delta_run && self.state.stake_system.extra_stake_delta_runs == 0 {
+            msg!(
+                "Double delta stake command for validator {} in epoch {}",
+                validator.validator_account,
+                self.clock.epoch
+            );
+            return Ok(()); // Not an error. Don't fail other instructions in tx
         }
 
         let last_slot = self.epoch_schedule.get_last_slot_in_epoch(self.clock.epoch);
@@ -139,35 +141,98 @@ impl<'info> StakeReserve<'info> {
             return Err(ProgramError::Custom(332));
         }
 
-        let validator_stake_target = self
-            .state
-            .validator_system
-            .validator_stake_target(&validator, total_stake_target)?;
+        let (stake_target, available_delta) = if validator.directed_stake_lamports > 0 {
+            if validator.stake_room() < self.state.stake_system.min_stake {
+                // validator reached max_stake, directed lamports go to the score based allocation.
+                // Nothing is staked, so the validator keeps its stake delta turn
+                msg!(
+                    "Validator {} reached max stake {}. Release {} directed lamports",
+                    validator.validator_account,
+                    validator.max_stake,
+                    validator.directed_stake_lamports
+                );
+                let directed_stake_lamports = validator.directed_stake_lamports;
+                self.state
+                    .on_directed_stake(&mut validator, directed_stake_lamports);
+                self.state.validator_system.set(
+                    &mut self.validator_list.data.as_ref().borrow_mut(),
+                    validator_index,
+                    validator,
+                )?;
+                return Ok(()); // Not an error. Don't fail other instructions in tx
+            }
+            // directed deposits are staked before the score based allocation
+            // stake_target = directed lamports, at least self.state.min_stake and at most delta_stake
+            (
+                validator
+                    .directed_stake_lamports
+                    .max(self.state.stake_system.min_stake)
+                    .min(stake_delta),
+                stake_delta,
+            )
+        } else {
+            // lamports waiting for directed stake are not distributed by score
+            let score_stake_delta = stake_delta.saturating_sub(self.state.total_directed_stake);
+            if score_stake_delta == 0 {
+                msg!("Stake delta {} is reserved for directed stake", stake_delta);
+                return Ok(()); // Not an error. Don't fail other instructions in tx
+            }
+            let total_stake_target = self
+                .state
+                .validator_system
+                .total_active_balance
+                .saturating_add(score_stake_delta);
 
-        //verify the validator is under-staked
-        if validator.active_balance >= validator_stake_target {
-            msg!(
+            let validator_stake_target = self
+                .state
+                .validator_stake_target(&validator, total_stake_target)?;
+
+            //verify the validator is under-staked
+            if validator.active_balance >= validator_stake_target {
+                msg!(
                     "Validator {} has already reached stake target {}. Please stake into another validator",
                     validator.validator_account,
                     validator_stake_target
                 );
-            return Ok(()); // Not an error. Don't fail other instructions in tx
-        }
+                return Ok(()); // Not an error. Don't fail other instructions in tx
+            }
 
-        // compute stake_target
-        // stake_target = target_validator_balance - validator.balance, at least self.state.min_stake and at most delta_stake
-        let stake_target = validator_stake_target
-            .saturating_sub(validator.active_balance)
-            .max(self.state.stake_system.min_stake)
-            .min(stake_delta);
+            // compute stake_target
+            // stake_target = target_validator_balance - validator.balance, at least self.state.min_stake and at most delta_stake
+            (
+                validator_stake_target
+                    .saturating_sub(validator.active_balance)
+                    .max(self.state.stake_system.min_stake)
+                    .min(score_stake_delta),
+                score_stake_delta,
+            )
+        };
 
         // if what's left after this stake is < state.min_stake, take all the remainder
-        let stake_target = if stake_delta - stake_target < self.state.stake_system.min_stake {
-            stake_delta
+        let stake_target = if available_delta - stake_target < self.state.stake_system.min_stake {
+            available_delta
         } else {
             stake_target
         };
 
+        // never stake over the validator max_stake
+        if stake_target > validator.stake_room()
+            && validator.stake_room() < self.state.stake_system.min_stake
+        {
+            msg!(
+                "Validator {} reached max stake {}. Please stake into another validator",
+                validator.validator_account,
+                validator.max_stake
+            );
+            return Ok(()); // Not an error. Don't fail other instructions in tx
+        }
+        let stake_target = stake_target.min(validator.stake_room());
+
+        if extra_stake_delta_run {
+            // some extra runs allowed. Use one
+            self.state.stake_system.extra_stake_delta_runs -= 1;
+        }
+
         // transfer SOL from reserve_pda to the stake-account
         self.state.with_reserve_seeds(|seeds| {
             sol_log_compute_units();
@@ -238,6 +303,7 @@ impl<'info> StakeReserve<'info> {
             .checked_add(stake_target)
             .ok_or(CommonError::CalculationFailure)?;
         validator.last_stake_delta_epoch = self.clock.epoch;
+        self.state.on_directed_stake(&mut validator, stake_target);
         // Any stake-delta activity must activate stake delta mode
         self.state.stake_system.last_stake_delta_epoch = self.clock.epoch;
         self.state.validator_system.set(
@@ -251,6 +317,15 @@ impl<'info> StakeReserve<'info> {
             .total_active_balance
             .checked_add(stake_target)
             .ok_or(CommonError::CalculationFailure)?;
+
+        emit!(StakeReserveEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: self.stake_account.key(),
+            validator_vote: *self.validator_vote.key,
+            lamports: stake_target,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
         Ok(())
     }
 }
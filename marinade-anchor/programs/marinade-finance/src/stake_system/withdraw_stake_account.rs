@@ -0,0 +1,321 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    program::invoke_signed,
+    stake::{self, state::StakeAuthorize},
+    system_program,
+};
+use anchor_spl::token::{burn, transfer, Burn, Transfer};
+
+use crate::{
+    checks::{check_address, check_min_amount, check_owner_program, check_token_mint},
+    events::WithdrawStakeAccountEvent,
+    stake_system::{prepare_split_stake_account, StakeSystemHelpers},
+    State, WithdrawStakeAccount,
+};
+
+impl<'info> WithdrawStakeAccount<'info> {
+    fn check_burn_msol_from(&self, msol_amount: u64) -> ProgramResult {
+        check_token_mint(&self.burn_msol_from, self.state.msol_mint, "burn_msol_from")?;
+
+        if msol_amount == 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if *self.burn_msol_authority.key == self.burn_msol_from.owner {
+            if self.burn_msol_from.amount < msol_amount {
+                msg!(
+                    "Requested to withdraw {} mSOL lamports but have only {}",
+                    msol_amount,
+                    self.burn_msol_from.amount
+                );
+                return Err(ProgramError::InsufficientFunds);
+            }
+        } else if self
+            .burn_msol_from
+            .delegate
+            .contains(self.burn_msol_authority.key)
+        {
+            // if delegated, check delegated amount
+            if self.burn_msol_from.delegated_amount < msol_amount {
+                msg!(
+                    "Delegated {} mSOL lamports. Requested {}",
+                    self.burn_msol_from.delegated_amount,
+                    msol_amount
+                );
+                return Err(ProgramError::InsufficientFunds);
+            }
+        } else {
+            msg!(
+                "Token must be delegated to {}",
+                self.burn_msol_authority.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    // fn withdraw_stake_account()
+    pub fn process(
+        &mut self,
+        stake_index: u32,
+        validator_index: u32,
+        msol_amount: u64,
+    ) -> ProgramResult {
+        self.state
+            .check_not_paused(State::PAUSE_WITHDRAW_STAKE_ACCOUNT)?;
+        self.state
+            .check_msol_mint(self.msol_mint.to_account_info().key)?;
+        self.check_burn_msol_from(msol_amount)?;
+        let is_treasury_msol_ready_for_transfer = self
+            .state
+            .check_treasury_msol_account(&self.treasury_msol_account)?;
+        self.state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+        self.state.stake_system.check_stake_list(&self.stake_list)?;
+        check_owner_program(&self.stake_account, &stake::program::ID, "stake_account")?;
+        self.state
+            .check_stake_deposit_authority(self.stake_deposit_authority.key)?;
+        self.state
+            .check_stake_withdraw_authority(self.stake_withdraw_authority.key)?;
+        check_address(
+            self.system_program.key,
+            &system_program::ID,
+            "system_program",
+        )?;
+        check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+        check_address(self.stake_program.key, &stake::program::ID, "stake_program")?;
+
+        let mut stake = self
+            .state
+            .stake_system
+            .get(&self.stake_list.data.as_ref().borrow(), stake_index)?;
+        check_address(
+            self.stake_account.to_account_info().key,
+            &stake.stake_account,
+            "stake_account",
+        )?;
+
+        let mut validator = self
+            .state
+            .validator_system
+            .get(&self.validator_list.data.as_ref().borrow(), validator_index)?;
+
+        let delegation = self.stake_account.delegation().ok_or_else(|| {
+            msg!(
+                "Stake {} must be delegated",
+                self.stake_account.to_account_info().key
+            );
+            ProgramError::InvalidAccountData
+        })?;
+        if delegation.voter_pubkey != validator.validator_account {
+            msg!(
+                "Invalid stake validator index. Need to point into validator {}",
+                validator.validator_account
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if delegation.deactivation_epoch != std::u64::MAX {
+            msg!(
+                "Stake {} is deactivating",
+                self.stake_account.to_account_info().key
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // rewards or slashing must be accounted in the mSOL price before splitting
+        if delegation.stake != stake.last_update_delegated_lamports {
+            msg!(
+                "Stake {} must be updated first. Delegated {}, last update {}",
+                stake.stake_account,
+                delegation.stake,
+                stake.last_update_delegated_lamports
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // fee is taken in mSOL, the user receives the lamport value of msol_amount - msol_fee
+        let msol_fee = self.state.withdraw_stake_account_fee.apply(msol_amount);
+        msg!("msol_fee {}", msol_fee);
+        let split_lamports = self
+            .state
+            .calc_lamports_from_msol_amount(msol_amount - msol_fee)?;
+
+        check_min_amount(
+            split_lamports,
+            self.state.stake_system.min_stake,
+            "withdraw stake",
+        )?;
+        // the account left in the stake list must stay usable
+        if stake.last_update_delegated_lamports < split_lamports
+            || stake.last_update_delegated_lamports - split_lamports
+                < self.state.stake_system.min_stake
+        {
+            msg!(
+                "Can not split {} lamports from stake {} with {}. At least {} must remain",
+                split_lamports,
+                stake.stake_account,
+                stake.last_update_delegated_lamports,
+                self.state.stake_system.min_stake
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+        // same min_stake floor as deactivate_stake
+        if validator.active_balance.saturating_sub(split_lamports) < validator.min_stake
+            && self.state.floors_fundable(
+                self.state
+                    .validator_system
+                    .total_active_balance
+                    .saturating_sub(split_lamports),
+            )
+        {
+            msg!(
+                "Can not split {} lamports. Validator {} must keep min stake {}. Withdraw from another validator",
+                split_lamports,
+                validator.validator_account,
+                validator.min_stake
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let beneficiary = self.burn_msol_from.owner;
+        msg!(
+            "Withdraw split {} ({} lamports) from stake {} to {}",
+            self.split_stake_account.key,
+            split_lamports,
+            stake.stake_account,
+            beneficiary
+        );
+
+        prepare_split_stake_account(
+            &self.split_stake_account,
+            &self.split_stake_rent_payer,
+            &self.rent,
+            &self.system_program,
+        )?;
+
+        self.state.with_stake_deposit_authority_seeds(|seeds| {
+            let split_instruction = stake::instruction::split(
+                self.stake_account.to_account_info().key,
+                self.stake_deposit_authority.key,
+                split_lamports,
+                self.split_stake_account.key,
+            )
+            .last()
+            .unwrap()
+            .clone();
+            invoke_signed(
+                &split_instruction,
+                &[
+                    self.stake_program.clone(),
+                    self.stake_account.to_account_info(),
+                    self.split_stake_account.clone(),
+                    self.stake_deposit_authority.clone(),
+                ],
+                &[seeds],
+            )?;
+
+            invoke_signed(
+                &stake::instruction::authorize(
+                    self.split_stake_account.key,
+                    self.stake_deposit_authority.key,
+                    &beneficiary,
+                    StakeAuthorize::Staker,
+                    None,
+                ),
+                &[
+                    self.stake_program.clone(),
+                    self.split_stake_account.clone(),
+                    self.clock.to_account_info(),
+                    self.stake_deposit_authority.clone(),
+                ],
+                &[seeds],
+            )
+        })?;
+
+        self.state.with_stake_withdraw_authority_seeds(|seeds| {
+            invoke_signed(
+                &stake::instruction::authorize(
+                    self.split_stake_account.key,
+                    self.stake_withdraw_authority.key,
+                    &beneficiary,
+                    StakeAuthorize::Withdrawer,
+                    None,
+                ),
+                &[
+                    self.stake_program.clone(),
+                    self.split_stake_account.clone(),
+                    self.clock.to_account_info(),
+                    self.stake_withdraw_authority.clone(),
+                ],
+                &[seeds],
+            )
+        })?;
+
+        // without a ready treasury the fee is burned too and stays with the stakers
+        let treasury_msol_cut = if is_treasury_msol_ready_for_transfer {
+            msol_fee
+        } else {
+            0
+        };
+        if treasury_msol_cut > 0 {
+            transfer(
+                CpiContext::new(
+                    self.token_program.clone(),
+                    Transfer {
+                        from: self.burn_msol_from.to_account_info(),
+                        to: self.treasury_msol_account.to_account_info(),
+                        authority: self.burn_msol_authority.clone(),
+                    },
+                ),
+                treasury_msol_cut,
+            )?;
+        }
+        let msol_burned = msol_amount - treasury_msol_cut;
+        burn(
+            CpiContext::new(
+                self.token_program.clone(),
+                Burn {
+                    mint: self.msol_mint.to_account_info(),
+                    to: self.burn_msol_from.to_account_info(),
+                    authority: self.burn_msol_authority.clone(),
+                },
+            ),
+            msol_burned,
+        )?;
+        self.state.on_msol_burn(msol_burned);
+
+        // split lamports leave marinade control
+        stake.last_update_delegated_lamports -= split_lamports;
+        validator.active_balance = validator.active_balance.saturating_sub(split_lamports);
+        self.state.validator_system.total_active_balance = self
+            .state
+            .validator_system
+            .total_active_balance
+            .saturating_sub(split_lamports);
+
+        self.state.stake_system.set(
+            &mut self.stake_list.data.as_ref().borrow_mut(),
+            stake_index,
+            stake,
+        )?;
+        self.state.validator_system.set(
+            &mut self.validator_list.data.as_ref().borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+
+        emit!(WithdrawStakeAccountEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: stake.stake_account,
+            split_stake_account: *self.split_stake_account.key,
+            validator_vote: validator.validator_account,
+            beneficiary,
+            msol_burned,
+            msol_fee,
+            lamports: split_lamports,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+        Ok(())
+    }
+}
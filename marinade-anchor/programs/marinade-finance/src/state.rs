@@ -2,23 +2,34 @@ use crate::{
     calc::{shares_from_value, value_from_shares},
     checks::check_address,
     error::CommonError,
-    liq_pool::LiqPool,
+    liq_pool::{FeeCurve, LiqPool},
     located::Located,
     stake_system::StakeSystem,
-    validator_system::ValidatorSystem,
+    validator_system::{ValidatorRecord, ValidatorSystem},
     Fee, ID,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program_pack::Pack;
 use std::mem::MaybeUninit;
 
+pub mod accept_authority;
+pub mod cancel_params_change;
 pub mod change_authority;
+pub mod change_beneficiary;
 pub mod claim;
 pub mod config_marinade;
 pub mod deposit;
+pub mod deposit_directed;
+pub mod execute_params_change;
+pub mod grow_list;
 pub mod initialize;
+pub mod instant_claim;
 pub mod liquid_unstake;
 pub mod order_unstake;
+pub mod propose_authority;
+pub mod queue_params_change;
+pub mod reclaim_rent;
+pub mod split_ticket;
 pub mod update;
 
 #[account]
@@ -67,6 +78,40 @@ pub struct State {
     pub staking_sol_cap: u64,
 
     pub emergency_cooling_down: u64,
+
+    /// bitmask of State::PAUSE_* operations halted by the admin_authority
+    pub paused_operations: u32,
+
+    // Authorities proposed by the admin_authority with ProposeAuthority.
+    // The new key must sign AcceptAuthority. Pubkey::default() means nothing pending
+    pub pending_admin_authority: Pubkey,
+    pub pending_validator_manager_authority: Pubkey,
+    pub pending_operational_sol_account: Pubkey,
+
+    /// config_marinade & set_lp_params must be queued this many epochs ahead (0 = immediate)
+    pub params_change_delay_epochs: u64,
+
+    /// fee applied on the mSOL burned by WithdrawStakeAccount, goes to the treasury
+    pub withdraw_stake_account_fee: Fee,
+
+    // Fields of the validator system and the liquidity pool added after the launch.
+    // They are kept at the end so State accounts created before read them from
+    // their spare (zeroed) space. Zero keeps the previous behavior for all of them
+    /// sum of all validators directed_stake_lamports waiting for stake_reserve
+    pub total_directed_stake: u64,
+    /// max vote account commission (percent) to stake into or accept deposited stake from (0 = no cap)
+    pub max_commission: u8,
+    /// sum of all validators min_stake floors
+    pub total_min_stake: u64,
+
+    /// lamports of not yet claimed tickets owned by the sol leg (InstantClaim, RebalanceLiqPool). Part of the LP value
+    pub liq_pool_tickets_receivable: u64,
+    pub liq_pool_fee_curve: FeeCurve,
+    /// max lamports leaving the SOL leg per epoch through LiquidUnstake and InstantClaim (0 = unlimited)
+    pub liq_pool_epoch_outflow_cap: u64,
+    /// lamports gone out of the SOL leg in liq_pool_outflow_epoch
+    pub liq_pool_epoch_outflow: u64,
+    pub liq_pool_outflow_epoch: u64,
 }
 
 impl State {
@@ -79,6 +124,27 @@ impl State {
     pub const STAKE_LIST_SEED: &'static str = "stake_list";
     pub const VALIDATOR_LIST_SEED: &'static str = "validator_list";
 
+    // paused_operations flags
+    pub const PAUSE_DEPOSIT: u32 = 1;
+    pub const PAUSE_DEPOSIT_STAKE_ACCOUNT: u32 = 1 << 1;
+    pub const PAUSE_LIQUID_UNSTAKE: u32 = 1 << 2; // also InstantClaim
+    pub const PAUSE_ORDER_UNSTAKE: u32 = 1 << 3; // also RebalanceLiqPool
+    pub const PAUSE_ADD_LIQUIDITY: u32 = 1 << 4;
+    pub const PAUSE_STAKE_RESERVE: u32 = 1 << 5;
+    pub const PAUSE_WITHDRAW_STAKE_ACCOUNT: u32 = 1 << 6;
+    pub const PAUSE_ALL: u32 = (1 << 7) - 1;
+    pub const PAUSE_OPERATIONS: [(u32, &'static str); 7] = [
+        (Self::PAUSE_DEPOSIT, "deposit"),
+        (Self::PAUSE_DEPOSIT_STAKE_ACCOUNT, "deposit-stake-account"),
+        (Self::PAUSE_LIQUID_UNSTAKE, "liquid-unstake"),
+        (Self::PAUSE_ORDER_UNSTAKE, "order-unstake"),
+        (Self::PAUSE_ADD_LIQUIDITY, "add-liquidity"),
+        (Self::PAUSE_STAKE_RESERVE, "stake-reserve"),
+        (Self::PAUSE_WITHDRAW_STAKE_ACCOUNT, "withdraw-stake-account"),
+    ];
+
+    pub const MAX_PARAMS_CHANGE_DELAY_EPOCHS: u64 = 30;
+
     pub fn serialized_len() -> usize {
         unsafe { MaybeUninit::<Self>::zeroed().assume_init() }
             .try_to_vec()
@@ -111,6 +177,31 @@ impl State {
         Ok(())
     }
 
+    pub fn check_not_paused(&self, operation: u32) -> ProgramResult {
+        if self.paused_operations & operation != 0 {
+            msg!(
+                "Operation {} is paused",
+                Self::PAUSE_OPERATIONS
+                    .iter()
+                    .find(|(flag, _)| *flag == operation)
+                    .map_or("unknown", |(_, name)| name)
+            );
+            return Err(CommonError::OperationPaused.into());
+        }
+        Ok(())
+    }
+
+    pub fn check_not_timelocked(&self) -> ProgramResult {
+        if self.params_change_delay_epochs > 0 {
+            msg!(
+                "Params changes are timelocked for {} epochs. Use QueueParamsChange",
+                self.params_change_delay_epochs
+            );
+            return Err(CommonError::ParamsChangeTimelocked.into());
+        }
+        Ok(())
+    }
+
     pub fn check_operational_sol_account(&self, operational_sol_account: &Pubkey) -> ProgramResult {
         check_address(
             operational_sol_account,
@@ -277,6 +368,116 @@ impl State {
         self.msol_supply = self.msol_supply.saturating_sub(amount);
     }
 
+    pub fn remove_validator(
+        &mut self,
+        validator_list_data: &mut [u8],
+        index: u32,
+        record: ValidatorRecord,
+    ) -> ProgramResult {
+        self.validator_system
+            .remove(validator_list_data, index, record)?;
+        // not staked directed lamports go back to the score based pool
+        self.total_directed_stake = self
+            .total_directed_stake
+            .saturating_sub(record.directed_stake_lamports);
+        self.total_min_stake = self.total_min_stake.saturating_sub(record.min_stake);
+        Ok(())
+    }
+
+    pub fn on_directed_deposit(
+        &mut self,
+        validator: &mut ValidatorRecord,
+        lamports: u64,
+    ) -> Result<(), CommonError> {
+        validator.directed_stake_lamports = validator
+            .directed_stake_lamports
+            .checked_add(lamports)
+            .ok_or(CommonError::CalculationFailure)?;
+        self.total_directed_stake = self
+            .total_directed_stake
+            .checked_add(lamports)
+            .ok_or(CommonError::CalculationFailure)?;
+        Ok(())
+    }
+
+    pub fn on_directed_stake(&mut self, validator: &mut ValidatorRecord, lamports: u64) {
+        let lamports = lamports.min(validator.directed_stake_lamports);
+        validator.directed_stake_lamports -= lamports;
+        self.total_directed_stake = self.total_directed_stake.saturating_sub(lamports);
+    }
+
+    /// the validators min_stake floors are honored only while the total stake can fund all of them.
+    /// Otherwise delayed unstake tickets could never be funded
+    pub fn floors_fundable(&self, total_stake_target: u64) -> bool {
+        self.total_min_stake <= total_stake_target
+    }
+
+    pub fn on_stake_bounds_change(&mut self, validator: &mut ValidatorRecord, min_stake: u64) {
+        self.total_min_stake = self
+            .total_min_stake
+            .saturating_sub(validator.min_stake)
+            .saturating_add(min_stake);
+        validator.min_stake = min_stake;
+    }
+
+    pub fn validator_stake_target(
+        &self,
+        validator: &ValidatorRecord,
+        total_stake_target: u64,
+    ) -> Result<u64, CommonError> {
+        self.validator_system.validator_stake_target(
+            validator,
+            total_stake_target,
+            self.floors_fundable(total_stake_target),
+        )
+    }
+
+    pub fn check_commission(&self, validator_vote: &AccountInfo) -> ProgramResult {
+        let commission = ValidatorSystem::vote_commission(validator_vote)?;
+        if self.max_commission > 0 && commission > self.max_commission {
+            msg!(
+                "Validator {} commission {}% is over max commission {}%",
+                validator_vote.key,
+                commission,
+                self.max_commission
+            );
+            return Err(CommonError::ValidatorCommissionTooHigh.into());
+        }
+        Ok(())
+    }
+
+    pub fn on_liq_pool_ticket_bought(&mut self, lamports: u64) {
+        self.liq_pool_tickets_receivable = self
+            .liq_pool_tickets_receivable
+            .checked_add(lamports)
+            .expect("tickets_receivable overflow");
+    }
+
+    pub fn on_liq_pool_ticket_claimed(&mut self, lamports: u64) {
+        self.liq_pool_tickets_receivable =
+            self.liq_pool_tickets_receivable.saturating_sub(lamports);
+    }
+
+    /// Circuit breaker against draining the SOL leg. The counter restarts every epoch
+    pub fn on_liq_pool_sol_outflow(&mut self, lamports: u64, epoch: u64) -> ProgramResult {
+        if epoch != self.liq_pool_outflow_epoch {
+            self.liq_pool_outflow_epoch = epoch;
+            self.liq_pool_epoch_outflow = 0;
+        }
+        let epoch_outflow = self.liq_pool_epoch_outflow.saturating_add(lamports);
+        if self.liq_pool_epoch_outflow_cap > 0 && epoch_outflow > self.liq_pool_epoch_outflow_cap {
+            msg!(
+                "Liquidity pool outflow cap reached {}/{} in epoch {}",
+                epoch_outflow,
+                self.liq_pool_epoch_outflow_cap,
+                epoch
+            );
+            return Err(CommonError::EpochOutflowCapReached.into());
+        }
+        self.liq_pool_epoch_outflow = epoch_outflow;
+        Ok(())
+    }
+
     /*
     pub fn register_stake_order(&mut self, lamports_amount: u64) {
         self.epoch_stake_orders += lamports_amount;
@@ -341,3 +542,55 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+    use std::mem::MaybeUninit;
+
+    use crate::State;
+
+    // Borsh size of the State fields before the post-launch ones, without the discriminator
+    const LAUNCH_STATE_LEN: usize = 568;
+
+    fn zeroed_state() -> State {
+        unsafe { MaybeUninit::<State>::zeroed().assume_init() }
+    }
+
+    #[test]
+    fn test_launch_layout_kept() {
+        let mut state = zeroed_state();
+        // the last field of the State at launch
+        state.emergency_cooling_down = 0x0102_0304_0506_0708;
+        let data = state.try_to_vec().unwrap();
+        assert_eq!(
+            data[LAUNCH_STATE_LEN - 8..LAUNCH_STATE_LEN],
+            0x0102_0304_0506_0708u64.to_le_bytes()
+        );
+
+        // accounts created at launch have zeroed spare space after the launch fields
+        let mut launch_data = data[..LAUNCH_STATE_LEN].to_vec();
+        launch_data.resize(LAUNCH_STATE_LEN + 2048, 0);
+        let state = State::deserialize(&mut launch_data.as_slice()).unwrap();
+        assert_eq!(state.emergency_cooling_down, 0x0102_0304_0506_0708);
+        assert_eq!(state.max_commission, 0);
+        assert_eq!(state.liq_pool_epoch_outflow_cap, 0);
+    }
+
+    #[test]
+    fn test_epoch_outflow_cap() {
+        let mut state = zeroed_state();
+        // unlimited
+        state.on_liq_pool_sol_outflow(u64::MAX, 9).unwrap();
+
+        state.liq_pool_epoch_outflow_cap = 1_000;
+        state.on_liq_pool_sol_outflow(600, 10).unwrap();
+        state.on_liq_pool_sol_outflow(400, 10).unwrap();
+        assert!(state.on_liq_pool_sol_outflow(1, 10).is_err());
+        assert_eq!(state.liq_pool_epoch_outflow, 1_000);
+        // a new epoch restarts the counter
+        state.on_liq_pool_sol_outflow(700, 11).unwrap();
+        assert_eq!(state.liq_pool_epoch_outflow, 700);
+        assert_eq!(state.liq_pool_outflow_epoch, 11);
+    }
+}
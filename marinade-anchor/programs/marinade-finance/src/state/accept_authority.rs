@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::AcceptAuthority;
+
+impl<'info> AcceptAuthority<'info> {
+    // accepts every pending authority proposed for the signer
+    pub fn process(&mut self) -> ProgramResult {
+        let new_authority = *self.new_authority.key;
+        if new_authority == Pubkey::default() {
+            msg!("Invalid new authority {}", new_authority);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut accepted = false;
+
+        if self.state.pending_admin_authority == new_authority {
+            msg!("Admin authority changed to {}", new_authority);
+            self.state.admin_authority = new_authority;
+            self.state.pending_admin_authority = Pubkey::default();
+            accepted = true;
+        }
+
+        if self.state.pending_validator_manager_authority == new_authority {
+            msg!("Validator manager authority changed to {}", new_authority);
+            self.state.validator_system.manager_authority = new_authority;
+            self.state.pending_validator_manager_authority = Pubkey::default();
+            accepted = true;
+        }
+
+        if self.state.pending_operational_sol_account == new_authority {
+            msg!("Operational sol account changed to {}", new_authority);
+            self.state.operational_sol_account = new_authority;
+            self.state.pending_operational_sol_account = Pubkey::default();
+            accepted = true;
+        }
+
+        if !accepted {
+            msg!("No authority proposed for {}", new_authority);
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+use crate::{queued_change::QueuedChangeData, CancelParamsChange};
+
+impl<'info> CancelParamsChange<'info> {
+    pub fn process(&mut self) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        self.queued_change
+            .check(self.state.to_account_info().key, self.rent_payer.key)?;
+        msg!("Cancelled {:?}", self.queued_change.change);
+
+        QueuedChangeData::close(&mut self.queued_change, &self.rent_payer)
+    }
+}
@@ -6,16 +6,13 @@ impl<'info> ChangeAuthority<'info> {
     pub fn process(&mut self, data: ChangeAuthorityData) -> ProgramResult {
         self.state.check_admin_authority(self.admin_authority.key)?;
 
-        if let Some(admin) = data.admin {
-            self.state.admin_authority = admin;
-        }
-
-        if let Some(validator_manager) = data.validator_manager {
-            self.state.validator_system.manager_authority = validator_manager;
-        }
-
-        if let Some(operational_sol_account) = data.operational_sol_account {
-            self.state.operational_sol_account = operational_sol_account;
+        // authorities move in two steps so a typo can not lock the program
+        if data.admin.is_some()
+            || data.validator_manager.is_some()
+            || data.operational_sol_account.is_some()
+        {
+            msg!("Use propose_authority and accept_authority to change admin, validator manager or operational sol account");
+            return Err(ProgramError::InvalidArgument);
         }
 
         if let Some(treasury_msol_account) = data.treasury_msol_account {
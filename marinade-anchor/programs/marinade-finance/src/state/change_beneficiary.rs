@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{
+    checks::check_owner_program, liq_pool::LiqPoolHelpers, ChangeBeneficiary, CommonError,
+};
+
+/// ChangeBeneficiary instruction: the current beneficiary of a Ticket-account reassigns it to another account.
+/// Claim sends SOL to the ticket beneficiary, so the new owner is the one who can claim the ticket
+impl<'info> ChangeBeneficiary<'info> {
+    fn check_ticket_account(&self) -> ProgramResult {
+        if &self.ticket_account.state_address != self.state.to_account_info().key {
+            msg!(
+                "Ticket has wrong marinade instance {}",
+                self.ticket_account.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.lamports_amount == 0 {
+            msg!("Used ticket");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.beneficiary != *self.beneficiary.key {
+            msg!("wrong beneficiary");
+            return Err(CommonError::WrongBeneficiary.into());
+        }
+        Ok(())
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        // fn change_beneficiary()
+        self.check_ticket_account()?;
+        // claim transfers SOL to a system account, so the ticket must stay claimable
+        check_owner_program(
+            &self.new_beneficiary,
+            &system_program::ID,
+            "new_beneficiary",
+        )?;
+        // claims to the liq-pool SOL leg are booked as tickets the pool bought (InstantClaim)
+        if *self.new_beneficiary.key == self.state.liq_pool_sol_leg_address() {
+            msg!("Can not give a ticket to the liquidity pool");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.ticket_account.beneficiary = *self.new_beneficiary.key;
+
+        // parseable by indexers: ticket, old beneficiary, new beneficiary, amount, created epoch
+        msg!(
+            "ChangeBeneficiary ticket={} from={} to={} lamports={} created_epoch={}",
+            self.ticket_account.to_account_info().key,
+            self.beneficiary.key,
+            self.new_beneficiary.key,
+            self.ticket_account.lamports_amount,
+            self.ticket_account.created_epoch
+        );
+        Ok(())
+    }
+}
@@ -2,7 +2,9 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{program::invoke_signed, system_instruction, system_program};
 
 use crate::{
-    checks::{check_address, check_owner_program},
+    checks::{check_address, check_min_amount, check_owner_program},
+    events::ClaimEvent,
+    liq_pool::LiqPoolHelpers,
     state::StateHelpers,
     Claim, CommonError,
 };
@@ -67,8 +69,7 @@ impl<'info> Claim<'info> {
         Ok(())
     }
 
-    pub fn process(&mut self) -> ProgramResult {
-        // fn claim()
+    fn check_accounts(&self) -> ProgramResult {
         check_address(
             self.system_program.to_account_info().key,
             &system_program::ID,
@@ -80,9 +81,10 @@ impl<'info> Claim<'info> {
             "transfer_sol_to",
         )?;
         self.state.check_reserve_address(self.reserve_pda.key)?;
-        self.check_ticket_account()?;
+        self.check_ticket_account()
+    }
 
-        let lamports = self.ticket_account.lamports_amount;
+    fn check_claim_amount(&self, lamports: u64) -> ProgramResult {
         if lamports > self.state.circulating_ticket_balance {
             msg!(
                 "Requested to withdraw {} when only {} is total circulating_ticket_balance",
@@ -104,12 +106,22 @@ impl<'info> Claim<'info> {
             //Error: "Wait a few hours and retry"
             return Err(CommonError::TicketNotReady.into());
         }
+        Ok(())
+    }
 
-        self.state.circulating_ticket_balance -= lamports;
-        self.state.circulating_ticket_count -= 1;
-        //disable ticket-account
-        self.ticket_account.lamports_amount = 0;
+    fn emit_claim_event(&self, lamports: u64) {
+        emit!(ClaimEvent {
+            state: *self.state.to_account_info().key,
+            ticket: *self.ticket_account.to_account_info().key,
+            beneficiary: *self.transfer_sol_to.key,
+            lamports,
+            ticket_lamports_left: self.ticket_account.lamports_amount,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+    }
 
+    fn transfer_from_reserve(&mut self, lamports: u64) -> ProgramResult {
         //transfer sol from reserve_pda to user
         self.state.with_reserve_seeds(|seeds| {
             invoke_signed(
@@ -127,6 +139,26 @@ impl<'info> Claim<'info> {
             )
         })?;
         self.state.on_transfer_from_reserve(lamports);
+        // ticket bought by the liq-pool (InstantClaim), it is not receivable anymore
+        if *self.transfer_sol_to.key == self.state.liq_pool_sol_leg_address() {
+            self.state.on_liq_pool_ticket_claimed(lamports);
+        }
+        Ok(())
+    }
+
+    pub fn process(&mut self) -> ProgramResult {
+        // fn claim()
+        self.check_accounts()?;
+
+        let lamports = self.ticket_account.lamports_amount;
+        self.check_claim_amount(lamports)?;
+
+        self.state.circulating_ticket_balance -= lamports;
+        self.state.circulating_ticket_count -= 1;
+        //disable ticket-account
+        self.ticket_account.lamports_amount = 0;
+
+        self.transfer_from_reserve(lamports)?;
 
         // move all rent-exempt ticket-account lamports to the user,
         // the ticket-account will be deleted eventually because is no longer rent-exempt
@@ -138,6 +170,43 @@ impl<'info> Claim<'info> {
             .ok_or(ProgramError::InvalidAccountData)?;
         **source_account_info.lamports.borrow_mut() = 0;
 
+        self.emit_claim_event(lamports);
+        Ok(())
+    }
+
+    /// Partial claim: pays out only `lamports` from a due ticket to its beneficiary.
+    /// The ticket keeps the remaining amount and stays open for later claims
+    pub fn process_partial(&mut self, lamports: u64) -> ProgramResult {
+        // fn partial_claim()
+        self.check_accounts()?;
+
+        let ticket_lamports = self.ticket_account.lamports_amount;
+        if lamports == ticket_lamports {
+            // nothing will be left, claim all and close the ticket
+            return self.process();
+        }
+        if lamports > ticket_lamports {
+            msg!(
+                "Requested to claim {} but ticket has only {}",
+                lamports,
+                ticket_lamports
+            );
+            return Err(CommonError::NumberTooHigh.into());
+        }
+        check_min_amount(lamports, self.state.min_withdraw, "claim SOL")?;
+        check_min_amount(
+            ticket_lamports - lamports,
+            self.state.min_withdraw,
+            "remaining ticket SOL",
+        )?;
+        self.check_claim_amount(lamports)?;
+
+        self.state.circulating_ticket_balance -= lamports;
+        // ticket stays alive with the remaining amount, so circulating_ticket_count is unchanged
+        self.ticket_account.lamports_amount = ticket_lamports - lamports;
+
+        self.transfer_from_reserve(lamports)?;
+        self.emit_claim_event(lamports);
         Ok(())
     }
 }
@@ -1,11 +1,31 @@
-use crate::{CommonError, ConfigMarinade, ConfigMarinadeParams, MAX_REWARD_FEE};
+use crate::{
+    validator_system::ValidatorSystem, CommonError, ConfigMarinade, ConfigMarinadeParams, State,
+    MAX_REWARD_FEE, MAX_WITHDRAW_STAKE_ACCOUNT_FEE,
+};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 
 impl<'info> ConfigMarinade<'info> {
-    const MIN_WITHDRAW_CAP: u64 = LAMPORTS_PER_SOL / 10;
     // fn config_marinade()
-    pub fn process(
+    pub fn process(&mut self, params: ConfigMarinadeParams) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        // pausing stays immediate for emergencies, everything else waits for the timelock
+        let only_pauses = ConfigMarinadeParams {
+            paused_operations: None,
+            ..params
+        } == ConfigMarinadeParams::default();
+        if !only_pauses {
+            self.state.check_not_timelocked()?;
+        }
+        self.state.config(params)
+    }
+}
+
+impl State {
+    const MIN_WITHDRAW_CAP: u64 = LAMPORTS_PER_SOL / 10;
+
+    /// Used by ConfigMarinade and ExecuteParamsChange
+    pub fn config(
         &mut self,
         ConfigMarinadeParams {
             rewards_fee,
@@ -16,49 +36,86 @@ impl<'info> ConfigMarinade<'info> {
             staking_sol_cap,
             liquidity_sol_cap,
             auto_add_validator_enabled,
+            paused_operations,
+            params_change_delay_epochs,
+            withdraw_stake_account_fee,
+            max_commission,
+            liquidity_epoch_outflow_cap,
         }: ConfigMarinadeParams,
     ) -> ProgramResult {
-        self.state.check_admin_authority(self.admin_authority.key)?;
         if let Some(rewards_fee) = rewards_fee {
             rewards_fee.check_max(MAX_REWARD_FEE)?;
-            self.state.reward_fee = rewards_fee;
+            self.reward_fee = rewards_fee;
         }
         if let Some(slots_for_stake_delta) = slots_for_stake_delta {
             const MIN_UPDATE_WINDOW: u64 = 3_000; //min value is 3_000 => half an hour
             if slots_for_stake_delta < MIN_UPDATE_WINDOW {
                 return Err(CommonError::NumberTooLow.into());
             };
-            self.state.stake_system.slots_for_stake_delta = slots_for_stake_delta;
+            self.stake_system.slots_for_stake_delta = slots_for_stake_delta;
         }
         if let Some(min_stake) = min_stake {
-            let min_accepted = 5 * self.state.rent_exempt_for_token_acc;
+            let min_accepted = 5 * self.rent_exempt_for_token_acc;
             if min_stake < min_accepted {
                 return Err(CommonError::NumberTooLow.into());
             };
-            self.state.stake_system.min_stake = min_stake;
+            self.stake_system.min_stake = min_stake;
         }
         if let Some(min_deposit) = min_deposit {
             // It is not dangerous to skip value checks because it is deposit only action
             // We can use u64::MAX to stop accepting deposits
             // or 0 to accept 1 lamport
-            self.state.min_deposit = min_deposit;
+            self.min_deposit = min_deposit;
         }
         if let Some(min_withdraw) = min_withdraw {
             if min_withdraw > Self::MIN_WITHDRAW_CAP {
                 return Err(CommonError::NumberTooHigh.into());
             }
-            self.state.min_withdraw = min_withdraw;
+            self.min_withdraw = min_withdraw;
         }
         if let Some(staking_sol_cap) = staking_sol_cap {
-            self.state.staking_sol_cap = staking_sol_cap;
+            self.staking_sol_cap = staking_sol_cap;
         }
         if let Some(liquidity_sol_cap) = liquidity_sol_cap {
-            self.state.liq_pool.liquidity_sol_cap = liquidity_sol_cap;
+            self.liq_pool.liquidity_sol_cap = liquidity_sol_cap;
         }
         if let Some(auto_add_validator_enabled) = auto_add_validator_enabled {
-            self.state.validator_system.auto_add_validator_enabled =
+            self.validator_system.auto_add_validator_enabled =
                 if auto_add_validator_enabled { 1 } else { 0 };
         }
+        if let Some(paused_operations) = paused_operations {
+            if paused_operations & !State::PAUSE_ALL != 0 {
+                msg!("Unknown paused operation flags {:b}", paused_operations);
+                return Err(CommonError::NumberTooHigh.into());
+            }
+            msg!("Paused operations {:b}", paused_operations);
+            self.paused_operations = paused_operations;
+        }
+        if let Some(params_change_delay_epochs) = params_change_delay_epochs {
+            if params_change_delay_epochs > Self::MAX_PARAMS_CHANGE_DELAY_EPOCHS {
+                return Err(CommonError::NumberTooHigh.into());
+            }
+            msg!("Params change delay {} epochs", params_change_delay_epochs);
+            self.params_change_delay_epochs = params_change_delay_epochs;
+        }
+        if let Some(withdraw_stake_account_fee) = withdraw_stake_account_fee {
+            withdraw_stake_account_fee.check_max(MAX_WITHDRAW_STAKE_ACCOUNT_FEE)?;
+            self.withdraw_stake_account_fee = withdraw_stake_account_fee;
+        }
+        if let Some(max_commission) = max_commission {
+            if max_commission > ValidatorSystem::MAX_COMMISSION {
+                return Err(CommonError::NumberTooHigh.into());
+            }
+            msg!("Max validator commission {}% (0 = no cap)", max_commission);
+            self.max_commission = max_commission;
+        }
+        if let Some(liquidity_epoch_outflow_cap) = liquidity_epoch_outflow_cap {
+            msg!(
+                "Liquidity epoch outflow cap {} (0 = unlimited)",
+                liquidity_epoch_outflow_cap
+            );
+            self.liq_pool_epoch_outflow_cap = liquidity_epoch_outflow_cap;
+        }
 
         Ok(())
     }
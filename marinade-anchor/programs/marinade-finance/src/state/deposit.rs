@@ -4,9 +4,11 @@ use anchor_spl::token::{mint_to, transfer, MintTo, Transfer};
 
 use crate::{
     checks::{check_address, check_min_amount, check_owner_program, check_token_mint},
+    events::{current_epoch, DepositEvent},
     liq_pool::LiqPoolHelpers,
+    referral::ReferralStateData,
     state::StateHelpers,
-    Deposit,
+    CommonError, Deposit, State,
 };
 
 impl<'info> Deposit<'info> {
@@ -24,7 +26,13 @@ impl<'info> Deposit<'info> {
     }
 
     // fn deposit_sol()
-    pub fn process(&mut self, lamports: u64) -> ProgramResult {
+    pub fn process(
+        &mut self,
+        lamports: u64,
+        min_msol_out: u64,
+        referral_state: Option<&AccountInfo<'info>>,
+    ) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_DEPOSIT)?;
         check_min_amount(lamports, self.state.min_deposit, "deposit SOL")?;
         self.state.check_reserve_address(self.reserve_pda.key)?;
         self.state
@@ -121,8 +129,10 @@ impl<'info> Deposit<'info> {
             user_lamports
         };
 
+        let sol_swapped = lamports - user_lamports;
+
         // check if we have more lamports from the user
-        if user_lamports > 0 {
+        let msol_minted = if user_lamports > 0 {
             self.state.check_staking_cap(user_lamports)?;
 
             //compute how much msol_to_mint
@@ -165,8 +175,50 @@ impl<'info> Deposit<'info> {
             }
             // self.state.stake_total += user_lamports; // auto calculated
             // self.state.epoch_stake_orders += user_lamports;
+            msol_to_mint
+        } else {
+            0
+        };
+
+        // guards the user against a price or liq-pool change after the simulation
+        let msol_out = swap_msol_max + msol_minted;
+        if msol_out < min_msol_out {
+            msg!(
+                "Deposit gives {} mSOL lamports but at least {} requested",
+                msol_out,
+                min_msol_out
+            );
+            return Err(CommonError::SlippageExceeded.into());
         }
 
+        // account the routed volume to the referral partner
+        let referral = if let Some(referral_state) = referral_state {
+            let mut referral =
+                ReferralStateData::load(referral_state, self.state.to_account_info().key)?;
+            if referral.is_paused() {
+                msg!("Referral {} is paused", referral.code());
+                Pubkey::default()
+            } else {
+                referral.on_deposit(lamports);
+                referral.save(referral_state)?;
+                *referral_state.key
+            }
+        } else {
+            Pubkey::default()
+        };
+
+        emit!(DepositEvent {
+            state: *self.state.to_account_info().key,
+            sol_owner: *self.transfer_from.key,
+            mint_to: *self.mint_to.to_account_info().key,
+            lamports,
+            msol_swapped: swap_msol_max,
+            sol_swapped,
+            msol_minted,
+            referral,
+            msol_price: self.state.msol_price,
+            epoch: current_epoch()?,
+        });
         Ok(())
     }
 }
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{checks::check_address, CommonError, DepositDirected};
+
+impl<'info> DepositDirected<'info> {
+    // fn deposit_directed()
+    pub fn process(
+        &mut self,
+        lamports: u64,
+        min_msol_out: u64,
+        validator_index: u32,
+    ) -> ProgramResult {
+        self.deposit
+            .state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+        let mut validator = self
+            .deposit
+            .state
+            .validator_system
+            .get(&self.validator_list.data.as_ref().borrow(), validator_index)?;
+        check_address(
+            self.validator_vote.key,
+            &validator.validator_account,
+            "validator_vote",
+        )?;
+        // a zero score validator is leaving the pool and gets no new stake
+        if validator.score == 0 {
+            msg!(
+                "Validator {} has zero score. Direct the stake to another validator",
+                validator.validator_account
+            );
+            return Err(CommonError::InvalidValidator.into());
+        }
+
+        let reserve_balance_before = self.deposit.state.available_reserve_balance;
+        self.deposit.process(lamports, min_msol_out, None)?;
+        // only the part going to the reserve is new stake.
+        // The part swapped with the liq-pool mSOL leg is already staked
+        let directed_lamports = self
+            .deposit
+            .state
+            .available_reserve_balance
+            .saturating_sub(reserve_balance_before);
+        if directed_lamports == 0 {
+            msg!("Deposit fully filled from the liq-pool. Nothing to direct");
+            return Ok(());
+        }
+
+        self.deposit
+            .state
+            .on_directed_deposit(&mut validator, directed_lamports)?;
+        self.deposit.state.validator_system.set(
+            &mut self.validator_list.data.as_ref().borrow_mut(),
+            validator_index,
+            validator,
+        )?;
+        msg!(
+            "Directed {} lamports to validator {}",
+            directed_lamports,
+            validator.validator_account
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::CommonError,
+    queued_change::{ParamsChange, QueuedChangeData},
+    ExecuteParamsChange,
+};
+
+/// Permissionless crank: applies a queued change once its delay has passed
+impl<'info> ExecuteParamsChange<'info> {
+    pub fn process(&mut self) -> ProgramResult {
+        self.queued_change
+            .check(self.state.to_account_info().key, self.rent_payer.key)?;
+        self.queued_change.check_change_version()?;
+        if self.clock.epoch < self.queued_change.earliest_execution_epoch {
+            msg!(
+                "Queued change is due on epoch {}",
+                self.queued_change.earliest_execution_epoch
+            );
+            return Err(CommonError::QueuedChangeNotDue.into());
+        }
+
+        // values are checked again because state could change since queued
+        match self.queued_change.change {
+            ParamsChange::ConfigMarinade(params) => self.state.config(params)?,
+            ParamsChange::SetLpParams {
+                min_fee,
+                max_fee,
+                liquidity_target,
+                fee_curve,
+            } => self
+                .state
+                .set_lp_params(min_fee, max_fee, liquidity_target, fee_curve)?,
+        }
+
+        QueuedChangeData::close(&mut self.queued_change, &self.rent_payer)
+    }
+}
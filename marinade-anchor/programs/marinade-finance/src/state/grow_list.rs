@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    checks::check_owner_program, stake_system::StakeRecord, validator_system::ValidatorRecord,
+    GrowList, ID,
+};
+
+impl<'info> GrowList<'info> {
+    // fn grow_list()
+    pub fn process(&mut self, max_copy_count: u32) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        check_owner_program(&self.new_list, &ID, "new_list")?;
+        if !self
+            .rent
+            .is_exempt(self.new_list.lamports(), self.new_list.data_len())
+        {
+            msg!("New list {} must be rent exempt", self.new_list.key);
+            return Err(ProgramError::InsufficientFunds);
+        }
+        if self.new_list.data_len() <= self.list.data_len() {
+            msg!(
+                "New list {} must be bigger than {} bytes",
+                self.new_list.key,
+                self.list.data_len()
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (list, discriminator, legacy_discriminator, record_size, migrate_legacy, list_name): (
+            _,
+            _,
+            _,
+            _,
+            fn(&[u8], &mut [u8]) -> ProgramResult,
+            _,
+        ) = if self.list.key == self.state.stake_system.stake_list_address() {
+            (
+                &mut self.state.stake_system.stake_list,
+                StakeRecord::DISCRIMINATOR,
+                StakeRecord::LEGACY_DISCRIMINATOR,
+                std::mem::size_of::<StakeRecord>(),
+                StakeRecord::migrate_legacy,
+                "stake_list",
+            )
+        } else if self.list.key == self.state.validator_system.validator_list_address() {
+            (
+                &mut self.state.validator_system.validator_list,
+                ValidatorRecord::DISCRIMINATOR,
+                ValidatorRecord::LEGACY_DISCRIMINATOR,
+                std::mem::size_of::<ValidatorRecord>(),
+                ValidatorRecord::migrate_legacy,
+                "validator_list",
+            )
+        } else {
+            msg!(
+                "Account {} is neither the stake list nor the validator list",
+                self.list.key
+            );
+            return Err(ProgramError::InvalidArgument);
+        };
+
+        // lists with the legacy record layout (whatever additional record space they have)
+        // are rewritten with the current one while copying
+        let is_legacy = &self.list.data.borrow()[0..8] == legacy_discriminator;
+        let done = if is_legacy {
+            list.migrate_account(
+                &self.list,
+                &self.new_list,
+                legacy_discriminator,
+                discriminator,
+                record_size as u32,
+                max_copy_count,
+                migrate_legacy,
+                list_name,
+            )?
+        } else {
+            list.change_account(
+                &self.list,
+                &self.new_list,
+                discriminator,
+                max_copy_count,
+                list_name,
+            )?
+        };
+        if done {
+            msg!("{} moved into {}", list_name, list.account);
+            // the old list is not used anymore
+            let old_list_lamports = self.list.lamports();
+            **self.list.lamports.borrow_mut() = 0;
+            **self.rent_receiver.lamports.borrow_mut() += old_list_lamports;
+        } else {
+            msg!(
+                "{} {}/{} items copied",
+                list_name,
+                list.copied_count,
+                list.len()
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction, system_program};
+
+use crate::{
+    checks::{check_address, check_min_amount, check_owner_program},
+    events::current_epoch,
+    liq_pool::LiqPoolHelpers,
+    CommonError, InstantClaim, State,
+};
+
+/// InstantClaim instruction: the beneficiary sells a (not yet due) Ticket-account to the liq-pool SOL leg.
+/// The user gets the ticket lamports minus the same fee LiquidUnstake charges,
+/// the ticket is reassigned to the SOL leg and booked as receivable until it is claimed into the pool
+impl<'info> InstantClaim<'info> {
+    fn check_ticket_account(&self) -> ProgramResult {
+        // ticket account program-owner must be marinade (TODO: I think it was checked by anchor already)
+        check_owner_program(
+            &self.ticket_account,
+            &crate::ID, //owner-program should be marinade
+            "ticket_account",
+        )?;
+        if &self.ticket_account.state_address != self.state.to_account_info().key {
+            msg!(
+                "Ticket has wrong marinade instance {}",
+                self.ticket_account.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.lamports_amount == 0 {
+            msg!("Used ticket");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.beneficiary != *self.beneficiary.key {
+            msg!("wrong beneficiary");
+            return Err(CommonError::WrongBeneficiary.into());
+        }
+        Ok(())
+    }
+
+    fn check_transfer_sol_to(&self) -> ProgramResult {
+        check_owner_program(
+            &self.transfer_sol_to,
+            &system_program::ID,
+            "transfer_sol_to",
+        )?;
+        Ok(())
+    }
+
+    // fn instant_claim()
+    pub fn process(&mut self) -> ProgramResult {
+        // the pool pays SOL out same as LiquidUnstake
+        self.state.check_not_paused(State::PAUSE_LIQUID_UNSTAKE)?;
+        self.check_ticket_account()?;
+        self.state
+            .check_liq_pool_sol_leg_pda(self.liq_pool_sol_leg_pda.key)?;
+        self.check_transfer_sol_to()?;
+        check_address(
+            self.system_program.key,
+            &system_program::ID,
+            "system_program",
+        )?;
+
+        let ticket_lamports = self.ticket_account.lamports_amount;
+
+        let max_lamports = self
+            .liq_pool_sol_leg_pda
+            .lamports()
+            .saturating_sub(self.state.rent_exempt_for_token_acc);
+
+        // fee is computed based on the liquidity *after* the user takes the sol, same as liquid-unstake
+        let instant_claim_fee = if ticket_lamports >= max_lamports {
+            // user is removing all liquidity
+            self.state.liq_pool.lp_max_fee
+        } else {
+            let after_lamports = max_lamports - ticket_lamports; //how much will be left?
+            self.state
+                .liq_pool
+                .fee(&self.state.liq_pool_fee_curve, after_lamports)
+        };
+
+        // the whole fee stays in the pool for the LPs
+        let lamports_fee = instant_claim_fee.apply(ticket_lamports);
+        msg!("lamports_fee {}", lamports_fee);
+        let working_lamports_value = ticket_lamports - lamports_fee;
+
+        // it can't be more than what's in the LiqPool
+        if working_lamports_value + self.state.rent_exempt_for_token_acc
+            > self.liq_pool_sol_leg_pda.lamports()
+        {
+            return Err(CommonError::InsufficientLiquidity.into());
+        }
+
+        check_min_amount(
+            working_lamports_value,
+            self.state.min_withdraw,
+            "withdraw SOL",
+        )?;
+        self.state
+            .on_liq_pool_sol_outflow(working_lamports_value, current_epoch()?)?;
+
+        //transfer SOL from the liq-pool to the user
+        self.state.with_liq_pool_sol_leg_seeds(|sol_seeds| {
+            invoke_signed(
+                &system_instruction::transfer(
+                    self.liq_pool_sol_leg_pda.key,
+                    self.transfer_sol_to.key,
+                    working_lamports_value,
+                ),
+                &[
+                    self.liq_pool_sol_leg_pda.clone(),
+                    self.transfer_sol_to.clone(),
+                    self.system_program.clone(),
+                ],
+                &[sol_seeds],
+            )
+        })?;
+
+        // the pool owns the ticket now, anyone can Claim it into the sol leg once it is due
+        self.ticket_account.beneficiary = *self.liq_pool_sol_leg_pda.key;
+        self.state.on_liq_pool_ticket_bought(ticket_lamports);
+
+        Ok(())
+    }
+}
@@ -5,8 +5,10 @@ use anchor_spl::token::{transfer, Transfer};
 use crate::checks::check_min_amount;
 use crate::{
     checks::{check_address, check_owner_program, check_token_mint},
+    events::{current_epoch, LiquidUnstakeEvent},
     liq_pool::LiqPoolHelpers,
-    CommonError, LiquidUnstake,
+    referral::ReferralStateData,
+    CommonError, LiquidUnstake, State,
 };
 
 impl<'info> LiquidUnstake<'info> {
@@ -53,7 +55,13 @@ impl<'info> LiquidUnstake<'info> {
     }
 
     // fn liquid_unstake()
-    pub fn process(&mut self, msol_amount: u64) -> ProgramResult {
+    pub fn process(
+        &mut self,
+        msol_amount: u64,
+        min_lamports_out: u64,
+        referral_accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_LIQUID_UNSTAKE)?;
         msg!("enter LiquidUnstake");
 
         self.state
@@ -74,6 +82,24 @@ impl<'info> LiquidUnstake<'info> {
             "system_program",
         )?;
         check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+        // paused referral gets neither the volume nor the fee share
+        let referral = if let [referral_state, referral_msol_account] = referral_accounts {
+            let referral =
+                ReferralStateData::load(referral_state, self.state.to_account_info().key)?;
+            check_address(
+                referral_msol_account.key,
+                &referral.msol_token_account,
+                "referral_msol_account",
+            )?;
+            if referral.is_paused() {
+                msg!("Referral {} is paused", referral.code());
+                None
+            } else {
+                Some((referral, referral_state, referral_msol_account))
+            }
+        } else {
+            None
+        };
 
         let max_lamports = self
             .liq_pool_sol_leg_pda
@@ -82,13 +108,11 @@ impl<'info> LiquidUnstake<'info> {
 
         // fee is computed based on the liquidity *after* the user takes the sol
         let user_remove_lamports = self.state.calc_lamports_from_msol_amount(msol_amount)?;
-        let liquid_unstake_fee = if user_remove_lamports >= max_lamports {
-            // user is removing all liquidity
-            self.state.liq_pool.lp_max_fee
-        } else {
-            let after_lamports = max_lamports - user_remove_lamports; //how much will be left?
-            self.state.liq_pool.linear_fee(after_lamports)
-        };
+        let liquid_unstake_fee = self.state.liq_pool.liquid_unstake_fee(
+            &self.state.liq_pool_fee_curve,
+            user_remove_lamports,
+            max_lamports,
+        );
 
         // compute fee in msol
         let msol_fee = liquid_unstake_fee.apply(msol_amount);
@@ -112,6 +136,17 @@ impl<'info> LiquidUnstake<'info> {
             self.state.min_withdraw,
             "withdraw SOL",
         )?;
+        // guards the user against a price or liq-pool fee change after the simulation
+        if working_lamports_value < min_lamports_out {
+            msg!(
+                "Liquid unstake gives {} lamports but at least {} requested",
+                working_lamports_value,
+                min_lamports_out
+            );
+            return Err(CommonError::SlippageExceeded.into());
+        }
+        self.state
+            .on_liq_pool_sol_outflow(working_lamports_value, current_epoch()?)?;
 
         //transfer SOL from the liq-pool to the user
         if working_lamports_value > 0 {
@@ -138,6 +173,13 @@ impl<'info> LiquidUnstake<'info> {
         } else {
             0
         };
+        // the referral partner gets a share of the treasury cut
+        let referral_msol_cut = if let Some((referral, _, _)) = &referral {
+            referral.treasury_cut_share.apply(treasury_msol_cut)
+        } else {
+            0
+        };
+        let treasury_msol_cut = treasury_msol_cut - referral_msol_cut;
         msg!("treasury_msol_cut {}", treasury_msol_cut);
 
         //transfer mSOL to the liq-pool
@@ -150,7 +192,7 @@ impl<'info> LiquidUnstake<'info> {
                     authority: self.get_msol_from_authority.clone(),
                 },
             ),
-            msol_amount - treasury_msol_cut,
+            msol_amount - treasury_msol_cut - referral_msol_cut,
         )?;
 
         //transfer treasury cut to treasury_msol_account
@@ -168,6 +210,42 @@ impl<'info> LiquidUnstake<'info> {
             )?;
         }
 
+        let referral = if let Some((mut referral, referral_state, referral_msol_account)) = referral
+        {
+            msg!("referral_msol_cut {}", referral_msol_cut);
+            if referral_msol_cut > 0 {
+                transfer(
+                    CpiContext::new(
+                        self.token_program.clone(),
+                        Transfer {
+                            from: self.get_msol_from.to_account_info(),
+                            to: referral_msol_account.clone(),
+                            authority: self.get_msol_from_authority.clone(),
+                        },
+                    ),
+                    referral_msol_cut,
+                )?;
+            }
+            referral.on_liquid_unstake(msol_amount, referral_msol_cut);
+            referral.save(referral_state)?;
+            *referral_state.key
+        } else {
+            Pubkey::default()
+        };
+
+        emit!(LiquidUnstakeEvent {
+            state: *self.state.to_account_info().key,
+            msol_owner: self.get_msol_from.owner,
+            transfer_sol_to: *self.transfer_sol_to.key,
+            msol_amount,
+            msol_fee,
+            treasury_msol_cut,
+            referral,
+            referral_msol_cut,
+            lamports: working_lamports_value,
+            msol_price: self.state.msol_price,
+            epoch: current_epoch()?,
+        });
         Ok(())
     }
 }
@@ -3,7 +3,8 @@ use anchor_spl::token::{burn, Burn};
 
 use crate::{
     checks::{check_address, check_min_amount, check_owner_program, check_token_mint},
-    OrderUnstake,
+    events::OrderUnstakeEvent,
+    OrderUnstake, State,
 };
 
 impl<'info> OrderUnstake<'info> {
@@ -64,6 +65,7 @@ impl<'info> OrderUnstake<'info> {
 
     // fn order_unstake() // create delayed-unstake Ticket-account
     pub fn process(&mut self, msol_amount: u64) -> ProgramResult {
+        self.state.check_not_paused(State::PAUSE_ORDER_UNSTAKE)?;
         // fn order_unstake()
         check_address(self.token_program.key, &spl_token::ID, "token_program")?;
         self.check_new_ticket_account()?;
@@ -126,6 +128,15 @@ impl<'info> OrderUnstake<'info> {
                 0
             };
 
+        emit!(OrderUnstakeEvent {
+            state: *self.state.to_account_info().key,
+            ticket: *self.new_ticket_account.to_account_info().key,
+            beneficiary: ticket_beneficiary,
+            msol_burned: msol_amount,
+            lamports: lamports_amount,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
         Ok(())
     }
 }
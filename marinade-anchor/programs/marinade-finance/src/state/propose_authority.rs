@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{ProposeAuthority, ProposeAuthorityData};
+
+impl<'info> ProposeAuthority<'info> {
+    // Some(Pubkey::default()) cancels the pending proposal
+    pub fn process(&mut self, data: ProposeAuthorityData) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+
+        if let Some(admin) = data.admin {
+            msg!("Proposed admin authority {}", admin);
+            self.state.pending_admin_authority = admin;
+        }
+
+        if let Some(validator_manager) = data.validator_manager {
+            msg!("Proposed validator manager authority {}", validator_manager);
+            self.state.pending_validator_manager_authority = validator_manager;
+        }
+
+        if let Some(operational_sol_account) = data.operational_sol_account {
+            msg!(
+                "Proposed operational sol account {}",
+                operational_sol_account
+            );
+            self.state.pending_operational_sol_account = operational_sol_account;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    queued_change::{ParamsChange, QueuedChangeData},
+    ConfigMarinadeParams, QueueParamsChange,
+};
+
+impl<'info> QueueParamsChange<'info> {
+    pub fn process(&mut self, change: ParamsChange) -> ProgramResult {
+        self.state.check_admin_authority(self.admin_authority.key)?;
+        // pausing is immediate with config_marinade, a queued one could resume operations later
+        if let ParamsChange::ConfigMarinade(ConfigMarinadeParams {
+            paused_operations: Some(_),
+            ..
+        }) = change
+        {
+            msg!("Paused operations can not be queued, use config_marinade");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if change.try_to_vec()?.len() > QueuedChangeData::MAX_CHANGE_LEN {
+            msg!("Change is too big");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let earliest_execution_epoch = self.clock.epoch + self.state.params_change_delay_epochs;
+        msg!(
+            "Queued {:?} to be executed from epoch {}",
+            change,
+            earliest_execution_epoch
+        );
+        self.queued_change.state_address = *self.state.to_account_info().key;
+        self.queued_change.rent_payer = *self.rent_payer.key;
+        self.queued_change.earliest_execution_epoch = earliest_execution_epoch;
+        self.queued_change.change_version = QueuedChangeData::CHANGE_VERSION;
+        self.queued_change.change = change;
+        Ok(())
+    }
+}
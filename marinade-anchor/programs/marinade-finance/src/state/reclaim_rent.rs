@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    checks::{check_address, check_owner_program},
+    ticket_account::TicketAccountData,
+    validator_system::ValidatorRecord,
+    ReclaimRent, ID,
+};
+
+impl<'info> ReclaimRent<'info> {
+    /// duplication flag of a validator which is not in the list anymore
+    fn check_duplication_flag(&self, validator_vote: &Pubkey) -> ProgramResult {
+        check_address(
+            self.orphaned_account.key,
+            &ValidatorRecord::find_duplication_flag(
+                self.state.to_account_info().key,
+                validator_vote,
+            )
+            .0,
+            "duplication_flag",
+        )?;
+        self.state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+        let validator_list = self.validator_list.data.borrow();
+        for index in 0..self.state.validator_system.validator_count() {
+            let validator = self.state.validator_system.get(&validator_list, index)?;
+            if &validator.validator_account == validator_vote {
+                msg!(
+                    "Validator {} is in the list. Its duplication flag is in use",
+                    validator_vote
+                );
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        Ok(())
+    }
+
+    /// fully claimed ticket left open
+    fn check_ticket(&self) -> ProgramResult {
+        let ticket: TicketAccountData =
+            AccountDeserialize::try_deserialize(&mut self.orphaned_account.data.borrow().as_ref())?;
+        if &ticket.state_address != self.state.to_account_info().key {
+            msg!(
+                "Ticket has wrong marinade instance {}",
+                ticket.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if ticket.lamports_amount != 0 {
+            msg!(
+                "Ticket {} still has {} lamports to claim",
+                self.orphaned_account.key,
+                ticket.lamports_amount
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    pub fn process(&mut self, validator_vote: Option<Pubkey>) -> ProgramResult {
+        self.state
+            .check_operational_sol_account(self.operational_sol_account.key)?;
+        check_owner_program(&self.orphaned_account, &ID, "orphaned_account")?;
+        if let Some(validator_vote) = validator_vote {
+            self.check_duplication_flag(&validator_vote)?;
+        } else {
+            self.check_ticket()?;
+        }
+
+        // the account will be deleted because is no longer rent-exempt
+        let rent_return = self.orphaned_account.lamports();
+        msg!(
+            "Reclaim {} lamports from {}",
+            rent_return,
+            self.orphaned_account.key
+        );
+        **self.orphaned_account.try_borrow_mut_lamports()? = 0;
+        **self.operational_sol_account.try_borrow_mut_lamports()? += rent_return;
+        Ok(())
+    }
+}
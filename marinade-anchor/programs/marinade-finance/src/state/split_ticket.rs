@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    checks::{check_min_amount, check_owner_program},
+    CommonError, SplitTicket,
+};
+
+/// SplitTicket instruction: the beneficiary moves part of a Ticket-account into a new Ticket-account.
+/// Both tickets keep the same beneficiary and due epoch, so they can be claimed (or transferred) separately
+impl<'info> SplitTicket<'info> {
+    fn check_ticket_account(&self) -> ProgramResult {
+        // ticket account program-owner must be marinade (TODO: I think it was checked by anchor already)
+        check_owner_program(
+            &self.ticket_account,
+            &crate::ID, //owner-program should be marinade
+            "ticket_account",
+        )?;
+        if &self.ticket_account.state_address != self.state.to_account_info().key {
+            msg!(
+                "Ticket has wrong marinade instance {}",
+                self.ticket_account.state_address
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.lamports_amount == 0 {
+            msg!("Used ticket");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if self.ticket_account.beneficiary != *self.beneficiary.key {
+            msg!("wrong beneficiary");
+            return Err(CommonError::WrongBeneficiary.into());
+        }
+        Ok(())
+    }
+
+    fn check_new_ticket_account(&self) -> ProgramResult {
+        // ticket account program-owner must be marinade (TODO: I think it was checked by anchor already)
+        check_owner_program(
+            &self.new_ticket_account,
+            &crate::ID, //owner-program should be marinade
+            "new_ticket_account",
+        )?;
+
+        // should be uninitialized - checked by anchor
+        // should be rent-exempt - checked by anchor
+        Ok(())
+    }
+
+    pub fn process(&mut self, new_ticket_lamports: u64) -> ProgramResult {
+        // fn split_ticket()
+        self.check_ticket_account()?;
+        self.check_new_ticket_account()?;
+
+        let ticket_lamports = self.ticket_account.lamports_amount;
+        if new_ticket_lamports >= ticket_lamports {
+            msg!(
+                "Requested to split {} from ticket with only {}",
+                new_ticket_lamports,
+                ticket_lamports
+            );
+            return Err(CommonError::NumberTooHigh.into());
+        }
+        // both parts must be claimable on their own
+        check_min_amount(
+            new_ticket_lamports,
+            self.state.min_withdraw,
+            "new ticket SOL",
+        )?;
+        check_min_amount(
+            ticket_lamports - new_ticket_lamports,
+            self.state.min_withdraw,
+            "remaining ticket SOL",
+        )?;
+
+        self.ticket_account.lamports_amount = ticket_lamports - new_ticket_lamports;
+
+        //initialize new_ticket_account, same instance, beneficiary and due epoch
+        self.new_ticket_account.state_address = self.ticket_account.state_address;
+        self.new_ticket_account.beneficiary = self.ticket_account.beneficiary;
+        self.new_ticket_account.lamports_amount = new_ticket_lamports;
+        self.new_ticket_account.created_epoch = self.ticket_account.created_epoch;
+
+        // circulating_ticket_balance is unchanged, only the number of tickets grows
+        self.state.circulating_ticket_count += 1;
+
+        Ok(())
+    }
+}
@@ -8,6 +8,8 @@ use anchor_spl::token::{mint_to, MintTo};
 
 use crate::{
     checks::check_address,
+    epoch_rewards::EpochRewards,
+    events::{SlashedEvent, UpdateActiveEvent, UpdateDeactivatedEvent},
     stake_system::{StakeRecord, StakeSystemHelpers},
     state::StateHelpers,
     State,
@@ -15,19 +17,13 @@ use crate::{
     UpdateCommon,
     // UpdateCoolingDown,
     UpdateDeactivated,
+    ID,
 };
 
-struct BeginOutput {
-    stake: StakeRecord,
-    is_treasury_msol_ready_for_transfer: bool,
-}
-
 impl<'info> UpdateCommon<'info> {
-    fn begin(&mut self, stake_index: u32) -> Result<BeginOutput, ProgramError> {
-        /*
-        self.state
-            .validator_system
-            .check_validator_list(self.validator_list.key)?;*/
+    /// Checks the accounts and syncs reserve balance & mSOL supply once per instruction.
+    /// Returns is_treasury_msol_ready_for_transfer
+    fn begin(&mut self) -> Result<bool, ProgramError> {
         self.state.stake_system.check_stake_list(&self.stake_list)?;
         self.state
             .check_msol_mint(self.msol_mint.to_account_info().key)?;
@@ -41,6 +37,11 @@ impl<'info> UpdateCommon<'info> {
         self.state.check_reserve_address(self.reserve_pda.key)?;
         check_address(self.stake_program.key, &stake::program::ID, "stake_program")?;
         check_address(self.token_program.key, &spl_token::ID, "token_program")?;
+        check_address(
+            self.epoch_rewards.key,
+            &EpochRewards::find_address(self.state.to_account_info().key).0,
+            "epoch_rewards",
+        )?;
 
         let virtual_reserve_balance = self
             .state
@@ -72,6 +73,10 @@ impl<'info> UpdateCommon<'info> {
         }
         self.state.msol_supply = self.msol_mint.supply;
 
+        Ok(is_treasury_msol_ready_for_transfer)
+    }
+
+    fn get_stake(&self, stake_index: u32) -> Result<StakeRecord, ProgramError> {
         let stake = self
             .state
             .stake_system
@@ -85,11 +90,13 @@ impl<'info> UpdateCommon<'info> {
             &stake.stake_account,
             "stake_account",
         )?;
+        Ok(stake)
+    }
 
-        Ok(BeginOutput {
-            stake,
-            is_treasury_msol_ready_for_transfer,
-        })
+    /// Switches to the next stake account of a batch (passed in the remaining accounts)
+    fn load_stake_account(&mut self, stake_account: &AccountInfo<'info>) -> ProgramResult {
+        self.stake_account = CpiAccount::try_from(stake_account)?;
+        Ok(())
     }
 
     pub fn withdraw_to_reserve(&mut self, amount: u64) -> ProgramResult {
@@ -120,7 +127,34 @@ impl<'info> UpdateCommon<'info> {
         Ok(())
     }
 
-    pub fn mint_to_treasury(&mut self, msol_lamports: u64) -> ProgramResult {
+    /// Appends the rewards of the instruction to the epoch record (if epoch_rewards is created)
+    fn record_epoch_rewards(&mut self, rewards: u64, msol_fees: u64) -> ProgramResult {
+        if self.epoch_rewards.owner != &ID {
+            return Ok(());
+        }
+        let state_address = *self.state.to_account_info().key;
+        let mut data = self.epoch_rewards.data.borrow_mut();
+        let mut epoch_rewards = EpochRewards::load(&data, &state_address)?;
+        epoch_rewards.add(
+            &mut data,
+            self.clock.epoch,
+            rewards,
+            msol_fees,
+            self.state.msol_price,
+            self.state.total_lamports_under_control(),
+        );
+        Ok(())
+    }
+
+    /// Adds the treasury fee to the mSOL supply right away, so the next fees and the price
+    /// are computed the same as if it was minted. Returns the amount to mint_to_treasury later
+    fn add_treasury_fee(&mut self, msol_lamports: u64) -> u64 {
+        self.state.on_msol_mint(msol_lamports);
+        msol_lamports
+    }
+
+    /// Mints all the treasury fees of the instruction (already added to the mSOL supply)
+    fn mint_to_treasury(&mut self, msol_lamports: u64) -> ProgramResult {
         if msol_lamports > 0 {
             self.state.with_msol_mint_authority_seeds(|seeds| {
                 mint_to(
@@ -136,7 +170,6 @@ impl<'info> UpdateCommon<'info> {
                     msol_lamports,
                 )
             })?;
-            self.state.on_msol_mint(msol_lamports);
         }
         Ok(())
     }
@@ -151,10 +184,67 @@ impl<'info> UpdateActive<'info> {
     //
     // fn update_active()
     pub fn process(&mut self, stake_index: u32, validator_index: u32) -> ProgramResult {
-        let BeginOutput {
-            mut stake,
-            is_treasury_msol_ready_for_transfer,
-        } = self.begin(stake_index)?;
+        self.process_batch(&[stake_index], &[validator_index], &[])
+    }
+
+    /// Updates many stake accounts at once. The first one is stake_account,
+    /// the others are passed in the remaining accounts in the same order as stake_indexes
+    pub fn process_batch(
+        &mut self,
+        stake_indexes: &[u32],
+        validator_indexes: &[u32],
+        stake_accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        if stake_indexes.len() != validator_indexes.len() {
+            msg!(
+                "Got {} stake indexes and {} validator indexes",
+                stake_indexes.len(),
+                validator_indexes.len()
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if stake_accounts.len() + 1 != stake_indexes.len() {
+            msg!(
+                "Got {} stake indexes and {} stake accounts",
+                stake_indexes.len(),
+                stake_accounts.len() + 1
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let is_treasury_msol_ready_for_transfer = self.begin()?;
+        self.state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+        let mut rewards = 0;
+        let mut msol_fees = 0;
+        for (i, (stake_index, validator_index)) in stake_indexes
+            .iter()
+            .zip(validator_indexes.iter())
+            .enumerate()
+        {
+            if i > 0 {
+                self.load_stake_account(&stake_accounts[i - 1])?;
+            }
+            let (stake_rewards, stake_msol_fees) = self.update_stake(
+                *stake_index,
+                *validator_index,
+                is_treasury_msol_ready_for_transfer,
+            )?;
+            rewards += stake_rewards;
+            msol_fees += stake_msol_fees;
+        }
+        self.mint_to_treasury(msol_fees)?;
+        self.record_epoch_rewards(rewards, msol_fees)
+    }
+
+    /// Returns rewards and mSOL fees to mint
+    fn update_stake(
+        &mut self,
+        stake_index: u32,
+        validator_index: u32,
+        is_treasury_msol_ready_for_transfer: bool,
+    ) -> Result<(u64, u64), ProgramError> {
+        let mut stake = self.get_stake(stake_index)?;
 
         let mut validator = self
             .state
@@ -193,13 +283,21 @@ impl<'info> UpdateActive<'info> {
         let extra_lamports = stake_balance_without_rent.saturating_sub(delegated_lamports);
         msg!("Extra lamports in stake balance: {}", extra_lamports);
         self.withdraw_to_reserve(extra_lamports)?;
+        // total mSOL minted to treasury by this update
+        let mut msol_fees = 0;
         if is_treasury_msol_ready_for_transfer {
             let msol_amount = self.state.calc_msol_from_lamports(extra_lamports)?;
-            self.mint_to_treasury(msol_amount)?;
+            msol_fees += self.add_treasury_fee(msol_amount);
         }
 
         msg!("current staked lamports {}", delegated_lamports);
-        if delegated_lamports >= stake.last_update_delegated_lamports {
+        let old_msol_price = self
+            .state
+            .calc_lamports_from_msol_amount(State::PRICE_DENOMINATOR)?;
+        let slashed = stake
+            .last_update_delegated_lamports
+            .saturating_sub(delegated_lamports);
+        let rewards = if slashed == 0 {
             // re-delegated by solana rewards
             let rewards = delegated_lamports - stake.last_update_delegated_lamports;
             msg!("Staking rewards: {}", rewards);
@@ -211,17 +309,18 @@ impl<'info> UpdateActive<'info> {
                 // compute mSOL amount for protocol_rewards_fee
                 let fee_as_msol_amount =
                     self.state.calc_msol_from_lamports(protocol_rewards_fee)?;
-                self.mint_to_treasury(fee_as_msol_amount)?;
+                msol_fees += self.add_treasury_fee(fee_as_msol_amount);
             }
 
             // validator active balance is updated with rewards
             validator.active_balance += rewards;
             // validator_system.total_active_balance is updated with re-delegated rewards (this impacts price-calculation)
             self.state.validator_system.total_active_balance += rewards;
+            rewards
         } else {
-            //slashed
-            let slashed = stake.last_update_delegated_lamports - delegated_lamports;
-            msg!("slashed {}", slashed);
+            // slashed. The loss is socialized: total_active_balance goes down
+            // and so does the mSOL price, no fee is taken
+            msg!("Slashed {}", slashed);
             //validator balance is updated with slashed
             validator.active_balance = validator.active_balance.saturating_sub(slashed);
             self.state.validator_system.total_active_balance = self
@@ -229,7 +328,10 @@ impl<'info> UpdateActive<'info> {
                 .validator_system
                 .total_active_balance
                 .saturating_sub(slashed);
-        }
+            0
+        };
+        let delegation_change =
+            delegated_lamports as i64 - stake.last_update_delegated_lamports as i64;
 
         // mark stake-account as visited
         stake.last_update_epoch = self.clock.epoch;
@@ -257,7 +359,29 @@ impl<'info> UpdateActive<'info> {
             self.state.available_reserve_balance + self.state.rent_exempt_for_token_acc,
             self.reserve_pda.lamports()
         );
-        Ok(())
+
+        if slashed > 0 {
+            emit!(SlashedEvent {
+                state: *self.state.to_account_info().key,
+                stake_account: stake.stake_account,
+                validator_vote: validator.validator_account,
+                slashed,
+                old_msol_price,
+                msol_price: self.state.msol_price,
+                epoch: self.clock.epoch,
+            });
+        }
+        emit!(UpdateActiveEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: stake.stake_account,
+            validator_vote: validator.validator_account,
+            delegation_change,
+            rewards,
+            msol_fees,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+        Ok((rewards, msol_fees))
     }
 }
 
@@ -268,11 +392,27 @@ impl<'info> UpdateDeactivated<'info> {
     /// Optional Future Expansion: Partial: If the stake-account is a fully-deactivated stake account ready to withdraw,
     /// (cool-down period is complete) delete-withdraw the stake-account, send SOL to reserve-account
     pub fn process(&mut self, stake_index: u32) -> ProgramResult {
-        let BeginOutput {
-            stake,
-            is_treasury_msol_ready_for_transfer,
-        } = self.begin(stake_index)?;
+        self.process_batch(&[stake_index], &[])
+    }
 
+    /// Updates many deactivated stake accounts at once. The first one is stake_account,
+    /// the others are passed in the remaining accounts in the same order as stake_indexes.
+    /// Stake accounts are removed from the list one by one (the last one takes the place
+    /// of the removed), so use decreasing stake_indexes
+    pub fn process_batch(
+        &mut self,
+        stake_indexes: &[u32],
+        stake_accounts: &[AccountInfo<'info>],
+    ) -> ProgramResult {
+        if stake_accounts.len() + 1 != stake_indexes.len() {
+            msg!(
+                "Got {} stake indexes and {} stake accounts",
+                stake_indexes.len(),
+                stake_accounts.len() + 1
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let is_treasury_msol_ready_for_transfer = self.begin()?;
         check_address(
             self.system_program.to_account_info().key,
             &system_program::ID,
@@ -281,6 +421,29 @@ impl<'info> UpdateDeactivated<'info> {
         self.state
             .check_operational_sol_account(self.operational_sol_account.key)?;
 
+        let mut rewards = 0;
+        let mut msol_fees = 0;
+        for (i, stake_index) in stake_indexes.iter().enumerate() {
+            if i > 0 {
+                self.load_stake_account(&stake_accounts[i - 1])?;
+            }
+            let (stake_rewards, stake_msol_fees) =
+                self.update_stake(*stake_index, is_treasury_msol_ready_for_transfer)?;
+            rewards += stake_rewards;
+            msol_fees += stake_msol_fees;
+        }
+        self.mint_to_treasury(msol_fees)?;
+        self.record_epoch_rewards(rewards, msol_fees)
+    }
+
+    /// Returns rewards and mSOL fees to mint
+    fn update_stake(
+        &mut self,
+        stake_index: u32,
+        is_treasury_msol_ready_for_transfer: bool,
+    ) -> Result<(u64, u64), ProgramError> {
+        let stake = self.get_stake(stake_index)?;
+
         let delegation = self
             .stake_account
             .delegation()
@@ -301,12 +464,20 @@ impl<'info> UpdateDeactivated<'info> {
         // and mint 100% mSOL to treasury to make admins decide what to do with this (maybe return to sender)
         let extra_lamports = stake_balance_without_rent.saturating_sub(delegated_lamports);
         msg!("Extra lamports in stake balance: {}", extra_lamports);
+        // total mSOL minted to treasury by this update
+        let mut msol_fees = 0;
         if is_treasury_msol_ready_for_transfer {
             let msol_amount = self.state.calc_msol_from_lamports(extra_lamports)?;
-            self.mint_to_treasury(msol_amount)?;
+            msol_fees += self.add_treasury_fee(msol_amount);
         }
 
-        if delegated_lamports >= stake.last_update_delegated_lamports {
+        let old_msol_price = self
+            .state
+            .calc_lamports_from_msol_amount(State::PRICE_DENOMINATOR)?;
+        let slashed = stake
+            .last_update_delegated_lamports
+            .saturating_sub(delegated_lamports);
+        let rewards = if slashed == 0 {
             // if there were rewards, mint treasury fee
             let rewards = delegated_lamports - stake.last_update_delegated_lamports;
             msg!("Staking rewards: {}", rewards);
@@ -318,16 +489,18 @@ impl<'info> UpdateDeactivated<'info> {
                 // compute mSOL amount for protocol_rewards_fee
                 let fee_as_msol_amount =
                     self.state.calc_msol_from_lamports(protocol_rewards_fee)?;
-                self.mint_to_treasury(fee_as_msol_amount)?;
+                msol_fees += self.add_treasury_fee(fee_as_msol_amount);
             }
+            rewards
         } else {
-            let slashed = stake.last_update_delegated_lamports - delegated_lamports;
+            // the reserve gets less than the cooling down amount, so the mSOL price goes down
             msg!("Slashed {}", slashed);
-        }
+            0
+        };
 
         // withdraw all to reserve (the stake account will be marked for deletion by the system)
-        self.common
-            .withdraw_to_reserve(self.stake_account.to_account_info().lamports())?;
+        let stake_account_lamports = self.stake_account.to_account_info().lamports();
+        self.common.withdraw_to_reserve(stake_account_lamports)?;
         // but send the rent-exempt lamports part to operational_sol_account for the future recreation of this slot's account
         self.state.with_reserve_seeds(|seeds| {
             invoke_signed(
@@ -374,6 +547,25 @@ impl<'info> UpdateDeactivated<'info> {
             stake_index,
         )?;
 
-        Ok(())
+        if slashed > 0 {
+            emit!(SlashedEvent {
+                state: *self.state.to_account_info().key,
+                stake_account: stake.stake_account,
+                validator_vote: delegation.voter_pubkey,
+                slashed,
+                old_msol_price,
+                msol_price: self.state.msol_price,
+                epoch: self.clock.epoch,
+            });
+        }
+        emit!(UpdateDeactivatedEvent {
+            state: *self.state.to_account_info().key,
+            stake_account: stake.stake_account,
+            lamports_to_reserve: stake_account_lamports - rent,
+            operational_sol_account_fee: rent,
+            msol_price: self.state.msol_price,
+            epoch: self.clock.epoch,
+        });
+        Ok((rewards, msol_fees))
     }
 }
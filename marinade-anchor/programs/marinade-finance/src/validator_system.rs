@@ -1,28 +1,67 @@
-//use std::convert::TryInto;
-
-use crate::{calc::proportional, checks::check_address, error::CommonError, list::List, ID};
+use crate::{
+    calc::proportional,
+    checks::{check_address, check_owner_program},
+    error::CommonError,
+    list::List,
+    ID,
+};
 use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use std::convert::TryInto;
 
 pub mod add;
+pub mod block;
 pub mod config_validator_system;
 pub mod remove;
 pub mod set_score;
+pub mod set_stake_bounds;
+
+/// solana_program 1.7 has no vote module
+pub mod vote_program {
+    anchor_lang::solana_program::declare_id!("Vote111111111111111111111111111111111111111");
+}
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+/// Zero-copy validator list item. Must have no implicit padding
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct ValidatorRecord {
     /// Validator vote pubkey
     pub validator_account: Pubkey,
 
     /// Validator total balance in lamports
     pub active_balance: u64, // must be 0 for removing
-    pub score: u32,
     pub last_stake_delta_epoch: u64,
+    /// lamports deposited with DepositDirected and not staked into this validator yet
+    pub directed_stake_lamports: u64,
+    /// stake target floor set by the validator manager (0 = no floor)
+    pub min_stake: u64,
+    /// stake target cap set by the validator manager (0 = no cap)
+    pub max_stake: u64,
+    pub score: u32,
     pub duplication_flag_bump_seed: u8,
+    pub reserved: [u8; 3],
+}
+
+unsafe impl Zeroable for ValidatorRecord {}
+unsafe impl Pod for ValidatorRecord {}
+
+/// Validator list item as it was stored before the stake bounds
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct LegacyValidatorRecord {
+    validator_account: Pubkey,
+    active_balance: u64,
+    score: u32,
+    last_stake_delta_epoch: u64,
+    duplication_flag_bump_seed: u8,
 }
 
 impl ValidatorRecord {
-    pub const DISCRIMINATOR: &'static [u8; 8] = b"validatr";
+    pub const DISCRIMINATOR: &'static [u8; 8] = b"validtr2";
     pub const DUPLICATE_FLAG_SEED: &'static [u8] = b"unique_validator";
+    /// Discriminator of the lists created before the stake bounds
+    pub const LEGACY_DISCRIMINATOR: &'static [u8; 8] = b"validatr";
+    /// Legacy record size without the additional record space
+    pub const LEGACY_SIZE: usize = 53;
 
     pub fn find_duplication_flag(state: &Pubkey, validator_account: &Pubkey) -> (Pubkey, u8) {
         Pubkey::find_program_address(
@@ -35,6 +74,31 @@ impl ValidatorRecord {
         )
     }
 
+    /// Rewrites a record stored with the Borsh layout used before the stake bounds
+    /// (the first LEGACY_SIZE bytes of the item) into the current layout.
+    /// Used by grow_list to migrate the list
+    pub fn migrate_legacy(old_item: &[u8], new_item: &mut [u8]) -> ProgramResult {
+        if old_item.len() < Self::LEGACY_SIZE {
+            msg!(
+                "Legacy validator record has {} bytes, {} expected",
+                old_item.len(),
+                Self::LEGACY_SIZE
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let legacy = LegacyValidatorRecord::try_from_slice(&old_item[..Self::LEGACY_SIZE])?;
+        let record = Self {
+            validator_account: legacy.validator_account,
+            active_balance: legacy.active_balance,
+            last_stake_delta_epoch: legacy.last_stake_delta_epoch,
+            score: legacy.score,
+            duplication_flag_bump_seed: legacy.duplication_flag_bump_seed,
+            ..Self::default()
+        };
+        new_item.copy_from_slice(bytemuck::bytes_of(&record));
+        Ok(())
+    }
+
     pub fn with_duplication_flag_seeds<R, F: FnOnce(&[&[u8]]) -> R>(
         &self,
         state: &Pubkey,
@@ -48,6 +112,30 @@ impl ValidatorRecord {
         ])
     }
 
+    /// applies the validator manager bounds to a score based stake target.
+    /// The min_stake floor is skipped when the floors of all the validators can not be funded
+    pub fn bound_stake_target(&self, stake_target: u64, with_floor: bool) -> u64 {
+        let stake_target = if with_floor {
+            stake_target.max(self.min_stake)
+        } else {
+            stake_target
+        };
+        if self.max_stake > 0 {
+            stake_target.min(self.max_stake)
+        } else {
+            stake_target
+        }
+    }
+
+    /// how many lamports can be staked into this validator before reaching max_stake
+    pub fn stake_room(&self) -> u64 {
+        if self.max_stake > 0 {
+            self.max_stake.saturating_sub(self.active_balance)
+        } else {
+            std::u64::MAX
+        }
+    }
+
     pub fn duplication_flag_address(&self, state: &Pubkey) -> Pubkey {
         self.with_duplication_flag_seeds(state, |seeds| Pubkey::create_program_address(seeds, &ID))
             .unwrap()
@@ -75,6 +163,10 @@ impl ValidatorRecord {
             score,
             last_stake_delta_epoch: std::u64::MAX, // never
             duplication_flag_bump_seed,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
+            reserved: [0; 3],
         })
     }
 }
@@ -91,9 +183,12 @@ pub struct ValidatorSystem {
 }
 
 impl ValidatorSystem {
+    pub const BLOCKED_FLAG_SEED: &'static [u8] = b"blocked_validator";
+    pub const MAX_COMMISSION: u8 = 100;
+
     pub fn bytes_for_list(count: u32, additional_record_space: u32) -> u32 {
         List::bytes_for(
-            ValidatorRecord::default().try_to_vec().unwrap().len() as u32 + additional_record_space,
+            std::mem::size_of::<ValidatorRecord>() as u32 + additional_record_space,
             count,
         )
     }
@@ -115,8 +210,7 @@ impl ValidatorSystem {
         Ok(Self {
             validator_list: List::new(
                 ValidatorRecord::DISCRIMINATOR,
-                ValidatorRecord::default().try_to_vec().unwrap().len() as u32
-                    + additional_record_space,
+                std::mem::size_of::<ValidatorRecord>() as u32 + additional_record_space,
                 validator_list_account,
                 validator_list_data,
                 "validator_list",
@@ -237,15 +331,19 @@ impl ValidatorSystem {
         &self,
         validator: &ValidatorRecord,
         total_stake_target: u64,
+        with_floor: bool,
     ) -> Result<u64, CommonError> {
         if self.total_validator_score == 0 {
-            return Ok(0);
+            return Ok(validator.bound_stake_target(0, with_floor));
         }
-        proportional(
-            total_stake_target,
-            validator.score as u64,
-            self.total_validator_score as u64,
-        )
+        Ok(validator.bound_stake_target(
+            proportional(
+                total_stake_target,
+                validator.score as u64,
+                self.total_validator_score as u64,
+            )?,
+            with_floor,
+        ))
     }
 
     pub fn check_validator_list<'info>(
@@ -257,7 +355,14 @@ impl ValidatorSystem {
             self.validator_list_address(),
             "validator_list",
         )?;
-        if &validator_list.data.borrow().as_ref()[0..8] != ValidatorRecord::DISCRIMINATOR {
+        let validator_list_data = validator_list.data.borrow();
+        let discriminator = &validator_list_data[0..8];
+        // lists created before the stake bounds keep the legacy record layout
+        if discriminator == ValidatorRecord::LEGACY_DISCRIMINATOR {
+            msg!("Validator list has the legacy record layout. Migrate it with grow_list");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if discriminator != ValidatorRecord::DISCRIMINATOR {
             msg!("Wrong validator list account discriminator");
             return Err(ProgramError::InvalidAccountData);
         }
@@ -271,4 +376,96 @@ impl ValidatorSystem {
             "validator_manager_authority",
         )
     }
+
+    /// Reads the commission from the vote account data without deserializing the whole vote state
+    pub fn vote_commission(validator_vote: &AccountInfo) -> Result<u8, ProgramError> {
+        // VoteStateVersions tags: V0_23_5 = 0, V1_14_11 = 1, Current = 2.
+        // V1_14_11 and Current start with node_pubkey and authorized_withdrawer, then the commission
+        const V1_14_11_VERSION: u32 = 1;
+        const CURRENT_VERSION: u32 = 2;
+        const COMMISSION_OFFSET: usize = 4 + 32 + 32;
+        check_owner_program(validator_vote, &vote_program::ID, "validator_vote")?;
+        let data = validator_vote.data.borrow();
+        if data.len() <= COMMISSION_OFFSET {
+            msg!("Vote account {} is too small", validator_vote.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let version = u32::from_le_bytes(data[0..4].try_into().unwrap());
+        if version != V1_14_11_VERSION && version != CURRENT_VERSION {
+            msg!(
+                "Unsupported vote account {} version {}",
+                validator_vote.key,
+                version
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(data[COMMISSION_OFFSET])
+    }
+
+    /// Blocklist entry of the vote account. The account exists (owned by the program) only while
+    /// the validator is blocked by the manager_authority
+    pub fn find_blocked_flag(state: &Pubkey, validator_vote: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                &state.to_bytes()[..32],
+                Self::BLOCKED_FLAG_SEED,
+                &validator_vote.to_bytes()[..32],
+            ],
+            &ID,
+        )
+    }
+
+    pub fn check_not_blocked(
+        blocked_flag: &AccountInfo,
+        state: &Pubkey,
+        validator_vote: &Pubkey,
+    ) -> ProgramResult {
+        check_address(
+            blocked_flag.key,
+            &Self::find_blocked_flag(state, validator_vote).0,
+            "blocked_flag",
+        )?;
+        if blocked_flag.owner == &ID {
+            msg!("Validator {} is blocked", validator_vote);
+            return Err(CommonError::ValidatorBlocked.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::*;
+
+    use crate::validator_system::{vote_program, ValidatorSystem};
+
+    fn vote_commission(version: u32, commission: u8) -> Result<u8, ProgramError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0; 3731];
+        data[0..4].copy_from_slice(&version.to_le_bytes());
+        data[4 + 32 + 32] = commission;
+        let validator_vote = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &vote_program::ID,
+            false,
+            0,
+        );
+        ValidatorSystem::vote_commission(&validator_vote)
+    }
+
+    #[test]
+    fn test_vote_commission() {
+        // V1_14_11
+        assert_eq!(vote_commission(1, 7).unwrap(), 7);
+        // Current
+        assert_eq!(vote_commission(2, 8).unwrap(), 8);
+        // V0_23_5 has another layout
+        assert!(vote_commission(0, 9).is_err());
+        assert!(vote_commission(3, 10).is_err());
+    }
 }
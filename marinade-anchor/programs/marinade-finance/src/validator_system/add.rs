@@ -3,6 +3,7 @@ use anchor_lang::solana_program::{program::invoke_signed, system_instruction, sy
 
 use crate::{
     checks::{check_address, check_owner_program},
+    validator_system::ValidatorSystem,
     AddValidator, ID,
 };
 //use super::{ValidatorRecord, ValidatorSystem};
@@ -20,6 +21,11 @@ impl<'info> AddValidator<'info> {
             &system_program::ID,
             "duplication_flag",
         )?;
+        ValidatorSystem::check_not_blocked(
+            &self.blocked_flag,
+            self.state.to_account_info().key,
+            self.validator_vote.key,
+        )?;
         check_owner_program(&self.rent_payer, &system_program::ID, "rent_payer")?;
         if !self.rent.is_exempt(self.rent_payer.lamports(), 0) {
             msg!(
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::system_program;
+
+use crate::{
+    checks::{check_address, check_owner_program},
+    create_pda_account,
+    validator_system::ValidatorSystem,
+    BlockValidator, UnblockValidator, ID,
+};
+
+impl<'info> BlockValidator<'info> {
+    pub fn process(&mut self, validator_vote: Pubkey) -> ProgramResult {
+        self.state
+            .validator_system
+            .check_validator_manager_authority(self.manager_authority.key)?;
+        let state_address = *self.state.to_account_info().key;
+        let (blocked_flag, bump_seed) =
+            ValidatorSystem::find_blocked_flag(&state_address, &validator_vote);
+        check_address(self.blocked_flag.key, &blocked_flag, "blocked_flag")?;
+        // owned by the program when already blocked
+        check_owner_program(&self.blocked_flag, &system_program::ID, "blocked_flag")?;
+        check_owner_program(&self.rent_payer, &system_program::ID, "rent_payer")?;
+        if !self.rent.is_exempt(self.rent_payer.lamports(), 0) {
+            msg!(
+                "Rent payer must have at least {} lamports",
+                self.rent.minimum_balance(0)
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+        check_address(
+            self.system_program.key,
+            &system_program::ID,
+            "system_program",
+        )?;
+
+        // Already listed validator stays in the list. Remove it separately if needed
+        msg!("Block validator {}", validator_vote);
+        // the validator may have funded the flag address to prevent the blocking
+        create_pda_account(
+            &self.rent_payer,
+            &self.blocked_flag,
+            self.rent.minimum_balance(0),
+            0,
+            &ID,
+            &self.system_program,
+            &[
+                &state_address.to_bytes()[..32],
+                ValidatorSystem::BLOCKED_FLAG_SEED,
+                &validator_vote.to_bytes()[..32],
+                &[bump_seed],
+            ],
+        )
+    }
+}
+
+impl<'info> UnblockValidator<'info> {
+    pub fn process(&mut self, validator_vote: Pubkey) -> ProgramResult {
+        self.state
+            .validator_system
+            .check_validator_manager_authority(self.manager_authority.key)?;
+        check_address(
+            self.blocked_flag.key,
+            &ValidatorSystem::find_blocked_flag(self.state.to_account_info().key, &validator_vote)
+                .0,
+            "blocked_flag",
+        )?;
+        check_owner_program(&self.blocked_flag, &ID, "blocked_flag")?;
+
+        msg!("Unblock validator {}", validator_vote);
+        let rent_return = self.blocked_flag.lamports();
+        **self.blocked_flag.try_borrow_mut_lamports()? = 0;
+        **self.rent_receiver.try_borrow_mut_lamports()? += rent_return;
+        Ok(())
+    }
+}
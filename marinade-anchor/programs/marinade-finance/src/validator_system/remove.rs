@@ -32,7 +32,7 @@ impl<'info> RemoveValidator<'info> {
             return Err(ProgramError::InvalidArgument);
         }
 
-        self.state.validator_system.remove(
+        self.state.remove_validator(
             &mut self.validator_list.data.as_ref().borrow_mut(),
             index,
             validator,
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::SetValidatorStakeBounds;
+
+impl<'info> SetValidatorStakeBounds<'info> {
+    pub fn process(
+        &mut self,
+        index: u32,
+        validator_vote: Pubkey,
+        min_stake: u64,
+        max_stake: u64,
+    ) -> ProgramResult {
+        self.state
+            .validator_system
+            .check_validator_manager_authority(self.manager_authority.key)?;
+        self.state
+            .validator_system
+            .check_validator_list(&self.validator_list)?;
+
+        let mut validator = self
+            .state
+            .validator_system
+            .get(&self.validator_list.data.borrow(), index)?;
+        if validator.validator_account != validator_vote {
+            msg!(
+                "Wrong validator {}. Validator #{} must be {}",
+                validator_vote,
+                index,
+                validator.validator_account
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // max_stake = 0 means no cap
+        if max_stake > 0 && min_stake > max_stake {
+            msg!(
+                "Validator min stake {} is greater than max stake {}",
+                min_stake,
+                max_stake
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        self.state.on_stake_bounds_change(&mut validator, min_stake);
+        validator.max_stake = max_stake;
+        self.state.validator_system.set(
+            &mut self.validator_list.data.borrow_mut(),
+            index,
+            validator,
+        )?;
+
+        Ok(())
+    }
+}
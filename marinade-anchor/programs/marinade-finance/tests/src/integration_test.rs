@@ -670,6 +670,15 @@ impl IntegrationTest {
     }
 
     pub fn install_validator(&mut self, validator: Arc<Keypair>, vote: Arc<Keypair>) {
+        self.install_validator_with_commission(validator, vote, 0);
+    }
+
+    pub fn install_validator_with_commission(
+        &mut self,
+        validator: Arc<Keypair>,
+        vote: Arc<Keypair>,
+        commission: u8,
+    ) {
         self.builder.begin();
         self.builder
             .create_account(
@@ -687,6 +696,7 @@ impl IntegrationTest {
             &VoteInit {
                 node_pubkey: validator.pubkey(),
                 authorized_voter: validator.pubkey(),
+                commission,
                 ..VoteInit::default()
             },
             self.rent.minimum_balance(VoteState::size_of()),
@@ -1,12 +1,18 @@
 #![allow(unused_imports)]
 use crate::{initialize::InitializeInputWithSeeds, integration_test::IntegrationTest};
+use crate::integration_test::deposit_sol_liquid_unstake::{do_deposit_sol, do_liquid_unstake};
+use crate::integration_test::test_add_remove_liquidity::do_add_liquidity;
 
 use marinade_finance_offchain_sdk::anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 use marinade_finance_offchain_sdk::marinade_finance;
 use marinade_finance_offchain_sdk::spl_associated_token_account::get_associated_token_address;
 use marinade_finance_offchain_sdk::{
     instruction_helpers::InstructionHelpers,
-    marinade_finance::{ticket_account::TicketAccountData, State},
+    marinade_finance::{
+        liq_pool::{FeeCurve, LiqPoolHelpers},
+        ticket_account::TicketAccountData,
+        State,
+    },
 };
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
@@ -320,3 +326,500 @@ async fn test_claim_ticket() -> anyhow::Result<()> {
     do_claim(&mut params, &mut test).await;
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_split_ticket_and_partial_claim() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        201, 37, 118, 9, 64, 173, 250, 31, 142, 77, 5, 199, 88, 213, 46, 160, 19, 231, 102, 57,
+        184, 12, 95, 240, 66, 137, 29, 178, 83, 221, 4, 150,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    let mut params = DelayedUnstakeParams::new(&test.state);
+    do_order_unstake(&mut params, &mut test).await;
+
+    // split 1 SOL into a new ticket
+    const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+    let new_ticket_account = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            new_ticket_account.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "new-ticket-account",
+        )
+        .unwrap();
+    let split_lamports = LAMPORTS_PER_SOL;
+    test.builder.split_ticket(
+        &test.state,
+        params.ticket_account.pubkey(),
+        params.user_sol.clone(), //ticket beneficiary
+        new_ticket_account.pubkey(),
+        split_lamports,
+    );
+    test.execute().await;
+
+    let ticket: TicketAccountData = test.get_account_data(&params.ticket_account.pubkey()).await;
+    let new_ticket: TicketAccountData = test.get_account_data(&new_ticket_account.pubkey()).await;
+    debug_assert_eq!(ticket.lamports_amount, params.expected_lamports - split_lamports);
+    debug_assert_eq!(new_ticket.lamports_amount, split_lamports);
+    debug_assert_eq!(new_ticket.beneficiary, ticket.beneficiary);
+    debug_assert_eq!(new_ticket.created_epoch, ticket.created_epoch);
+    debug_assert_eq!(test.state.circulating_ticket_count, 2);
+    debug_assert_eq!(
+        test.state.circulating_ticket_balance,
+        params.expected_lamports
+    );
+
+    //move to epoch 3, tickets are due
+    let clock = test.move_to_slot(470).await;
+    debug_assert_eq!(clock.epoch, 3);
+
+    // claim part of the new ticket
+    let user_sol_address = params.user_sol.pubkey();
+    let pre_balance = test.get_sol_balance(&user_sol_address).await;
+    let partial_lamports = split_lamports / 2;
+    test.builder.partial_claim(
+        &test.state,
+        new_ticket_account.pubkey(),
+        user_sol_address,
+        partial_lamports,
+    );
+    test.execute().await;
+
+    let post_balance = test.get_sol_balance(&user_sol_address).await;
+    debug_assert_eq!(post_balance, pre_balance + partial_lamports);
+    let new_ticket: TicketAccountData = test.get_account_data(&new_ticket_account.pubkey()).await;
+    debug_assert_eq!(new_ticket.lamports_amount, split_lamports - partial_lamports);
+    debug_assert_eq!(test.state.circulating_ticket_count, 2);
+    debug_assert_eq!(
+        test.state.circulating_ticket_balance,
+        params.expected_lamports - partial_lamports
+    );
+
+    // claiming the rest closes the ticket
+    test.builder.partial_claim(
+        &test.state,
+        new_ticket_account.pubkey(),
+        user_sol_address,
+        split_lamports - partial_lamports,
+    );
+    test.execute().await;
+    debug_assert_eq!(test.state.circulating_ticket_count, 1);
+    debug_assert_eq!(
+        test.state.circulating_ticket_balance,
+        params.expected_lamports - split_lamports
+    );
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_change_beneficiary() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        58, 140, 3, 222, 117, 36, 190, 71, 12, 203, 164, 99, 48, 251, 130, 7, 86, 175, 21, 238,
+        109, 62, 147, 34, 195, 80, 227, 14, 156, 91, 42, 119,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    let mut params = DelayedUnstakeParams::new(&test.state);
+    do_order_unstake(&mut params, &mut test).await;
+
+    let new_beneficiary = Arc::new(Keypair::new());
+    test.builder.change_beneficiary(
+        &test.state,
+        params.ticket_account.pubkey(),
+        params.user_sol.clone(), //current ticket beneficiary
+        new_beneficiary.pubkey(),
+    );
+    test.execute().await;
+
+    let ticket: TicketAccountData = test.get_account_data(&params.ticket_account.pubkey()).await;
+    debug_assert_eq!(ticket.beneficiary, new_beneficiary.pubkey());
+    debug_assert_eq!(ticket.lamports_amount, params.expected_lamports);
+
+    // old beneficiary can not move the ticket anymore
+    test.builder.change_beneficiary(
+        &test.state,
+        params.ticket_account.pubkey(),
+        params.user_sol.clone(),
+        params.user_sol.pubkey(),
+    );
+    const ERR_CODE_TICKET_WRONG_BENEFICIARY: u32 = 0x1105;
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_CODE_TICKET_WRONG_BENEFICIARY) => println!(
+            "(expected tx failure 0x{:x})",
+            ERR_CODE_TICKET_WRONG_BENEFICIARY
+        ),
+        Err(x) => debug_assert!(
+            false,
+            "expected err(ERR_CODE_TICKET_WRONG_BENEFICIARY) got 0x{:x}",
+            x
+        ),
+    }
+
+    // the ticket can not be given to the liq-pool, it would be claimed as a pool-bought ticket
+    test.builder.change_beneficiary(
+        &test.state,
+        params.ticket_account.pubkey(),
+        new_beneficiary.clone(),
+        test.state.liq_pool_sol_leg_address(),
+    );
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(x) => println!("(expected tx failure 0x{:x})", x),
+    }
+
+    //move to epoch 3, ticket is due
+    let clock = test.move_to_slot(470).await;
+    debug_assert_eq!(clock.epoch, 3);
+
+    // new beneficiary claims the ticket
+    test.builder.claim(
+        &test.state,
+        params.ticket_account.pubkey(),
+        new_beneficiary.pubkey(),
+    );
+    test.execute().await;
+
+    let balance = test.get_sol_balance(&new_beneficiary.pubkey()).await;
+    debug_assert_eq!(
+        balance,
+        params.expected_lamports + params.ticket_account_rent_exempt_lamports
+    );
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_instant_claim() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        97, 14, 233, 180, 61, 128, 7, 245, 39, 112, 176, 90, 3, 219, 151, 68, 200, 45, 121, 16,
+        87, 230, 59, 138, 172, 26, 99, 211, 8, 77, 190, 143,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    let mut params = DelayedUnstakeParams::new(&test.state);
+    do_order_unstake(&mut params, &mut test).await;
+
+    // bob adds liquidity
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    do_add_liquidity(&mut bob, 25 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    let sol_leg_address = test.state.liq_pool_sol_leg_address();
+    let sol_leg_balance = test.get_sol_balance(&sol_leg_address).await;
+    let available_lamports = sol_leg_balance - test.state.rent_exempt_for_token_acc;
+    let fee = test
+        .state
+        .liq_pool
+        .linear_fee(available_lamports - params.expected_lamports);
+    let expected_user_lamports = params.expected_lamports - fee.apply(params.expected_lamports);
+
+    // ticket is not due, but the pool buys it
+    let user_sol_address = params.user_sol.pubkey();
+    let pre_balance = test.get_sol_balance(&user_sol_address).await;
+    test.builder.instant_claim(
+        &test.state,
+        params.ticket_account.pubkey(),
+        params.user_sol.clone(), //ticket beneficiary
+        user_sol_address,
+    );
+    test.execute().await;
+
+    let post_balance = test.get_sol_balance(&user_sol_address).await;
+    debug_assert_eq!(post_balance, pre_balance + expected_user_lamports);
+    let ticket: TicketAccountData = test.get_account_data(&params.ticket_account.pubkey()).await;
+    debug_assert_eq!(ticket.beneficiary, sol_leg_address);
+    debug_assert_eq!(
+        test.state.liq_pool_tickets_receivable,
+        params.expected_lamports
+    );
+
+    //move to epoch 3, ticket is due
+    let clock = test.move_to_slot(470).await;
+    debug_assert_eq!(clock.epoch, 3);
+
+    // anyone can claim the ticket into the pool
+    test.builder.claim(
+        &test.state,
+        params.ticket_account.pubkey(),
+        sol_leg_address,
+    );
+    test.execute().await;
+
+    debug_assert_eq!(test.state.liq_pool_tickets_receivable, 0);
+    let sol_leg_balance_after = test.get_sol_balance(&sol_leg_address).await;
+    debug_assert_eq!(
+        sol_leg_balance_after,
+        sol_leg_balance - expected_user_lamports
+            + params.expected_lamports
+            + params.ticket_account_rent_exempt_lamports
+    );
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_remove_liquidity_with_pool_ticket() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        163, 28, 74, 201, 9, 118, 240, 55, 187, 36, 92, 147, 210, 3, 66, 129, 248, 17, 105, 184,
+        41, 222, 60, 133, 11, 199, 86, 154, 30, 237, 112, 71,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    let mut params = DelayedUnstakeParams::new(&test.state);
+    do_order_unstake(&mut params, &mut test).await;
+
+    // bob is the only LP, the pool buys the ticket
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    let lp_tokens = 25 * LAMPORTS_PER_SOL;
+    do_add_liquidity(&mut bob, lp_tokens, &mut test)
+        .await
+        .unwrap();
+    test.builder.instant_claim(
+        &test.state,
+        params.ticket_account.pubkey(),
+        params.user_sol.clone(), //ticket beneficiary
+        params.user_sol.pubkey(),
+    );
+    test.execute().await;
+    debug_assert_eq!(
+        test.state.liq_pool_tickets_receivable,
+        params.expected_lamports
+    );
+
+    let bob_lp = bob.get_or_create_lp_token_account(&mut test).await.pubkey;
+    let bob_msol = bob.get_or_create_msol_account(&mut test).await.pubkey;
+    let sol_leg_address = test.state.liq_pool_sol_leg_address();
+    let sol_leg_lamports =
+        test.get_sol_balance(&sol_leg_address).await - test.state.rent_exempt_for_token_acc;
+
+    // the SOL leg can not pay the receivable share of 24 of 25 LP tokens
+    let tokens = 24 * LAMPORTS_PER_SOL;
+    test.builder.remove_liquidity(
+        &test.state,
+        bob_lp,
+        bob.keypair.clone(),
+        bob.keypair.pubkey(),
+        bob_msol,
+        tokens,
+    );
+    const ERR_CODE_INSUFFICIENT_LIQUIDITY: u32 = 0x1199;
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_CODE_INSUFFICIENT_LIQUIDITY) => println!(
+            "(expected tx failure 0x{:x})",
+            ERR_CODE_INSUFFICIENT_LIQUIDITY
+        ),
+        Err(x) => debug_assert!(
+            false,
+            "expected err(ERR_CODE_INSUFFICIENT_LIQUIDITY) got 0x{:x}",
+            x
+        ),
+    }
+
+    // with a pool ticket bob gets his share of the SOL leg and a split of the ticket
+    const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+    let bob_ticket = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            bob_ticket.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "bob ticket-account",
+        )
+        .unwrap();
+    let expected_sol = sol_leg_lamports * tokens / lp_tokens;
+    let expected_ticket_lamports =
+        (sol_leg_lamports + params.expected_lamports) * tokens / lp_tokens - expected_sol;
+    let bob_balance = bob.sol_balance(&mut test).await;
+    test.builder.remove_liquidity_with_ticket(
+        &test.state,
+        bob_lp,
+        bob.keypair.clone(),
+        bob.keypair.pubkey(),
+        bob_msol,
+        tokens,
+        params.ticket_account.pubkey(),
+        bob_ticket.pubkey(),
+    );
+    test.execute().await;
+
+    debug_assert_eq!(bob.sol_balance(&mut test).await, bob_balance + expected_sol);
+    let ticket: TicketAccountData = test.get_account_data(&bob_ticket.pubkey()).await;
+    debug_assert_eq!(ticket.beneficiary, bob.keypair.pubkey());
+    debug_assert_eq!(ticket.lamports_amount, expected_ticket_lamports);
+    let pool_ticket: TicketAccountData =
+        test.get_account_data(&params.ticket_account.pubkey()).await;
+    debug_assert_eq!(pool_ticket.beneficiary, sol_leg_address);
+    debug_assert_eq!(
+        pool_ticket.lamports_amount,
+        params.expected_lamports - expected_ticket_lamports
+    );
+    debug_assert_eq!(ticket.created_epoch, pool_ticket.created_epoch);
+    debug_assert_eq!(
+        test.state.liq_pool_tickets_receivable,
+        pool_ticket.lamports_amount
+    );
+
+    // the last LP token takes the rest of the pool ticket
+    let unused_ticket = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            unused_ticket.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "unused ticket-account",
+        )
+        .unwrap();
+    test.builder.remove_liquidity_with_ticket(
+        &test.state,
+        bob_lp,
+        bob.keypair.clone(),
+        bob.keypair.pubkey(),
+        bob_msol,
+        lp_tokens - tokens,
+        params.ticket_account.pubkey(),
+        unused_ticket.pubkey(),
+    );
+    test.execute().await;
+
+    let pool_ticket: TicketAccountData =
+        test.get_account_data(&params.ticket_account.pubkey()).await;
+    debug_assert_eq!(pool_ticket.beneficiary, bob.keypair.pubkey());
+    debug_assert_eq!(test.state.liq_pool_tickets_receivable, 0);
+    debug_assert_eq!(test.get_token_balance(&bob_lp).await, 0);
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_rebalance_liq_pool() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        45, 182, 11, 203, 96, 157, 72, 230, 18, 139, 61, 244, 107, 33, 190, 85, 126, 2, 215, 168,
+        53, 99, 241, 14, 176, 80, 131, 27, 209, 68, 155, 36,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    // bob fills the pool up to the liquidity target, alice liquid-unstakes so the pool gets mSOL
+    let liquidity_target = 50 * LAMPORTS_PER_SOL;
+    test.builder
+        .set_lp_params(
+            &test.state,
+            test.admin_authority.clone(),
+            test.state.liq_pool.lp_min_fee,
+            test.state.liq_pool.lp_max_fee,
+            liquidity_target,
+            FeeCurve::linear(),
+        )
+        .unwrap();
+    test.execute().await;
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    do_add_liquidity(&mut bob, liquidity_target, &mut test)
+        .await
+        .unwrap();
+    let mut alice = test
+        .create_test_user("alice", 100 * LAMPORTS_PER_SOL)
+        .await;
+    do_deposit_sol(&mut alice, 10 * LAMPORTS_PER_SOL, &mut test).await;
+    do_liquid_unstake(&mut alice, 5 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    // the mSOL leg is worth more than the SOL paid out (fee), so only the shortfall is rebalanced
+    let msol_leg_address = test.state.liq_pool.msol_leg;
+    let msol_leg_amount = test.get_token_balance(&msol_leg_address).await;
+    let sol_leg_address = test.state.liq_pool_sol_leg_address();
+    let sol_leg_lamports =
+        test.get_sol_balance(&sol_leg_address).await - test.state.rent_exempt_for_token_acc;
+    let shortfall_msol = test
+        .state
+        .calc_msol_from_lamports(liquidity_target - sol_leg_lamports)
+        .unwrap();
+    debug_assert!(shortfall_msol < msol_leg_amount);
+    let expected_lamports = test
+        .state
+        .calc_lamports_from_msol_amount(shortfall_msol)
+        .unwrap();
+
+    // anyone can crank the rebalance
+    const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+    let ticket_account = Arc::new(Keypair::new());
+    let ticket_account_rent_exempt_lamports = test.rent.minimum_balance(TICKET_ACCOUNT_SPACE);
+    test.builder
+        .create_account(
+            ticket_account.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "liq-pool ticket-account",
+        )
+        .unwrap();
+    test.builder
+        .rebalance_liq_pool(&test.state, ticket_account.pubkey(), msol_leg_amount);
+    test.execute().await;
+
+    debug_assert_eq!(
+        test.get_token_balance(&msol_leg_address).await,
+        msol_leg_amount - shortfall_msol
+    );
+    let ticket: TicketAccountData = test.get_account_data(&ticket_account.pubkey()).await;
+    debug_assert_eq!(ticket.beneficiary, sol_leg_address);
+    debug_assert_eq!(ticket.lamports_amount, expected_lamports);
+    debug_assert_eq!(test.state.liq_pool_tickets_receivable, expected_lamports);
+
+    // the SOL leg with the receivable ticket is at the target now
+    let other_ticket_account = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            other_ticket_account.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "liq-pool ticket-account",
+        )
+        .unwrap();
+    test.builder.rebalance_liq_pool(
+        &test.state,
+        other_ticket_account.pubkey(),
+        msol_leg_amount - shortfall_msol,
+    );
+    const ERR_CODE_LIQUIDITY_TARGET_REACHED: u32 = 0x110B;
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_CODE_LIQUIDITY_TARGET_REACHED) => println!(
+            "(expected tx failure 0x{:x})",
+            ERR_CODE_LIQUIDITY_TARGET_REACHED
+        ),
+        Err(x) => debug_assert!(
+            false,
+            "expected err(ERR_CODE_LIQUIDITY_TARGET_REACHED) got 0x{:x}",
+            x
+        ),
+    }
+
+    //move to epoch 3, ticket is due
+    let clock = test.move_to_slot(470).await;
+    debug_assert_eq!(clock.epoch, 3);
+
+    let sol_leg_balance = test.get_sol_balance(&sol_leg_address).await;
+    test.builder
+        .claim(&test.state, ticket_account.pubkey(), sol_leg_address);
+    test.execute().await;
+
+    debug_assert_eq!(test.state.liq_pool_tickets_receivable, 0);
+    debug_assert_eq!(
+        test.get_sol_balance(&sol_leg_address).await,
+        sol_leg_balance + expected_lamports + ticket_account_rent_exempt_lamports
+    );
+    Ok(())
+}
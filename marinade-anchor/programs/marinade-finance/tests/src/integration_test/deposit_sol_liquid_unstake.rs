@@ -8,7 +8,9 @@ use crate::{initialize::InitializeInputWithSeeds, integration_test::*};
 use marinade_finance_offchain_sdk::spl_associated_token_account::get_associated_token_address;
 use marinade_finance_offchain_sdk::{
     instruction_helpers::InstructionHelpers,
-    marinade_finance::{calc::proportional, liq_pool::LiqPoolHelpers, State},
+    marinade_finance::{
+        self, calc::proportional, liq_pool::LiqPoolHelpers, referral::ReferralStateData, Fee, State,
+    },
 };
 use rand::{distributions::Uniform, prelude::Distribution, CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
@@ -23,6 +25,7 @@ use solana_sdk::{
 use test_env_log::test;
 
 use crate::integration_test::test_add_remove_liquidity::*;
+use crate::integration_test::test_add_remove_validators::do_add_validator;
 
 pub struct DepositSolParams {
     pub user_sol: Arc<Keypair>,
@@ -248,3 +251,365 @@ async fn test_liquid_unstake() -> anyhow::Result<()> {
         .unwrap();
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_deposit_directed() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        73, 18, 240, 155, 6, 92, 201, 37, 128, 64, 219, 3, 177, 110, 45, 250, 81, 162, 29, 196,
+        14, 233, 57, 120, 99, 186, 8, 143, 70, 211, 24, 167,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    let validator_vote_keypair = Arc::new(Keypair::new());
+    do_add_validator(
+        Arc::new(Keypair::new()),
+        validator_vote_keypair.clone(),
+        100_000,
+        &mut test,
+    )
+    .await;
+
+    let user = test
+        .create_test_user("test_dep_directed_user", 200 * LAMPORTS_PER_SOL)
+        .await;
+    let user_msol_account = user.get_or_create_msol_account(&mut test).await;
+    let lamports = random_amount(1, 100, &mut rng);
+    test.builder.deposit_directed(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        lamports,
+        0,
+        0,
+        validator_vote_keypair.pubkey(),
+    );
+    test.execute().await;
+
+    let validator_list_address = *test.state.validator_system.validator_list_address();
+    let validator_list = test
+        .context
+        .banks_client
+        .get_account(validator_list_address)
+        .await?
+        .unwrap()
+        .data;
+    let validator = test.state.validator_system.get(&validator_list, 0)?;
+    assert_eq!(validator.directed_stake_lamports, lamports);
+    assert_eq!(test.state.total_directed_stake, lamports);
+
+    // validator vote must match the validator index
+    test.builder.deposit_directed(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        lamports,
+        0,
+        0,
+        Pubkey::new_unique(),
+    );
+    assert!(test.try_execute().await.is_err());
+
+    // directed deposits have the slippage guard too
+    test.builder.deposit_directed(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        lamports,
+        std::u64::MAX,
+        0,
+        validator_vote_keypair.pubkey(),
+    );
+    const ERR_SLIPPAGE_EXCEEDED: u32 = 0x1109;
+    assert_eq!(test.try_execute().await, Err(ERR_SLIPPAGE_EXCEEDED));
+
+    // zero score validators get no directed stake
+    test.builder.set_validator_score(
+        &test.state,
+        test.validator_manager_authority.clone(),
+        0,
+        validator_vote_keypair.pubkey(),
+        0,
+    )?;
+    test.execute().await;
+    test.builder.deposit_directed(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        lamports,
+        0,
+        0,
+        validator_vote_keypair.pubkey(),
+    );
+    const ERR_INVALID_VALIDATOR: u32 = 0xBAD1;
+    assert_eq!(test.try_execute().await, Err(ERR_INVALID_VALIDATOR));
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_referral() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        201, 44, 130, 7, 88, 173, 15, 242, 61, 119, 34, 190, 226, 5, 147, 98, 52, 209, 76, 131, 11,
+        164, 237, 90, 27, 183, 102, 69, 154, 3, 218, 40,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let partner = test.create_test_user("partner", LAMPORTS_PER_SOL).await;
+    let partner_msol_account = partner.get_or_create_msol_account(&mut test).await;
+    let referral_state = Arc::new(Keypair::new());
+    test.builder.create_account(
+        referral_state.clone(),
+        ReferralStateData::SPACE,
+        &marinade_finance::ID,
+        &test.rent,
+        "referral",
+    )?;
+    test.builder.create_referral(
+        &test.state,
+        test.admin_authority.clone(),
+        referral_state.pubkey(),
+        partner.keypair.pubkey(),
+        partner_msol_account.pubkey,
+        "partner".to_string(),
+        Fee::from_basis_points(5_000),
+    )?;
+    test.execute().await;
+
+    let alice = test
+        .create_test_user("alice", 1000 * LAMPORTS_PER_SOL)
+        .await;
+    let alice_msol_account = alice.get_or_create_msol_account(&mut test).await;
+    let deposit_lamports = 26 * LAMPORTS_PER_SOL;
+    test.builder.deposit_with_referral(
+        &test.state,
+        alice.keypair.clone(),
+        alice_msol_account.pubkey,
+        deposit_lamports,
+        0,
+        referral_state.pubkey(),
+    );
+    test.execute().await;
+    let referral: ReferralStateData = test.get_account_data(&referral_state.pubkey()).await;
+    assert_eq!(referral.code(), "partner");
+    assert_eq!(referral.deposit_sol_amount, deposit_lamports);
+    assert_eq!(referral.deposit_sol_operations, 1);
+
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    do_add_liquidity(&mut bob, 25 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    // the referral msol account must match
+    let unstake_msol = 10 * LAMPORTS_PER_SOL;
+    test.builder.liquid_unstake_with_referral(
+        &test.state,
+        alice_msol_account.pubkey,
+        alice.keypair.clone(),
+        alice.keypair.pubkey(),
+        unstake_msol,
+        0,
+        referral_state.pubkey(),
+        alice_msol_account.pubkey,
+    );
+    assert!(test.try_execute().await.is_err());
+
+    test.builder.liquid_unstake_with_referral(
+        &test.state,
+        alice_msol_account.pubkey,
+        alice.keypair.clone(),
+        alice.keypair.pubkey(),
+        unstake_msol,
+        0,
+        referral_state.pubkey(),
+        partner_msol_account.pubkey,
+    );
+    test.execute().await;
+    let referral: ReferralStateData = test.get_account_data(&referral_state.pubkey()).await;
+    assert_eq!(referral.liq_unstake_msol_amount, unstake_msol);
+    assert_eq!(referral.liq_unstake_operations, 1);
+    assert!(referral.msol_fees_paid > 0);
+    assert_eq!(
+        test.get_token_balance(&partner_msol_account.pubkey).await,
+        referral.msol_fees_paid
+    );
+
+    // paused referral is not accounted
+    test.builder.update_referral(
+        &test.state,
+        test.admin_authority.clone(),
+        referral_state.pubkey(),
+        Some(true),
+        None,
+    )?;
+    test.builder.deposit_with_referral(
+        &test.state,
+        alice.keypair.clone(),
+        alice_msol_account.pubkey,
+        deposit_lamports,
+        0,
+        referral_state.pubkey(),
+    );
+    test.execute().await;
+    let referral: ReferralStateData = test.get_account_data(&referral_state.pubkey()).await;
+    assert_eq!(referral.pause, 1);
+    assert_eq!(referral.deposit_sol_operations, 1);
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_slippage() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        58, 141, 9, 220, 117, 36, 199, 84, 3, 162, 75, 248, 131, 20, 97, 186, 44, 213, 158, 67, 1,
+        239, 120, 93, 175, 52, 206, 11, 144, 81, 27, 230,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let alice = test
+        .create_test_user("alice", 1000 * LAMPORTS_PER_SOL)
+        .await;
+    let alice_msol_account = alice.get_or_create_msol_account(&mut test).await;
+    const ERR_SLIPPAGE_EXCEEDED: u32 = 0x1109;
+
+    // the liq-pool mSOL leg is empty, so all the deposit is minted
+    let deposit_lamports = 26 * LAMPORTS_PER_SOL;
+    let expected_msol = test
+        .state
+        .calc_msol_from_lamports(deposit_lamports)
+        .unwrap();
+    test.builder.deposit_with_min_out(
+        &test.state,
+        alice.keypair.clone(),
+        alice_msol_account.pubkey,
+        deposit_lamports,
+        expected_msol + 1,
+    );
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_SLIPPAGE_EXCEEDED) => {
+            println!("(expected tx failure 0x{:x})", ERR_SLIPPAGE_EXCEEDED)
+        }
+        Err(x) => debug_assert!(false, "expected ERR_SLIPPAGE_EXCEEDED got 0x{:x}", x),
+    }
+    test.builder.deposit_with_min_out(
+        &test.state,
+        alice.keypair.clone(),
+        alice_msol_account.pubkey,
+        deposit_lamports,
+        expected_msol,
+    );
+    test.execute().await;
+    assert_eq!(
+        test.get_token_balance(&alice_msol_account.pubkey).await,
+        expected_msol
+    );
+
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    do_add_liquidity(&mut bob, 25 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    // the liq-pool fee makes the value without a fee unreachable
+    let unstake_msol = 10 * LAMPORTS_PER_SOL;
+    let value_without_fee = test
+        .state
+        .calc_lamports_from_msol_amount(unstake_msol)
+        .unwrap();
+    test.builder.liquid_unstake_with_min_out(
+        &test.state,
+        alice_msol_account.pubkey,
+        alice.keypair.clone(),
+        alice.keypair.pubkey(),
+        unstake_msol,
+        value_without_fee,
+    );
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_SLIPPAGE_EXCEEDED) => {
+            println!("(expected tx failure 0x{:x})", ERR_SLIPPAGE_EXCEEDED)
+        }
+        Err(x) => debug_assert!(false, "expected ERR_SLIPPAGE_EXCEEDED got 0x{:x}", x),
+    }
+
+    let alice_sol_balance_before = alice.sol_balance(&mut test).await;
+    // the fee can not be more than lp_max_fee
+    let min_lamports_out =
+        value_without_fee - test.state.liq_pool.lp_max_fee.apply(value_without_fee) - 1;
+    test.builder.liquid_unstake_with_min_out(
+        &test.state,
+        alice_msol_account.pubkey,
+        alice.keypair.clone(),
+        alice.keypair.pubkey(),
+        unstake_msol,
+        min_lamports_out,
+    );
+    test.execute().await;
+    assert!(alice.sol_balance(&mut test).await >= alice_sol_balance_before + min_lamports_out);
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_liquid_unstake_epoch_outflow_cap() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        207, 64, 151, 22, 93, 180, 37, 246, 118, 9, 171, 56, 229, 140, 83, 14, 195, 102, 61, 238,
+        29, 154, 77, 210, 5, 168, 123, 42, 251, 96, 187, 70,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let mut alice = test
+        .create_test_user("alice", 1000 * LAMPORTS_PER_SOL)
+        .await;
+    do_deposit_sol(&mut alice, 26 * LAMPORTS_PER_SOL, &mut test).await;
+
+    let mut bob = test
+        .create_test_user("bob", 50_000 * LAMPORTS_PER_SOL)
+        .await;
+    do_add_liquidity(&mut bob, 25 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    let outflow_cap = 5 * LAMPORTS_PER_SOL;
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            marinade_finance::ConfigMarinadeParams {
+                liquidity_epoch_outflow_cap: Some(outflow_cap),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.liq_pool_epoch_outflow_cap, outflow_cap);
+
+    do_liquid_unstake(&mut alice, 4 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+
+    // the SOL leg can not lose more than the cap in one epoch
+    const ERR_EPOCH_OUTFLOW_CAP_REACHED: u32 = 0x110A;
+    match do_liquid_unstake(&mut alice, 2 * LAMPORTS_PER_SOL, &mut test).await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_EPOCH_OUTFLOW_CAP_REACHED) => println!(
+            "(expected tx failure 0x{:x})",
+            ERR_EPOCH_OUTFLOW_CAP_REACHED
+        ),
+        Err(x) => debug_assert!(
+            false,
+            "expected err(ERR_EPOCH_OUTFLOW_CAP_REACHED) got 0x{:x}",
+            x
+        ),
+    }
+
+    // the counter restarts in the next epoch
+    test.move_to_next_epoch().await;
+    do_liquid_unstake(&mut alice, 2 * LAMPORTS_PER_SOL, &mut test)
+        .await
+        .unwrap();
+    Ok(())
+}
@@ -4,11 +4,15 @@
 //
 use std::sync::Arc;
 
-use crate::{initialize::InitializeInputWithSeeds, integration_test::*};
+use crate::{
+    initialize::InitializeInputWithSeeds,
+    integration_test::{deposit_sol_liquid_unstake::do_deposit_sol, *},
+};
 
 use marinade_finance_offchain_sdk::spl_associated_token_account::get_associated_token_address;
 use marinade_finance_offchain_sdk::{
-    instruction_helpers::InstructionHelpers, marinade_finance::State,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{liq_pool::LiqPoolHelpers, State},
 };
 use rand::{distributions::Uniform, prelude::Distribution, CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
@@ -172,6 +176,78 @@ async fn test_add_liquidity() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test(tokio::test)]
+async fn test_add_liquidity_msol() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        94, 17, 203, 66, 140, 231, 8, 175, 52, 119, 186, 29, 243, 101, 74, 160, 38, 215, 130, 5,
+        199, 83, 112, 247, 61, 176, 20, 154, 91, 226, 47, 138,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let mut alice = test
+        .create_test_user("alice", 1001 * LAMPORTS_PER_SOL)
+        .await;
+    do_deposit_sol(&mut alice, 100 * LAMPORTS_PER_SOL, &mut test).await;
+    do_add_liquidity(&mut alice, random_amount(1, 100, &mut rng), &mut test)
+        .await
+        .unwrap();
+
+    let user_msol_account = alice.get_or_create_msol_account(&mut test).await;
+    let user_lp_token_account = alice.get_or_create_lp_token_account(&mut test).await;
+    let user_msol_balance_before = test.get_token_balance(&user_msol_account.pubkey).await;
+    let user_lp_token_balance_before = test.get_token_balance(&user_lp_token_account.pubkey).await;
+    let liq_pool_msol_leg = test.state.liq_pool.msol_leg;
+    let liq_pool_msol_before = test.get_token_balance(&liq_pool_msol_leg).await;
+    let sol_leg_lamports = test
+        .get_sol_balance(&test.state.liq_pool_sol_leg_address())
+        .await
+        - test.state.rent_exempt_for_token_acc;
+
+    let msol_amount = random_amount(1, 100, &mut rng);
+    test.builder.add_liquidity_msol(
+        &test.state,
+        user_msol_account.pubkey,
+        alice.keypair.clone(),
+        user_lp_token_account.pubkey,
+        msol_amount,
+    );
+    test.execute().await;
+
+    // mSOL goes to the mSOL leg
+    assert_eq!(
+        test.get_token_balance(&user_msol_account.pubkey).await,
+        user_msol_balance_before - msol_amount
+    );
+    assert_eq!(
+        test.get_token_balance(&liq_pool_msol_leg).await,
+        liq_pool_msol_before + msol_amount
+    );
+    // the pool only holds SOL so one LP token is worth one lamport.
+    // mSOL is valued after the liquid-unstake fee
+    let msol_fee = test
+        .state
+        .liq_pool
+        .liquid_unstake_fee(
+            &test.state.liq_pool_fee_curve,
+            test.state
+                .calc_lamports_from_msol_amount(msol_amount)
+                .unwrap(),
+            sol_leg_lamports,
+        )
+        .apply(msol_amount);
+    assert!(msol_fee > 0);
+    assert_eq!(
+        test.get_token_balance(&user_lp_token_account.pubkey).await,
+        user_lp_token_balance_before
+            + test
+                .state
+                .calc_lamports_from_msol_amount(msol_amount - msol_fee)
+                .unwrap()
+    );
+    Ok(())
+}
+
 #[test(tokio::test)]
 async fn test_remove_all_liquidity() -> anyhow::Result<()> {
     let mut rng = ChaChaRng::from_seed([
@@ -7,7 +7,8 @@ use std::sync::Arc;
 use crate::{initialize::InitializeInputWithSeeds, integration_test::*};
 use marinade_finance_offchain_sdk::spl_associated_token_account::get_associated_token_address;
 use marinade_finance_offchain_sdk::{
-    instruction_helpers::InstructionHelpers, marinade_finance::State,
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{self, list::List, validator_system::ValidatorRecord, State},
 };
 use rand::{distributions::Uniform, prelude::Distribution, CryptoRng, RngCore, SeedableRng};
 use rand_chacha::ChaChaRng;
@@ -75,3 +76,232 @@ async fn test_add_validator() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_set_validator_stake_bounds() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        31, 204, 77, 150, 12, 239, 95, 68, 180, 23, 141, 6, 218, 59, 113, 246, 88, 37, 170, 2, 199,
+        124, 51, 233, 16, 105, 162, 74, 247, 40, 131, 9,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let validator_vote_keypair = Arc::new(Keypair::new());
+    let score: u32 = 100_000;
+    do_add_validator(
+        Arc::new(Keypair::new()),
+        validator_vote_keypair.clone(),
+        score,
+        &mut test,
+    )
+    .await;
+
+    let min_stake = 10 * LAMPORTS_PER_SOL;
+    let max_stake = 1_000 * LAMPORTS_PER_SOL;
+    test.builder
+        .set_validator_stake_bounds(
+            &test.state,
+            test.validator_manager_authority.clone(),
+            0,
+            validator_vote_keypair.pubkey(),
+            min_stake,
+            max_stake,
+        )
+        .unwrap();
+    test.execute().await;
+
+    let validator_list_address = *test.state.validator_system.validator_list_address();
+    let validator_list = test
+        .context
+        .banks_client
+        .get_account(validator_list_address)
+        .await?
+        .unwrap()
+        .data;
+    let validator = test.state.validator_system.get(&validator_list, 0)?;
+    assert_eq!(validator.min_stake, min_stake);
+    assert_eq!(validator.max_stake, max_stake);
+    // score is not touched
+    assert_eq!(test.state.validator_system.total_validator_score, score);
+    assert_eq!(test.state.total_min_stake, min_stake);
+    // target is bounded
+    assert_eq!(
+        test.state
+            .validator_stake_target(&validator, min_stake / 2)?,
+        min_stake / 2,
+        "floors over the total stake target are not honored"
+    );
+    assert_eq!(
+        test.state.validator_stake_target(&validator, min_stake)?,
+        min_stake
+    );
+    assert_eq!(
+        test.state
+            .validator_stake_target(&validator, 1_000_000 * LAMPORTS_PER_SOL)?,
+        max_stake
+    );
+
+    // min_stake must not be greater than max_stake
+    test.builder
+        .set_validator_stake_bounds(
+            &test.state,
+            test.validator_manager_authority.clone(),
+            0,
+            validator_vote_keypair.pubkey(),
+            max_stake + 1,
+            max_stake,
+        )
+        .unwrap();
+    assert!(test.try_execute().await.is_err());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_grow_validator_list() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        17, 143, 222, 60, 5, 198, 121, 34, 250, 87, 169, 12, 76, 231, 100, 45, 189, 3, 214, 158,
+        66, 27, 140, 93, 208, 51, 117, 246, 39, 182, 9, 130,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+    test.add_test_validators().await;
+
+    let old_list_address = *test.state.validator_system.validator_list_address();
+    let old_list = test
+        .context
+        .banks_client
+        .get_account(old_list_address)
+        .await?
+        .unwrap()
+        .data;
+    let old_capacity = test
+        .state
+        .validator_system
+        .validator_list_capacity(old_list.len())?;
+    let new_capacity = old_capacity + 10;
+
+    let new_list = Arc::new(Keypair::new());
+    test.builder.create_account(
+        new_list.clone(),
+        List::bytes_for(
+            test.state.validator_system.validator_record_size(),
+            new_capacity,
+        ) as usize,
+        &marinade_finance::ID,
+        &test.rent,
+        "new_validator_list",
+    )?;
+    test.builder.grow_list(
+        &test.state,
+        test.admin_authority.clone(),
+        old_list_address,
+        new_list.pubkey(),
+        test.fee_payer(),
+        3,
+    )?;
+    test.execute().await;
+    assert!(test
+        .state
+        .validator_system
+        .validator_list
+        .is_changing_account());
+
+    // the list is locked while moving
+    test.add_validator(Arc::new(Keypair::new()), Arc::new(Keypair::new()), 100_000);
+    assert!(test.try_execute().await.is_err());
+
+    test.builder.grow_list(
+        &test.state,
+        test.admin_authority.clone(),
+        old_list_address,
+        new_list.pubkey(),
+        test.fee_payer(),
+        3,
+    )?;
+    test.execute().await;
+    assert!(!test
+        .state
+        .validator_system
+        .validator_list
+        .is_changing_account());
+    assert_eq!(
+        *test.state.validator_system.validator_list_address(),
+        new_list.pubkey()
+    );
+    assert!(test
+        .context
+        .banks_client
+        .get_account(old_list_address)
+        .await?
+        .is_none());
+
+    let new_list_data = test
+        .context
+        .banks_client
+        .get_account(new_list.pubkey())
+        .await?
+        .unwrap()
+        .data;
+    assert_eq!(
+        test.state
+            .validator_system
+            .validator_list_capacity(new_list_data.len())?,
+        new_capacity
+    );
+    for index in 0..test.state.validator_system.validator_count() {
+        assert_eq!(
+            test.state.validator_system.get(&new_list_data, index)?,
+            test.state.validator_system.get(&old_list, index)?
+        );
+    }
+
+    let validator_count = test.state.validator_system.validator_count();
+    test.add_validator(Arc::new(Keypair::new()), Arc::new(Keypair::new()), 100_000);
+    test.execute().await;
+    assert_eq!(
+        test.state.validator_system.validator_count(),
+        validator_count + 1
+    );
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_reclaim_rent_of_listed_validator() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        58, 190, 7, 223, 141, 36, 99, 172, 14, 205, 81, 248, 120, 63, 2, 159, 94, 177, 46, 211, 30,
+        135, 68, 252, 19, 104, 187, 76, 241, 9, 160, 53,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let validator_vote_keypair = Arc::new(Keypair::new());
+    do_add_validator(
+        Arc::new(Keypair::new()),
+        validator_vote_keypair.clone(),
+        100_000,
+        &mut test,
+    )
+    .await;
+
+    let duplication_flag =
+        ValidatorRecord::find_duplication_flag(&test.state.key, &validator_vote_keypair.pubkey()).0;
+    let flag_lamports = test.get_sol_balance(&duplication_flag).await;
+    assert!(flag_lamports > 0);
+
+    // the flag of a listed validator is in use
+    test.builder.reclaim_rent(
+        &test.state,
+        duplication_flag,
+        Some(validator_vote_keypair.pubkey()),
+    );
+    assert!(test.try_execute().await.is_err());
+
+    // and it is not a ticket
+    test.builder
+        .reclaim_rent(&test.state, duplication_flag, None);
+    assert!(test.try_execute().await.is_err());
+
+    assert_eq!(test.get_sol_balance(&duplication_flag).await, flag_lamports);
+    Ok(())
+}
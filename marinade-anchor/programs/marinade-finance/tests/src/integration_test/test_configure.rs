@@ -3,7 +3,15 @@ use crate::{
 };
 use marinade_finance_offchain_sdk::{
     instruction_helpers::InstructionHelpers,
-    marinade_finance::{located::Located, ConfigMarinadeParams, Fee, MAX_REWARD_FEE},
+    marinade_finance::{
+        self,
+        liq_pool::{FeeCurve, FeeCurvePoint},
+        located::Located,
+        queued_change::{ParamsChange, QueuedChangeData},
+        ticket_account::TicketAccountData,
+        ChangeAuthorityData, ConfigMarinadeParams, Fee, ProposeAuthorityData, State,
+        MAX_REWARD_FEE,
+    },
 };
 
 use marinade_finance_offchain_sdk::anchor_lang::solana_program::native_token::{
@@ -11,9 +19,13 @@ use marinade_finance_offchain_sdk::anchor_lang::solana_program::native_token::{
 };
 use rand::{distributions::Uniform, prelude::Distribution, SeedableRng};
 use rand_chacha::ChaChaRng;
-use solana_sdk::signature::Signer;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
 
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 use test_env_log::test;
 
 #[test(tokio::test)]
@@ -52,6 +64,7 @@ async fn test_set_lp_params_and_config() -> anyhow::Result<()> {
             min_fee_new,
             max_fee_new,
             liquidity_target_new,
+            FeeCurve::linear(),
         )
         .unwrap();
     test.execute().await;
@@ -85,6 +98,11 @@ async fn test_set_lp_params_and_config() -> anyhow::Result<()> {
                 staking_sol_cap: Some(staking_sol_cap),
                 liquidity_sol_cap: Some(liquidity_sol_cap),
                 auto_add_validator_enabled: None,
+                paused_operations: None,
+                params_change_delay_epochs: None,
+                withdraw_stake_account_fee: None,
+                max_commission: None,
+                liquidity_epoch_outflow_cap: None,
             },
         )
         .unwrap();
@@ -119,6 +137,11 @@ async fn test_set_lp_params_and_config() -> anyhow::Result<()> {
                 staking_sol_cap: None,
                 liquidity_sol_cap: None,
                 auto_add_validator_enabled: None,
+                paused_operations: None,
+                params_change_delay_epochs: None,
+                withdraw_stake_account_fee: None,
+                max_commission: None,
+                liquidity_epoch_outflow_cap: None,
             },
         )
         .unwrap();
@@ -148,6 +171,11 @@ async fn test_set_lp_params_and_config() -> anyhow::Result<()> {
                 staking_sol_cap: None,
                 liquidity_sol_cap: None,
                 auto_add_validator_enabled: None,
+                paused_operations: None,
+                params_change_delay_epochs: None,
+                withdraw_stake_account_fee: None,
+                max_commission: None,
+                liquidity_epoch_outflow_cap: None,
             },
         )
         .unwrap();
@@ -183,3 +211,503 @@ async fn test_config_validator_system() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_set_lp_fee_curve() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        44, 190, 7, 129, 205, 63, 118, 240, 31, 86, 152, 17, 223, 99, 170, 4, 61, 138, 245, 22,
+        109, 77, 196, 53, 12, 181, 90, 234, 147, 68, 5, 201,
+    ]);
+
+    let mut test = IntegrationTest::start(&InitializeInputWithSeeds::random(&mut rng)).await?;
+    let min_fee = Fee::from_basis_points(30);
+    let max_fee = Fee::from_basis_points(300);
+    let liquidity_target = 10_000 * LAMPORTS_PER_SOL;
+    let fee_curve = FeeCurve::piecewise(&[
+        FeeCurvePoint {
+            liquidity: 1_000 * LAMPORTS_PER_SOL,
+            fee: Fee::from_basis_points(150),
+        },
+        FeeCurvePoint {
+            liquidity: 5_000 * LAMPORTS_PER_SOL,
+            fee: Fee::from_basis_points(50),
+        },
+    ])
+    .unwrap();
+
+    test.builder
+        .set_lp_params(
+            &test.state,
+            test.admin_authority.clone(),
+            min_fee,
+            max_fee,
+            liquidity_target,
+            fee_curve,
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.as_ref().liq_pool_fee_curve, fee_curve);
+    assert_eq!(
+        test.state
+            .as_ref()
+            .liq_pool
+            .fee(&fee_curve, 1_000 * LAMPORTS_PER_SOL),
+        Fee::from_basis_points(150)
+    );
+
+    // exponential
+    let fee_curve = FeeCurve::exponential(2_000 * LAMPORTS_PER_SOL);
+    test.builder
+        .set_lp_params(
+            &test.state,
+            test.admin_authority.clone(),
+            min_fee,
+            max_fee,
+            liquidity_target,
+            fee_curve,
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.as_ref().liq_pool_fee_curve, fee_curve);
+
+    // curve point with fee above max_fee
+    test.builder
+        .set_lp_params(
+            &test.state,
+            test.admin_authority.clone(),
+            min_fee,
+            max_fee,
+            liquidity_target,
+            FeeCurve::piecewise(&[FeeCurvePoint {
+                liquidity: 1_000 * LAMPORTS_PER_SOL,
+                fee: Fee::from_basis_points(400),
+            }])
+            .unwrap(),
+        )
+        .unwrap();
+    // should fail with INVALID_FEE_CURVE
+    const ERR_INVALID_FEE_CURVE: u32 = 0x1106;
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_INVALID_FEE_CURVE) => {
+            println!("(expected tx failure 0x{:x})", ERR_INVALID_FEE_CURVE)
+        }
+        Err(x) => debug_assert!(false, "expected err(INVALID_FEE_CURVE) got 0x{:x}", x),
+    }
+    assert_eq!(test.state.as_ref().liq_pool_fee_curve, fee_curve);
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_pause_operations() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        140, 27, 93, 214, 61, 178, 5, 230, 112, 47, 189, 8, 156, 99, 22, 243, 71, 130, 15, 204, 86,
+        167, 38, 251, 120, 3, 196, 57, 225, 148, 10, 79,
+    ]);
+    let mut test = IntegrationTest::start(&InitializeInputWithSeeds::random(&mut rng)).await?;
+    let user = test
+        .create_test_user("test_pause_user", 100 * LAMPORTS_PER_SOL)
+        .await;
+    let user_msol_account = user.get_or_create_msol_account(&mut test).await;
+
+    let pause = |paused_operations| ConfigMarinadeParams {
+        rewards_fee: None,
+        slots_for_stake_delta: None,
+        min_stake: None,
+        min_deposit: None,
+        min_withdraw: None,
+        staking_sol_cap: None,
+        liquidity_sol_cap: None,
+        auto_add_validator_enabled: None,
+        paused_operations: Some(paused_operations),
+        params_change_delay_epochs: None,
+        withdraw_stake_account_fee: None,
+        max_commission: None,
+        liquidity_epoch_outflow_cap: None,
+    };
+
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            pause(State::PAUSE_DEPOSIT | State::PAUSE_LIQUID_UNSTAKE),
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(
+        test.state.as_ref().paused_operations,
+        State::PAUSE_DEPOSIT | State::PAUSE_LIQUID_UNSTAKE
+    );
+
+    // deposit is paused
+    test.builder.deposit(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        LAMPORTS_PER_SOL,
+    );
+    const ERR_OPERATION_PAUSED: u32 = 0x1200;
+    const TICKET_ACCOUNT_SPACE: usize = 8 + std::mem::size_of::<TicketAccountData>();
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_OPERATION_PAUSED) => {
+            println!("(expected tx failure 0x{:x})", ERR_OPERATION_PAUSED)
+        }
+        Err(x) => debug_assert!(false, "expected ERR_OPERATION_PAUSED got 0x{:x}", x),
+    }
+
+    // rebalance delayed-unstakes like order-unstake
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            pause(State::PAUSE_ORDER_UNSTAKE),
+        )
+        .unwrap();
+    test.execute().await;
+    let pool_ticket_account = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            pool_ticket_account.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "liq-pool ticket-account",
+        )
+        .unwrap();
+    test.builder
+        .rebalance_liq_pool(&test.state, pool_ticket_account.pubkey(), LAMPORTS_PER_SOL);
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_OPERATION_PAUSED) => {
+            println!("(expected tx failure 0x{:x})", ERR_OPERATION_PAUSED)
+        }
+        Err(x) => debug_assert!(false, "expected ERR_OPERATION_PAUSED got 0x{:x}", x),
+    }
+
+    // unknown flags are rejected
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            pause(State::PAUSE_ALL + 1),
+        )
+        .unwrap();
+    assert!(test.try_execute().await.is_err());
+
+    // resume
+    test.builder
+        .config_marinade(&test.state, test.admin_authority.clone(), pause(0))
+        .unwrap();
+    test.execute().await;
+    test.builder.deposit(
+        &test.state,
+        user.keypair.clone(),
+        user_msol_account.pubkey,
+        LAMPORTS_PER_SOL,
+    );
+    test.execute().await;
+
+    // instant claim pays SOL from the liq-pool like liquid-unstake
+    let ticket_account = Arc::new(Keypair::new());
+    test.builder
+        .create_account(
+            ticket_account.clone(),
+            TICKET_ACCOUNT_SPACE,
+            &marinade_finance::ID,
+            &test.rent,
+            "ticket-account",
+        )
+        .unwrap();
+    test.builder.order_unstake(
+        &test.state,
+        user_msol_account.pubkey,
+        user.keypair.clone(),
+        LAMPORTS_PER_SOL / 2,
+        ticket_account.pubkey(),
+    );
+    test.execute().await;
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            pause(State::PAUSE_LIQUID_UNSTAKE),
+        )
+        .unwrap();
+    test.execute().await;
+    test.builder.instant_claim(
+        &test.state,
+        ticket_account.pubkey(),
+        user.keypair.clone(),
+        user.keypair.pubkey(),
+    );
+    match test.try_execute().await {
+        Ok(()) => debug_assert!(false, "expected err got Ok"),
+        Err(ERR_OPERATION_PAUSED) => {
+            println!("(expected tx failure 0x{:x})", ERR_OPERATION_PAUSED)
+        }
+        Err(x) => debug_assert!(false, "expected ERR_OPERATION_PAUSED got 0x{:x}", x),
+    }
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_propose_accept_authority() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        61, 200, 14, 177, 92, 35, 248, 103, 9, 156, 74, 211, 138, 47, 190, 26, 83, 165, 240, 5,
+        119, 58, 227, 144, 12, 97, 183, 66, 31, 205, 152, 70,
+    ]);
+    let mut test = IntegrationTest::start(&InitializeInputWithSeeds::random(&mut rng)).await?;
+    let new_authority = Arc::new(Keypair::new());
+    let old_admin = test.state.as_ref().admin_authority;
+
+    test.builder
+        .propose_authority(
+            &test.state,
+            test.admin_authority.clone(),
+            ProposeAuthorityData {
+                admin: Some(new_authority.pubkey()),
+                validator_manager: Some(new_authority.pubkey()),
+                operational_sol_account: None,
+            },
+        )
+        .unwrap();
+    test.execute().await;
+    // nothing changes until accepted
+    assert_eq!(test.state.as_ref().admin_authority, old_admin);
+    assert_eq!(
+        test.state.as_ref().pending_admin_authority,
+        new_authority.pubkey()
+    );
+    assert_eq!(
+        test.state.as_ref().pending_validator_manager_authority,
+        new_authority.pubkey()
+    );
+    assert_eq!(
+        test.state.as_ref().pending_operational_sol_account,
+        Pubkey::default()
+    );
+
+    // other keys can not accept
+    test.builder
+        .accept_authority(&test.state, Arc::new(Keypair::new()));
+    assert!(test.try_execute().await.is_err());
+    assert_eq!(test.state.as_ref().admin_authority, old_admin);
+
+    test.builder
+        .accept_authority(&test.state, new_authority.clone());
+    test.execute().await;
+    assert_eq!(test.state.as_ref().admin_authority, new_authority.pubkey());
+    assert_eq!(
+        test.state.as_ref().validator_system.manager_authority,
+        new_authority.pubkey()
+    );
+    assert_eq!(
+        test.state.as_ref().pending_admin_authority,
+        Pubkey::default()
+    );
+    assert_eq!(
+        test.state.as_ref().pending_validator_manager_authority,
+        Pubkey::default()
+    );
+
+    // already accepted
+    test.builder
+        .accept_authority(&test.state, new_authority.clone());
+    assert!(test.try_execute().await.is_err());
+
+    // change_authority can not skip the two steps
+    test.builder
+        .change_authority(
+            &test.state,
+            new_authority.clone(),
+            ChangeAuthorityData {
+                admin: Some(old_admin),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert!(test.try_execute().await.is_err());
+    assert_eq!(test.state.as_ref().admin_authority, new_authority.pubkey());
+
+    let new_treasury = Pubkey::new_unique();
+    test.builder
+        .change_authority(
+            &test.state,
+            new_authority.clone(),
+            ChangeAuthorityData {
+                treasury_msol_account: Some(new_treasury),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.as_ref().treasury_msol_account, new_treasury);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_queued_params_change() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        18, 240, 77, 139, 6, 201, 55, 162, 98, 31, 224, 13, 170, 84, 119, 45, 250, 67, 142, 9, 188,
+        36, 215, 101, 73, 158, 22, 233, 127, 4, 199, 60,
+    ]);
+    let mut test = IntegrationTest::start(&InitializeInputWithSeeds::random(&mut rng)).await?;
+    const DELAY_EPOCHS: u64 = 2;
+    let empty_params = ConfigMarinadeParams::default();
+
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            ConfigMarinadeParams {
+                params_change_delay_epochs: Some(DELAY_EPOCHS),
+                ..empty_params
+            },
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.params_change_delay_epochs, DELAY_EPOCHS);
+
+    // immediate changes are rejected now
+    const ERR_PARAMS_CHANGE_TIMELOCKED: u32 = 0x1107;
+    let min_deposit = sol_to_lamports(0.05);
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            ConfigMarinadeParams {
+                min_deposit: Some(min_deposit),
+                ..empty_params
+            },
+        )
+        .unwrap();
+    assert_eq!(test.try_execute().await, Err(ERR_PARAMS_CHANGE_TIMELOCKED));
+    test.builder
+        .set_lp_params(
+            &test.state,
+            test.admin_authority.clone(),
+            test.state.liq_pool.lp_min_fee,
+            test.state.liq_pool.lp_max_fee,
+            test.state.liq_pool.lp_liquidity_target,
+            FeeCurve::linear(),
+        )
+        .unwrap();
+    assert_eq!(test.try_execute().await, Err(ERR_PARAMS_CHANGE_TIMELOCKED));
+
+    // but pausing is still immediate
+    test.builder
+        .config_marinade(
+            &test.state,
+            test.admin_authority.clone(),
+            ConfigMarinadeParams {
+                paused_operations: Some(State::PAUSE_DEPOSIT),
+                ..empty_params
+            },
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.paused_operations, State::PAUSE_DEPOSIT);
+
+    // queue a change
+    let queue = |test: &mut IntegrationTest, change| {
+        let queued_change = Arc::new(Keypair::new());
+        test.builder
+            .create_account(
+                queued_change.clone(),
+                QueuedChangeData::SPACE,
+                &marinade_finance_offchain_sdk::marinade_finance::ID,
+                &test.rent,
+                "queued-change",
+            )
+            .unwrap();
+        test.builder
+            .queue_params_change(
+                &test.state,
+                test.admin_authority.clone(),
+                queued_change.pubkey(),
+                change,
+            )
+            .unwrap();
+        queued_change.pubkey()
+    };
+    let queued_change = queue(
+        &mut test,
+        ParamsChange::ConfigMarinade(ConfigMarinadeParams {
+            min_deposit: Some(min_deposit),
+            ..empty_params
+        }),
+    );
+    test.execute().await;
+    let fee_payer = test.fee_payer();
+
+    // pausing can not be queued
+    queue(
+        &mut test,
+        ParamsChange::ConfigMarinade(ConfigMarinadeParams {
+            paused_operations: Some(0),
+            ..empty_params
+        }),
+    );
+    assert!(test.try_execute().await.is_err());
+
+    // not due yet
+    const ERR_QUEUED_CHANGE_NOT_DUE: u32 = 0x1108;
+    test.builder
+        .execute_params_change(&test.state, queued_change, fee_payer);
+    assert_eq!(test.try_execute().await, Err(ERR_QUEUED_CHANGE_NOT_DUE));
+
+    for _ in 0..DELAY_EPOCHS {
+        test.move_to_next_epoch().await;
+    }
+    test.builder
+        .execute_params_change(&test.state, queued_change, fee_payer);
+    // give the rent back in the same tx to keep the executed change account alive
+    test.builder
+        .add_instruction(
+            system_instruction::transfer(
+                &fee_payer,
+                &queued_change,
+                test.rent.minimum_balance(QueuedChangeData::SPACE),
+            ),
+            format!("refund queued change {}", queued_change),
+        )
+        .unwrap();
+    test.execute().await;
+    assert_eq!(test.state.min_deposit, min_deposit);
+
+    // an executed change can not be executed again
+    test.builder
+        .execute_params_change(&test.state, queued_change, fee_payer);
+    assert!(test.try_execute().await.is_err());
+
+    // cancel
+    let queued_change = queue(
+        &mut test,
+        ParamsChange::ConfigMarinade(ConfigMarinadeParams {
+            min_deposit: Some(0),
+            ..empty_params
+        }),
+    );
+    test.execute().await;
+    test.builder
+        .cancel_params_change(
+            &test.state,
+            test.admin_authority.clone(),
+            queued_change,
+            fee_payer,
+        )
+        .unwrap();
+    test.execute().await;
+    assert!(test
+        .context
+        .banks_client
+        .get_account(queued_change)
+        .await?
+        .is_none());
+    assert_eq!(test.state.min_deposit, min_deposit);
+
+    Ok(())
+}
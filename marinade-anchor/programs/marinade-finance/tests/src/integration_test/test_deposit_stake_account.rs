@@ -9,13 +9,17 @@ use marinade_finance_offchain_sdk::spl_associated_token_account::{
 };
 use marinade_finance_offchain_sdk::{
     instruction_helpers::InstructionHelpers,
-    marinade_finance::{ticket_account::TicketAccountData, State},
+    marinade_finance::{
+        pending_stake_deposit::PendingStakeDepositData, stake_system::StakeSystemHelpers,
+        ticket_account::TicketAccountData, validator_system::ValidatorSystem,
+        ConfigMarinadeParams, Fee, State,
+    },
 };
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use solana_sdk::stake;
 use solana_sdk::stake::instruction::LockupArgs;
-use solana_sdk::stake::state::Lockup;
+use solana_sdk::stake::state::{Lockup, StakeState};
 use solana_sdk::{
     native_token::sol_to_lamports,
     pubkey::Pubkey,
@@ -60,6 +64,7 @@ async fn test_deposit_stake_account() -> anyhow::Result<()> {
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
     test.execute().await;
     assert_eq!(
@@ -76,6 +81,14 @@ async fn test_deposit_stake_account() -> anyhow::Result<()> {
         "user",
         "stake",
     )?;
+    let stake_with_extra_lamports_state: StakeWrapper = test
+        .get_account_data(&stake_with_extra_lamports.pubkey())
+        .await;
+    // extra lamports are credited through the reserve
+    let expected_msol = test
+        .state
+        .calc_msol_from_lamports(stake_with_extra_lamports_state.delegation().unwrap().stake + 1)?;
+    let user_msol_balance = test.get_token_balance(&user_msol).await;
     test.builder.deposit_stake_account(
         &test.state,
         stake_with_extra_lamports.pubkey(),
@@ -84,54 +97,581 @@ async fn test_deposit_stake_account() -> anyhow::Result<()> {
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
-    test.try_execute()
-        .await
-        .expect_err("Must not accept extra lamports on balance");
+    test.execute().await;
+    assert_eq!(
+        test.get_token_balance(&user_msol).await,
+        user_msol_balance + expected_msol
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_deposit_stake_account_with_lockup() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        142, 61, 203, 18, 97, 230, 5, 174, 39, 88, 151, 222, 70, 13, 196, 107, 54, 241, 126, 33,
+        180, 2, 163, 91, 218, 47, 135, 76, 9, 250, 112, 187,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let validator = Arc::new(Keypair::generate(&mut rng));
+    let vote = Arc::new(Keypair::generate(&mut rng));
+    test.add_validator(validator, vote.clone(), 0x100);
+
+    let user_msol = test.builder.create_associated_token_account(
+        &test.fee_payer(),
+        &test.state.msol_mint,
+        "mSOL",
+    )?;
+    const ERR_ACCOUNT_WITH_LOCKUP: u32 = 45694;
+
+    let custodian: Arc<dyn Signer> = Arc::new(Keypair::new());
+    let create_stake_with_lockup = |test: &mut IntegrationTest, stake: Pubkey, epoch: u64| {
+        test.builder
+            .add_instruction(
+                stake::instruction::set_lockup(
+                    &stake,
+                    &LockupArgs {
+                        unix_timestamp: Some(0),
+                        epoch: Some(epoch),
+                        custodian: Some(custodian.pubkey()),
+                    },
+                    &test.fee_payer(),
+                ),
+                format!("Set lockup"),
+            )
+            .unwrap();
+    };
+
+    // lockup in force without the custodian signature
+    let stake_in_force = test
+        .create_activated_stake_account(&vote.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .await;
+    let lockup_epoch = test.get_clock().await.epoch + 10;
+    create_stake_with_lockup(&mut test, stake_in_force.pubkey(), lockup_epoch);
+    test.execute().await;
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake_in_force.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    assert_eq!(test.try_execute().await, Err(ERR_ACCOUNT_WITH_LOCKUP));
+    // a signer which is not the custodian is not enough
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake_in_force.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        Some(Arc::new(Keypair::new())),
+    );
+    assert_eq!(test.try_execute().await, Err(ERR_ACCOUNT_WITH_LOCKUP));
+    assert_eq!(test.get_token_balance_or_zero(&user_msol).await, 0);
 
-    let stake_with_lockup = test
+    // lockup in force with the custodian signature
+    let stake_state: StakeWrapper = test.get_account_data(&stake_in_force.pubkey()).await;
+    let expected_msol = test
+        .state
+        .calc_msol_from_lamports(stake_state.delegation().unwrap().stake)?;
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake_in_force.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        Some(custodian.clone()),
+    );
+    test.execute().await;
+    assert_eq!(test.get_token_balance(&user_msol).await, expected_msol);
+    let stake_state: StakeWrapper = test.get_account_data(&stake_in_force.pubkey()).await;
+    assert_eq!(stake_state.lockup().unwrap(), Lockup::default());
+    assert_eq!(
+        stake_state.meta().unwrap().authorized.withdrawer,
+        test.state.stake_withdraw_authority()
+    );
+
+    // expired lockup without the custodian signature
+    let stake_expired = test
         .create_activated_stake_account(&vote.pubkey(), 10 * LAMPORTS_PER_SOL)
         .await;
     let lockup_epoch = test.get_clock().await.epoch + 1;
-    test.builder.add_instruction(
-        stake::instruction::set_lockup(
-            &stake_with_lockup.pubkey(),
-            &LockupArgs {
-                unix_timestamp: Some(0),
-                epoch: Some(lockup_epoch),
-                custodian: Some(Pubkey::new_unique()),
-            },
-            &test.fee_payer(),
-        ),
-        format!("Set lockup"),
+    create_stake_with_lockup(&mut test, stake_expired.pubkey(), lockup_epoch);
+    test.execute().await;
+    let clock = test.move_to_next_epoch().await;
+    assert_eq!(clock.epoch, lockup_epoch);
+
+    let stake_state: StakeWrapper = test.get_account_data(&stake_expired.pubkey()).await;
+    assert_eq!(stake_state.lockup().unwrap().epoch, lockup_epoch);
+    let user_msol_balance = test.get_token_balance(&user_msol).await;
+    let expected_msol = test
+        .state
+        .calc_msol_from_lamports(stake_state.delegation().unwrap().stake)?;
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake_expired.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    test.execute().await;
+    assert_eq!(
+        test.get_token_balance(&user_msol).await,
+        user_msol_balance + expected_msol
+    );
+    let stake_state: StakeWrapper = test.get_account_data(&stake_expired.pubkey()).await;
+    assert_eq!(stake_state.lockup().unwrap(), Lockup::default());
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_withdraw_stake_account() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        94, 187, 12, 230, 51, 8, 141, 77, 200, 33, 169, 5, 118, 62, 245, 19, 137, 84, 216, 40, 99,
+        3, 172, 58, 121, 250, 36, 193, 70, 145, 11, 207,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let validator = Arc::new(Keypair::generate(&mut rng));
+    let vote = Arc::new(Keypair::generate(&mut rng));
+
+    test.add_validator(validator, vote.clone(), 0x100);
+
+    let stake = test
+        .create_activated_stake_account(&vote.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .await;
+    let user_msol = test.builder.create_associated_token_account(
+        &test.fee_payer(),
+        &test.state.msol_mint,
+        "mSOL",
     )?;
     test.builder.deposit_stake_account(
         &test.state,
-        stake_with_lockup.pubkey(),
+        stake.pubkey(),
         test.fee_payer_signer(),
         user_msol,
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
+    );
+    test.execute().await;
+
+    const WITHDRAW_FEE: Fee = Fee { basis_points: 100 };
+    test.builder.config_marinade(
+        &test.state,
+        test.admin_authority.clone(),
+        ConfigMarinadeParams {
+            withdraw_stake_account_fee: Some(WITHDRAW_FEE),
+            max_commission: None,
+            liquidity_epoch_outflow_cap: None,
+            ..ConfigMarinadeParams::default()
+        },
+    )?;
+    test.execute().await;
+    assert_eq!(test.state.withdraw_stake_account_fee, WITHDRAW_FEE);
+
+    // must not leave less than min_stake in the marinade stake
+    let split_stake = Arc::new(Keypair::new());
+    test.builder.create_account(
+        split_stake.clone(),
+        std::mem::size_of::<StakeState>(),
+        &stake::program::ID,
+        &test.rent,
+        "split_stake_account",
+    )?;
+    test.execute().await;
+    let all_msol = test.get_token_balance(&user_msol).await;
+    test.builder.withdraw_stake_account(
+        &test.state,
+        user_msol,
+        test.fee_payer_signer(),
+        stake.pubkey(),
+        split_stake.clone(),
+        test.fee_payer_signer(),
+        0,
+        0,
+        all_msol,
     );
     test.try_execute()
         .await
-        .expect_err("Must not accept locked up stake");
+        .expect_err("Must keep min stake in the marinade stake account");
+
+    let msol_amount = 3 * LAMPORTS_PER_SOL;
+    let expected_lamports = test
+        .state
+        .calc_lamports_from_msol_amount(msol_amount - WITHDRAW_FEE.apply(msol_amount))?;
+    let user_msol_balance = test.get_token_balance(&user_msol).await;
+    let total_active_balance = test.state.validator_system.total_active_balance;
+    test.builder.withdraw_stake_account(
+        &test.state,
+        user_msol,
+        test.fee_payer_signer(),
+        stake.pubkey(),
+        split_stake.clone(),
+        test.fee_payer_signer(),
+        0,
+        0,
+        msol_amount,
+    );
+    test.execute().await;
+
+    assert_eq!(
+        test.get_token_balance(&user_msol).await,
+        user_msol_balance - msol_amount
+    );
+    let split_stake_state: StakeWrapper = test.get_account_data(&split_stake.pubkey()).await;
+    assert_eq!(
+        split_stake_state.delegation().unwrap().stake,
+        expected_lamports
+    );
+    let authorized = split_stake_state.meta().unwrap().authorized;
+    assert_eq!(authorized.staker, test.fee_payer());
+    assert_eq!(authorized.withdrawer, test.fee_payer());
+    assert_eq!(
+        test.state.validator_system.total_active_balance,
+        total_active_balance - expected_lamports
+    );
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_deposit_activating_stake_account() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        17, 203, 66, 142, 9, 251, 120, 37, 180, 91, 4, 226, 55, 168, 73, 210, 29, 147, 88, 6, 190,
+        61, 132, 245, 14, 102, 219, 43, 176, 80, 35, 157,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    let validator = Arc::new(Keypair::generate(&mut rng));
+    let vote = Arc::new(Keypair::generate(&mut rng));
+
+    test.add_validator(validator, vote.clone(), 0x100);
+
+    let stake = Arc::new(Keypair::new());
+    test.create_stake(&vote.pubkey(), 10 * LAMPORTS_PER_SOL, stake.clone());
+    test.execute().await;
+    let user_msol = test.builder.create_associated_token_account(
+        &test.fee_payer(),
+        &test.state.msol_mint,
+        "mSOL",
+    )?;
+    let stake_state: StakeWrapper = test.get_account_data(&stake.pubkey()).await;
+    let delegated = stake_state.delegation().unwrap().stake;
+
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    test.try_execute()
+        .await
+        .expect_err("Must not accept activating stake without pending deposit");
+
+    let pending_deposit = PendingStakeDepositData::find_address(&test.state.key, &stake.pubkey()).0;
+    // funding the address in advance must not block the deposit
+    test.builder.add_instruction(
+        system_instruction::transfer(&test.fee_payer(), &pending_deposit, 1),
+        format!("fund pending deposit {}", pending_deposit),
+    )?;
+    test.builder.deposit_activating_stake_account(
+        &test.state,
+        stake.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    test.execute().await;
+    assert_eq!(test.get_token_balance_or_zero(&user_msol).await, 0);
+    let pending: PendingStakeDepositData = test.get_account_data(&pending_deposit).await;
+    assert_eq!(pending.stake_account, stake.pubkey());
+    assert_eq!(pending.mint_to, user_msol);
+    assert_eq!(
+        pending.activation_epoch,
+        stake_state.delegation().unwrap().activation_epoch
+    );
+    let stake_state: StakeWrapper = test.get_account_data(&stake.pubkey()).await;
+    let authorized = stake_state.meta().unwrap().authorized;
+    assert_eq!(authorized.staker, test.state.stake_deposit_authority());
+    assert_eq!(authorized.withdrawer, test.state.stake_withdraw_authority());
+
+    test.builder
+        .finish_stake_deposit(&test.state, stake.pubkey(), test.fee_payer(), user_msol, 0);
+    test.try_execute()
+        .await
+        .expect_err("Must not finish the deposit before activation");
+
+    test.context
+        .increment_vote_account_credits(&vote.pubkey(), 1000);
+    test.move_to_next_epoch().await;
+    test.context
+        .increment_vote_account_credits(&vote.pubkey(), 2000);
     test.move_to_next_epoch().await;
 
+    let expected_msol = test.state.calc_msol_from_lamports(delegated)?;
+    let total_active_balance = test.state.validator_system.total_active_balance;
+    test.builder
+        .finish_stake_deposit(&test.state, stake.pubkey(), test.fee_payer(), user_msol, 0);
+    // give the rent back in the same tx to keep the finished pending deposit alive
+    test.builder.add_instruction(
+        system_instruction::transfer(
+            &test.fee_payer(),
+            &pending_deposit,
+            test.rent.minimum_balance(PendingStakeDepositData::SPACE),
+        ),
+        format!("refund pending deposit {}", pending_deposit),
+    )?;
+    test.execute().await;
+    assert_eq!(test.get_token_balance(&user_msol).await, expected_msol);
+    assert_eq!(
+        test.state.validator_system.total_active_balance,
+        total_active_balance + delegated
+    );
+    let stake_count = test.state.stake_system.stake_count();
+
+    // a finished deposit can not mint again
+    test.builder
+        .finish_stake_deposit(&test.state, stake.pubkey(), test.fee_payer(), user_msol, 0);
+    test.try_execute()
+        .await
+        .expect_err("Must not finish the deposit twice");
+    assert_eq!(test.get_token_balance(&user_msol).await, expected_msol);
+    assert_eq!(test.state.stake_system.stake_count(), stake_count);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_deposit_stake_account_blocked_validator() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        73, 190, 4, 221, 38, 117, 9, 162, 250, 81, 33, 146, 208, 57, 12, 99, 175, 64, 231, 18, 140,
+        7, 93, 186, 29, 240, 111, 52, 167, 3, 214, 80,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    const ERR_VALIDATOR_BLOCKED: u32 = 0xBAD2;
+
+    let validator = Arc::new(Keypair::generate(&mut rng));
+    let vote = Arc::new(Keypair::generate(&mut rng));
+    test.install_validator(validator, vote.clone());
+    // funding the flag address in advance must not prevent the blocking
+    let blocked_flag = ValidatorSystem::find_blocked_flag(&test.state.key, &vote.pubkey()).0;
+    test.builder
+        .add_instruction(
+            system_instruction::transfer(&test.fee_payer(), &blocked_flag, 1),
+            format!("fund blocked flag {}", blocked_flag),
+        )
+        .unwrap();
+    test.execute().await;
+    test.builder.block_validator(
+        &test.state,
+        test.validator_manager_authority.clone(),
+        vote.pubkey(),
+        test.fee_payer_signer(),
+    )?;
+    test.execute().await;
+
+    test.builder.add_validator(
+        &test.state,
+        test.validator_manager_authority.clone(),
+        vote.pubkey(),
+        0x100,
+        test.fee_payer_signer(),
+    )?;
+    assert_eq!(test.try_execute().await, Err(ERR_VALIDATOR_BLOCKED));
+
+    test.builder.config_marinade(
+        &test.state,
+        test.admin_authority.clone(),
+        ConfigMarinadeParams {
+            rewards_fee: None,
+            slots_for_stake_delta: None,
+            min_stake: None,
+            min_deposit: None,
+            min_withdraw: None,
+            staking_sol_cap: None,
+            liquidity_sol_cap: None,
+            auto_add_validator_enabled: Some(true),
+            paused_operations: None,
+            params_change_delay_epochs: None,
+            withdraw_stake_account_fee: None,
+            max_commission: None,
+            liquidity_epoch_outflow_cap: None,
+        },
+    )?;
+    test.execute().await;
+
+    let stake = test
+        .create_activated_stake_account(&vote.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .await;
+    let user_msol = test.builder.create_associated_token_account(
+        &test.fee_payer(),
+        &test.state.msol_mint,
+        "mSOL",
+    )?;
+    test.execute().await;
+
+    // auto-add is refused for the blocked validator
     test.builder.deposit_stake_account(
         &test.state,
-        stake_with_lockup.pubkey(),
+        stake.pubkey(),
         test.fee_payer_signer(),
         user_msol,
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
+    assert_eq!(test.try_execute().await, Err(ERR_VALIDATOR_BLOCKED));
+
+    test.builder.unblock_validator(
+        &test.state,
+        test.validator_manager_authority.clone(),
+        vote.pubkey(),
+        test.fee_payer(),
+    )?;
     test.execute().await;
 
-    let stake_with_lockup: StakeWrapper = test.get_account_data(&simple_stake.pubkey()).await;
-    assert_eq!(stake_with_lockup.lockup().unwrap(), Lockup::default());
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    test.execute().await;
+    assert_eq!(test.state.validator_system.validator_count(), 1);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_deposit_stake_account_max_commission() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        158, 41, 203, 9, 76, 190, 33, 112, 5, 247, 61, 138, 24, 99, 180, 52, 7, 216, 143, 70, 11,
+        229, 88, 164, 37, 121, 250, 3, 196, 58, 83, 14,
+    ]);
+    let input = InitializeInputWithSeeds::random(&mut rng);
+    let mut test = IntegrationTest::start(&input).await?;
+
+    const ERR_VALIDATOR_COMMISSION_TOO_HIGH: u32 = 0xBAD3;
+
+    let validator = Arc::new(Keypair::generate(&mut rng));
+    let vote = Arc::new(Keypair::generate(&mut rng));
+    test.install_validator_with_commission(validator, vote.clone(), 10);
+    test.builder.add_validator(
+        &test.state,
+        test.validator_manager_authority.clone(),
+        vote.pubkey(),
+        0x100,
+        test.fee_payer_signer(),
+    )?;
+    test.execute().await;
+
+    let config_max_commission = |max_commission| ConfigMarinadeParams {
+        rewards_fee: None,
+        slots_for_stake_delta: None,
+        min_stake: None,
+        min_deposit: None,
+        min_withdraw: None,
+        staking_sol_cap: None,
+        liquidity_sol_cap: None,
+        auto_add_validator_enabled: None,
+        paused_operations: None,
+        params_change_delay_epochs: None,
+        withdraw_stake_account_fee: None,
+        max_commission: Some(max_commission),
+        liquidity_epoch_outflow_cap: None,
+    };
+    test.builder.config_marinade(
+        &test.state,
+        test.admin_authority.clone(),
+        config_max_commission(5),
+    )?;
+    test.execute().await;
+    assert_eq!(test.state.max_commission, 5);
+
+    let stake = test
+        .create_activated_stake_account(&vote.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .await;
+    let user_msol = test.builder.create_associated_token_account(
+        &test.fee_payer(),
+        &test.state.msol_mint,
+        "mSOL",
+    )?;
+    test.execute().await;
+
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    assert_eq!(
+        test.try_execute().await,
+        Err(ERR_VALIDATOR_COMMISSION_TOO_HIGH)
+    );
+
+    test.builder.config_marinade(
+        &test.state,
+        test.admin_authority.clone(),
+        config_max_commission(10),
+    )?;
+    test.execute().await;
+
+    test.builder.deposit_stake_account(
+        &test.state,
+        stake.pubkey(),
+        test.fee_payer_signer(),
+        user_msol,
+        0,
+        vote.pubkey(),
+        test.fee_payer_signer(),
+        None,
+    );
+    test.execute().await;
+    assert_eq!(
+        test.state.validator_system.total_active_balance,
+        test.get_account_data::<StakeWrapper>(&stake.pubkey())
+            .await
+            .delegation()
+            .unwrap()
+            .stake
+    );
 
     Ok(())
 }
@@ -62,6 +62,7 @@ async fn test_merge_success() -> anyhow::Result<()> {
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
     test.builder.deposit_stake_account(
         &test.state,
@@ -71,6 +72,7 @@ async fn test_merge_success() -> anyhow::Result<()> {
         0,
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
     test.execute().await;
     assert_eq!(test.state.stake_system.stake_count(), 2);
@@ -73,6 +73,7 @@ async fn test_unstake_unlisted() -> anyhow::Result<()> {
         test.state.validator_system.validator_count(), // new validator
         vote.pubkey(),
         test.fee_payer_signer(),
+        None,
     );
     test.execute().await;
     assert_eq!(test.state.validator_system.validator_count(), 5);
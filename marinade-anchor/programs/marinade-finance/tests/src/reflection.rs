@@ -62,7 +62,11 @@ async fn test_reflection_write_read() -> anyhow::Result<()> {
             stake_count: 0,
             score: Uniform::from(10..100).sample(&mut rng),
             last_stake_delta_epoch: Epoch::MAX,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
             total_delegated_delta: 0, // no stakes
+            total_slashed: 0,         // no stakes
             total_extra_balance: 0,   // no stakes
         },
     )?;
@@ -87,7 +91,11 @@ async fn test_reflection_write_read() -> anyhow::Result<()> {
             stake_count: 1,
             score: Uniform::from(10..100).sample(&mut rng),
             last_stake_delta_epoch: Epoch::MAX,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
             total_delegated_delta: validator2_stake.delta,
+            total_slashed: 0,
             total_extra_balance: validator2_stake.extra_balance,
         },
     )?;
@@ -109,7 +117,11 @@ async fn test_reflection_write_read() -> anyhow::Result<()> {
             stake_count: validator3_stakes.len() as u32,
             score: Uniform::from(10..100).sample(&mut rng),
             last_stake_delta_epoch: Epoch::MAX,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
             total_delegated_delta: validator3_stakes.iter().map(|data| data.delta).sum(),
+            total_slashed: 0,
             total_extra_balance: validator3_stakes
                 .iter()
                 .map(|data| data.extra_balance)
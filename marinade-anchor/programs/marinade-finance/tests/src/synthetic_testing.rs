@@ -1,2 +1,3 @@
 pub mod test_update_price;
-pub mod test_deactivate_stake;
\ No newline at end of file
+pub mod test_deactivate_stake;
+pub mod test_grow_list;
\ No newline at end of file
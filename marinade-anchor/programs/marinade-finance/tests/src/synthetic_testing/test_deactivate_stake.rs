@@ -59,7 +59,11 @@ async fn test_deactivate_stake_whole() -> anyhow::Result<()> {
             stake_count: 1,
             score: Uniform::from(1..200).sample(&mut rng),
             last_stake_delta_epoch: Epoch::MAX,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
             total_delegated_delta: 0,
+            total_slashed: 0,
             total_extra_balance: 0,
         },
     )?;
@@ -168,7 +172,11 @@ async fn test_deactivate_stake_split() -> anyhow::Result<()> {
             stake_count: 1,
             score: Uniform::from(1..200).sample(&mut rng),
             last_stake_delta_epoch: Epoch::MAX,
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
             total_delegated_delta: 0,
+            total_slashed: 0,
             total_extra_balance: 0,
         },
     )?;
@@ -0,0 +1,246 @@
+use crate::program_test;
+use marinade_finance_offchain_sdk::anchor_lang::{AccountDeserialize, AccountSerialize};
+use marinade_finance_offchain_sdk::{
+    instruction_helpers::InstructionHelpers,
+    marinade_finance::{
+        self, list::List, stake_system::StakeRecord, validator_system::ValidatorRecord, State,
+    },
+    transaction_builder::TransactionBuilder,
+    WithKey,
+};
+use marinade_reflection::{accounts_builder::AccountsBuilder, builder::RandomBuildParams};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    sysvar::rent::Rent,
+};
+use std::{io::Cursor, sync::Arc};
+use test_env_log::test;
+
+// additional record space used by the SDK when the legacy lists were created
+const LEGACY_ADDITIONAL_RECORD_SPACE: usize = 8;
+
+fn legacy_list_account(
+    legacy_discriminator: &[u8; 8],
+    legacy_records: &[Vec<u8>],
+    capacity: u32,
+    rent: &Rent,
+) -> Account {
+    let item_size = legacy_records[0].len() + LEGACY_ADDITIONAL_RECORD_SPACE;
+    let data_len = 8 + capacity as usize * item_size;
+    let mut account = Account::new(rent.minimum_balance(data_len), data_len, &marinade_finance::ID);
+    account.data[0..8].copy_from_slice(legacy_discriminator);
+    for (index, record) in legacy_records.iter().enumerate() {
+        let start = 8 + index * item_size;
+        account.data[start..start + record.len()].copy_from_slice(record);
+    }
+    account
+}
+
+#[test(tokio::test)]
+async fn test_grow_legacy_lists() -> anyhow::Result<()> {
+    let mut rng = ChaChaRng::from_seed([
+        203, 41, 118, 7, 250, 96, 33, 172, 145, 62, 218, 9, 87, 190, 24, 133, 76, 241, 15, 108,
+        160, 53, 229, 38, 194, 81, 12, 147, 66, 205, 99, 170,
+    ]);
+    let rent = Rent::default();
+    let admin_authority = Arc::new(Keypair::generate(&mut rng));
+
+    let mut builder = marinade_reflection::builder::Builder::default();
+    builder.set_admin_authority(admin_authority.pubkey());
+    let params = RandomBuildParams::pick(&mut builder, &mut rng);
+    builder.random_fill(&mut rng, &params, &rent);
+    let reflection = builder.build(&rent);
+
+    let mut account_builder = AccountsBuilder::new_random(&reflection, &mut rng, 0, 0);
+    account_builder.random_fill(&mut rng);
+    let accounts = account_builder.build(&rent)?;
+    let mut storage = accounts.storage;
+
+    let mut state: State = AccountDeserialize::try_deserialize(
+        &mut storage.get(&accounts.state).unwrap().data.as_slice(),
+    )?;
+
+    // rewrite both lists the way they were stored before the zero-copy records
+    let validator_list_data = storage.get(&accounts.validator_list).unwrap().data.clone();
+    let validator_capacity = state
+        .validator_system
+        .validator_list_capacity(validator_list_data.len())?;
+    let mut validators = vec![];
+    let mut legacy_validators = vec![];
+    for index in 0..state.validator_system.validator_count() {
+        let validator = state.validator_system.get(&validator_list_data, index)?;
+        let mut legacy = validator.validator_account.to_bytes().to_vec();
+        legacy.extend_from_slice(&validator.active_balance.to_le_bytes());
+        legacy.extend_from_slice(&validator.score.to_le_bytes());
+        legacy.extend_from_slice(&validator.last_stake_delta_epoch.to_le_bytes());
+        legacy.push(validator.duplication_flag_bump_seed);
+        assert_eq!(legacy.len(), ValidatorRecord::LEGACY_SIZE);
+        legacy_validators.push(legacy);
+        // the legacy layout has no stake bounds and no directed stake
+        validators.push(ValidatorRecord {
+            directed_stake_lamports: 0,
+            min_stake: 0,
+            max_stake: 0,
+            ..validator
+        });
+    }
+    assert!(!validators.is_empty());
+    storage.insert(
+        accounts.validator_list,
+        legacy_list_account(
+            ValidatorRecord::LEGACY_DISCRIMINATOR,
+            &legacy_validators,
+            validator_capacity,
+            &rent,
+        ),
+    );
+    state.validator_system.validator_list.item_size =
+        (ValidatorRecord::LEGACY_SIZE + LEGACY_ADDITIONAL_RECORD_SPACE) as u32;
+    assert_eq!(state.validator_system.validator_record_size(), 61);
+
+    let stake_list_data = storage.get(&accounts.stake_list).unwrap().data.clone();
+    let stake_capacity = state
+        .stake_system
+        .stake_list_capacity(stake_list_data.len())?;
+    let mut stakes = vec![];
+    let mut legacy_stakes = vec![];
+    for index in 0..state.stake_system.stake_count() {
+        let stake = state.stake_system.get(&stake_list_data, index)?;
+        let mut legacy = stake.stake_account.to_bytes().to_vec();
+        legacy.extend_from_slice(&stake.last_update_delegated_lamports.to_le_bytes());
+        legacy.extend_from_slice(&stake.last_update_epoch.to_le_bytes());
+        legacy.push(stake.is_emergency_unstaking);
+        assert_eq!(legacy.len(), StakeRecord::LEGACY_SIZE);
+        legacy_stakes.push(legacy);
+        stakes.push(stake);
+    }
+    assert!(!stakes.is_empty());
+    storage.insert(
+        accounts.stake_list,
+        legacy_list_account(
+            StakeRecord::LEGACY_DISCRIMINATOR,
+            &legacy_stakes,
+            stake_capacity,
+            &rent,
+        ),
+    );
+    state.stake_system.stake_list.item_size =
+        (StakeRecord::LEGACY_SIZE + LEGACY_ADDITIONAL_RECORD_SPACE) as u32;
+
+    let state_account = storage.get_mut(&accounts.state).unwrap();
+    state.try_serialize(&mut Cursor::new(&mut state_account.data))?;
+
+    let mut test = program_test();
+    for (key, account) in storage {
+        test.add_account(key, account);
+    }
+    let mut context = test.start_with_context().await;
+    let fee_payer = Arc::new(Keypair::from_bytes(&context.payer.to_bytes()).unwrap());
+    let state = WithKey::new(state, accounts.state);
+
+    let new_validator_list = Arc::new(Keypair::generate(&mut rng));
+    let new_stake_list = Arc::new(Keypair::generate(&mut rng));
+    let mut transaction_builder = TransactionBuilder::unlimited(fee_payer.clone());
+    transaction_builder.create_account(
+        new_validator_list.clone(),
+        List::bytes_for(
+            std::mem::size_of::<ValidatorRecord>() as u32,
+            validator_capacity + 1,
+        ) as usize,
+        &marinade_finance::ID,
+        &rent,
+        "new_validator_list",
+    )?;
+    transaction_builder.create_account(
+        new_stake_list.clone(),
+        List::bytes_for(std::mem::size_of::<StakeRecord>() as u32, stake_capacity + 1) as usize,
+        &marinade_finance::ID,
+        &rent,
+        "new_stake_list",
+    )?;
+    // the records are migrated in chunks
+    let validator_chunk = (validators.len() as u32 + 1) / 2;
+    for _ in 0..(validators.len() as u32 + validator_chunk - 1) / validator_chunk {
+        transaction_builder.grow_list(
+            &state,
+            admin_authority.clone(),
+            accounts.validator_list,
+            new_validator_list.pubkey(),
+            fee_payer.pubkey(),
+            validator_chunk,
+        )?;
+    }
+    let stake_chunk = (stakes.len() as u32 + 1) / 2;
+    for _ in 0..(stakes.len() as u32 + stake_chunk - 1) / stake_chunk {
+        transaction_builder.grow_list(
+            &state,
+            admin_authority.clone(),
+            accounts.stake_list,
+            new_stake_list.pubkey(),
+            fee_payer.pubkey(),
+            stake_chunk,
+        )?;
+    }
+    let transaction = transaction_builder
+        .build_one_combined()
+        .unwrap()
+        .into_signed(context.banks_client.get_recent_blockhash().await?)?;
+    context.banks_client.process_transaction(transaction).await?;
+
+    let state: State = AccountDeserialize::try_deserialize(
+        &mut context
+            .banks_client
+            .get_account(accounts.state)
+            .await?
+            .unwrap()
+            .data
+            .as_slice(),
+    )?;
+
+    assert_eq!(
+        *state.validator_system.validator_list_address(),
+        new_validator_list.pubkey()
+    );
+    assert_eq!(
+        state.validator_system.validator_record_size() as usize,
+        std::mem::size_of::<ValidatorRecord>()
+    );
+    let validator_list_data = context
+        .banks_client
+        .get_account(new_validator_list.pubkey())
+        .await?
+        .unwrap()
+        .data;
+    assert_eq!(&validator_list_data[0..8], ValidatorRecord::DISCRIMINATOR);
+    for (index, validator) in validators.iter().enumerate() {
+        assert_eq!(
+            state
+                .validator_system
+                .get(&validator_list_data, index as u32)?,
+            *validator
+        );
+    }
+
+    assert_eq!(
+        *state.stake_system.stake_list_address(),
+        new_stake_list.pubkey()
+    );
+    assert_eq!(
+        state.stake_system.stake_record_size() as usize,
+        std::mem::size_of::<StakeRecord>()
+    );
+    let stake_list_data = context
+        .banks_client
+        .get_account(new_stake_list.pubkey())
+        .await?
+        .unwrap()
+        .data;
+    assert_eq!(&stake_list_data[0..8], StakeRecord::DISCRIMINATOR);
+    for (index, stake) in stakes.iter().enumerate() {
+        assert_eq!(state.stake_system.get(&stake_list_data, index as u32)?, *stake);
+    }
+    Ok(())
+}
@@ -10,7 +10,11 @@ use marinade_finance_offchain_sdk::anchor_lang::solana_program::{
 };
 use marinade_finance_offchain_sdk::{
     instruction_helpers::InstructionHelpers,
-    marinade_finance::{Fee, State},
+    marinade_finance::{
+        calc::proportional,
+        epoch_rewards::{EpochRewardRecord, EpochRewards},
+        Fee, State,
+    },
     transaction_builder::TransactionBuilder,
     WithKey,
 };
@@ -23,8 +27,8 @@ use marinade_reflection::{
 use rand::{distributions::Uniform, prelude::*, SeedableRng};
 use rand_chacha::ChaChaRng;
 use solana_sdk::{
-    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
-    stake::state::StakeState, sysvar::rent::Rent,
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey,
+    stake::state::StakeState, system_instruction, sysvar::rent::Rent,
 };
 use solana_vote_program::vote_state::{VoteInit, VoteState};
 use std::{collections::HashMap, sync::Arc};
@@ -156,3 +160,361 @@ async fn test_update_active() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test(tokio::test)]
+async fn test_update_active_slashed() -> anyhow::Result<()> {
+    use rand_chacha::rand_core::SeedableRng;
+    let mut rng = ChaChaRng::from_seed([
+        31, 206, 77, 140, 12, 251, 168, 45, 93, 120, 6, 214, 159, 38, 187, 66, 240, 17, 102, 129,
+        55, 226, 81, 194, 3, 147, 70, 233, 118, 28, 175, 90,
+    ]);
+    let rent = Rent::default(); // must be the equal to actual rent sysvar in blockchain. Will be checked later
+
+    let mut builder = marinade_reflection::builder::Builder::default();
+    builder.set_reward_fee(Fee::from_basis_points(0)); // Do not change mSOL supply for simplicity
+    let params = RandomBuildParams::pick(&mut builder, &mut rng);
+    builder.random_fill(&mut rng, &params, &rent); // basic state
+
+    // Validator for test
+    let (validator_vote, validator) = builder
+        .validators
+        .iter_mut()
+        .find(|(_key, validator)| validator.stake_count > 0)
+        .expect("There must be non empty validator");
+    let validator_vote = *validator_vote;
+
+    // delegation went down since the last update
+    let test_stake = marinade_reflection::accounts_builder::StakeBuilder {
+        address: random_pubkey(&mut rng),
+        voter_pubkey: validator_vote,
+        stake: 10 * LAMPORTS_PER_SOL + 7021,
+        is_active: true,
+        last_update_delegated_lamports: 11 * LAMPORTS_PER_SOL + 2343,
+        last_update_epoch: 0,
+        extra_balance: 0,
+    };
+    let slashed = test_stake.last_update_delegated_lamports - test_stake.stake;
+    validator.stake_count += 1;
+    validator.total_slashed += slashed;
+
+    let initial_reflection = builder.build(&rent);
+    let mut account_builder = AccountsBuilder::new_random(&initial_reflection, &mut rng, 0, 0);
+    let clock = Clock::default();
+
+    let validator_identity = random_pubkey(&mut rng);
+    account_builder.add_validator(
+        validator_vote,
+        VoteState::new(
+            &VoteInit {
+                node_pubkey: validator_identity,
+                authorized_voter: validator_identity,
+                ..VoteInit::default()
+            },
+            &clock,
+        ),
+    )?;
+    account_builder.add_stake(test_stake.clone())?;
+    account_builder.random_fill(&mut rng);
+
+    let mut test =
+        IntegrationTest::start_synthetic(&account_builder, HashMap::new(), &mut rng).await?;
+
+    let stake_index = account_builder
+        .stakes
+        .iter()
+        .position(|stake| stake.address == test_stake.address)
+        .unwrap() as u32;
+    let validator_index = account_builder
+        .validators
+        .iter()
+        .position(|validator| validator.vote_address == validator_vote)
+        .unwrap() as u32;
+    test.builder.update_active(
+        &test.state,
+        test_stake.address,
+        stake_index,
+        validator_index,
+    );
+    test.execute().await;
+
+    let mut expected_reflection = initial_reflection.clone();
+    expected_reflection.available_reserve_balance = expected_reflection.actual_reserve_balance
+        - rent.minimum_balance(spl_token::state::Account::LEN);
+    expected_reflection.msol_supply = initial_reflection.actual_msol_supply;
+    let validator_reflection = expected_reflection
+        .validators
+        .get_mut(&validator_vote)
+        .expect("Validator not found");
+    // the loss is taken from the validator balance
+    validator_reflection.active_balance -= slashed;
+    validator_reflection.total_slashed -= slashed;
+
+    assert_json_eq!(test.reflection, expected_reflection);
+
+    // and shared by all mSOL holders
+    let price_without_slashing = proportional(
+        State::PRICE_DENOMINATOR,
+        test.state.total_lamports_under_control() + slashed,
+        test.state.msol_supply,
+    )
+    .unwrap();
+    assert!(test.state.msol_price < price_without_slashing);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_update_active_batch() -> anyhow::Result<()> {
+    use rand_chacha::rand_core::SeedableRng;
+    let mut rng = ChaChaRng::from_seed([
+        72, 13, 201, 158, 36, 247, 90, 119, 4, 183, 66, 225, 140, 31, 210, 57, 163, 98, 12, 239,
+        80, 175, 23, 146, 54, 197, 110, 8, 221, 69, 132, 251,
+    ]);
+    let rent = Rent::default(); // must be the equal to actual rent sysvar in blockchain. Will be checked later
+
+    let mut builder = marinade_reflection::builder::Builder::default();
+    builder.set_reward_fee(Fee::from_basis_points(0)); // Do not change mSOL supply for simplicity
+    let params = RandomBuildParams::pick(&mut builder, &mut rng);
+    builder.random_fill(&mut rng, &params, &rent); // basic state
+
+    // Validator for test
+    let (validator_vote, validator) = builder
+        .validators
+        .iter_mut()
+        .find(|(_key, validator)| validator.stake_count > 0)
+        .expect("There must be non empty validator");
+    let validator_vote = *validator_vote;
+
+    let extra_balance = 0; // for not changing mSOL supply
+    let test_stakes: Vec<marinade_reflection::accounts_builder::StakeBuilder> = (0..3)
+        .map(|i| marinade_reflection::accounts_builder::StakeBuilder {
+            address: random_pubkey(&mut rng),
+            voter_pubkey: validator_vote,
+            stake: (12 + i) * LAMPORTS_PER_SOL + 10267,
+            is_active: true,
+            last_update_delegated_lamports: (11 + i) * LAMPORTS_PER_SOL + 2343,
+            last_update_epoch: 0,
+            extra_balance,
+        })
+        .collect();
+    // Add test stakes
+    for test_stake in &test_stakes {
+        validator.stake_count += 1;
+        validator.total_delegated_delta +=
+            test_stake.stake - test_stake.last_update_delegated_lamports;
+        validator.total_extra_balance += extra_balance;
+    }
+
+    let initial_reflection = builder.build(&rent);
+
+    let mut account_builder = AccountsBuilder::new_random(&initial_reflection, &mut rng, 0, 0);
+    let clock = Clock::default(); // TODO?
+
+    // Install test validator
+    let validator_identity = random_pubkey(&mut rng);
+    account_builder.add_validator(
+        validator_vote,
+        VoteState::new(
+            &VoteInit {
+                node_pubkey: validator_identity,
+                authorized_voter: validator_identity,
+                ..VoteInit::default()
+            },
+            &clock,
+        ),
+    )?;
+
+    for test_stake in &test_stakes {
+        account_builder.add_stake(test_stake.clone())?;
+    }
+
+    // Install all other validators
+    account_builder.random_fill(&mut rng);
+
+    let mut test =
+        IntegrationTest::start_synthetic(&account_builder, HashMap::new(), &mut rng).await?;
+
+    let stake_indexes: Vec<u32> = test_stakes
+        .iter()
+        .map(|test_stake| {
+            account_builder
+                .stakes
+                .iter()
+                .position(|stake| stake.address == test_stake.address)
+                .unwrap() as u32
+        })
+        .collect();
+    let validator_index = account_builder
+        .validators
+        .iter()
+        .position(|validator| validator.vote_address == validator_vote)
+        .unwrap() as u32;
+    let stake_accounts: Vec<Pubkey> = test_stakes
+        .iter()
+        .map(|test_stake| test_stake.address)
+        .collect();
+
+    // every stake must have its validator index
+    test.builder.update_active_batch(
+        &test.state,
+        &stake_accounts,
+        stake_indexes.clone(),
+        vec![validator_index; test_stakes.len() - 1],
+    );
+    assert!(test.try_execute().await.is_err());
+
+    // every stake index must have its stake account
+    test.builder.update_active_batch(
+        &test.state,
+        &stake_accounts[..stake_accounts.len() - 1],
+        stake_indexes.clone(),
+        vec![validator_index; test_stakes.len()],
+    );
+    assert!(test.try_execute().await.is_err());
+
+    test.builder.update_active_batch(
+        &test.state,
+        &stake_accounts,
+        stake_indexes,
+        vec![validator_index; test_stakes.len()],
+    );
+    test.execute().await;
+
+    let mut expected_reflection = initial_reflection.clone();
+    // And update available_reserve_balance field
+    expected_reflection.available_reserve_balance = expected_reflection.actual_reserve_balance
+        - rent.minimum_balance(spl_token::state::Account::LEN);
+    // It must update msol_supply
+    expected_reflection.msol_supply = initial_reflection.actual_msol_supply;
+    let validator_reflection = expected_reflection
+        .validators
+        .get_mut(&validator_vote)
+        .expect("Validator not found");
+    for test_stake in &test_stakes {
+        // count acount delegation instead of last update
+        validator_reflection.active_balance += test_stake.stake;
+        validator_reflection.active_balance -= test_stake.last_update_delegated_lamports;
+        validator_reflection.total_delegated_delta += test_stake.last_update_delegated_lamports;
+        validator_reflection.total_delegated_delta -= test_stake.stake;
+    }
+
+    assert_json_eq!(test.reflection, expected_reflection);
+
+    Ok(())
+}
+
+#[test(tokio::test)]
+async fn test_update_active_epoch_rewards() -> anyhow::Result<()> {
+    use rand_chacha::rand_core::SeedableRng;
+    let mut rng = ChaChaRng::from_seed([
+        9, 244, 61, 130, 187, 22, 105, 76, 218, 3, 159, 40, 121, 234, 88, 17, 196, 51, 142, 69,
+        230, 114, 7, 183, 35, 250, 96, 128, 62, 171, 14, 205,
+    ]);
+    let rent = Rent::default(); // must be the equal to actual rent sysvar in blockchain. Will be checked later
+
+    let mut builder = marinade_reflection::builder::Builder::default();
+    builder.set_reward_fee(Fee::from_basis_points(0)); // Do not change mSOL supply for simplicity
+    let params = RandomBuildParams::pick(&mut builder, &mut rng);
+    builder.random_fill(&mut rng, &params, &rent); // basic state
+
+    // Validator for test
+    let (validator_vote, validator) = builder
+        .validators
+        .iter_mut()
+        .find(|(_key, validator)| validator.stake_count > 0)
+        .expect("There must be non empty validator");
+    let validator_vote = *validator_vote;
+
+    let test_stake = marinade_reflection::accounts_builder::StakeBuilder {
+        address: random_pubkey(&mut rng),
+        voter_pubkey: validator_vote,
+        stake: 12 * LAMPORTS_PER_SOL + 10267,
+        is_active: true,
+        last_update_delegated_lamports: 11 * LAMPORTS_PER_SOL + 2343,
+        last_update_epoch: 0,
+        extra_balance: 0,
+    };
+    validator.stake_count += 1;
+    validator.total_delegated_delta += test_stake.stake - test_stake.last_update_delegated_lamports;
+
+    let initial_reflection = builder.build(&rent);
+    let mut account_builder = AccountsBuilder::new_random(&initial_reflection, &mut rng, 0, 0);
+    let clock = Clock::default();
+
+    let validator_identity = random_pubkey(&mut rng);
+    account_builder.add_validator(
+        validator_vote,
+        VoteState::new(
+            &VoteInit {
+                node_pubkey: validator_identity,
+                authorized_voter: validator_identity,
+                ..VoteInit::default()
+            },
+            &clock,
+        ),
+    )?;
+    account_builder.add_stake(test_stake.clone())?;
+    account_builder.random_fill(&mut rng);
+
+    let mut test =
+        IntegrationTest::start_synthetic(&account_builder, HashMap::new(), &mut rng).await?;
+
+    let stake_index = account_builder
+        .stakes
+        .iter()
+        .position(|stake| stake.address == test_stake.address)
+        .unwrap() as u32;
+    let validator_index = account_builder
+        .validators
+        .iter()
+        .position(|validator| validator.vote_address == validator_vote)
+        .unwrap() as u32;
+
+    // funding the epoch rewards address in advance must not prevent the creation
+    let epoch_rewards_address = EpochRewards::find_address(&test.state.key).0;
+    test.builder
+        .add_instruction(
+            system_instruction::transfer(&test.fee_payer(), &epoch_rewards_address, 1),
+            format!("fund epoch rewards {}", epoch_rewards_address),
+        )
+        .unwrap();
+    test.execute().await;
+
+    test.builder
+        .create_epoch_rewards(&test.state, test.fee_payer_signer());
+    test.execute().await;
+
+    // the second update of the epoch goes to the same record
+    for _ in 0..2 {
+        test.builder.update_active(
+            &test.state,
+            test_stake.address,
+            stake_index,
+            validator_index,
+        );
+    }
+    test.execute().await;
+
+    let epoch_rewards_data = test
+        .context
+        .banks_client
+        .get_account(EpochRewards::find_address(&test.state.key).0)
+        .await?
+        .unwrap()
+        .data;
+    let epoch_rewards = EpochRewards::load(&epoch_rewards_data, &test.state.key)?;
+    assert_eq!(epoch_rewards.record_count, 1);
+    let records = epoch_rewards.records(&epoch_rewards_data);
+    assert_eq!(
+        records,
+        vec![EpochRewardRecord {
+            epoch: test.get_clock().await.epoch,
+            rewards: test_stake.stake - test_stake.last_update_delegated_lamports,
+            msol_fees: 0,
+            msol_price: test.state.msol_price,
+            total_lamports_under_control: test.state.total_lamports_under_control(),
+        }]
+    );
+
+    Ok(())
+}
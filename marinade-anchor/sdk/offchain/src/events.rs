@@ -0,0 +1,174 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use marinade_finance_onchain_sdk::marinade_finance::{self, events::*};
+
+const PROGRAM_LOG: &str = "Program log: ";
+const PROGRAM: &str = "Program ";
+
+/// Any event logged by the marinade program
+pub enum MarinadeEvent {
+    Deposit(DepositEvent),
+    DepositStakeAccount(DepositStakeAccountEvent),
+    PendingStakeDeposit(PendingStakeDepositEvent),
+    LiquidUnstake(LiquidUnstakeEvent),
+    OrderUnstake(OrderUnstakeEvent),
+    Claim(ClaimEvent),
+    AddLiquidity(AddLiquidityEvent),
+    AddLiquidityMsol(AddLiquidityMsolEvent),
+    RemoveLiquidity(RemoveLiquidityEvent),
+    StakeReserve(StakeReserveEvent),
+    DeactivateStake(DeactivateStakeEvent),
+    MergeStakes(MergeStakesEvent),
+    WithdrawStakeAccount(WithdrawStakeAccountEvent),
+    UpdateActive(UpdateActiveEvent),
+    UpdateDeactivated(UpdateDeactivatedEvent),
+    Slashed(SlashedEvent),
+}
+
+fn decode_as<E: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<E> {
+    if data.len() < 8 || data[..8] != E::discriminator() {
+        return None;
+    }
+    E::deserialize(&mut &data[8..]).ok()
+}
+
+/// Decodes a single log line (with or without the "Program log: " prefix).
+/// Returns None for the lines which are not marinade events
+pub fn decode_event(log: &str) -> Option<MarinadeEvent> {
+    let encoded = log.strip_prefix(PROGRAM_LOG).unwrap_or(log);
+    let data = base64::decode(encoded).ok()?;
+    decode_as(&data)
+        .map(MarinadeEvent::Deposit)
+        .or_else(|| decode_as(&data).map(MarinadeEvent::DepositStakeAccount))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::PendingStakeDeposit))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::LiquidUnstake))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::OrderUnstake))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::Claim))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::AddLiquidity))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::AddLiquidityMsol))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::RemoveLiquidity))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::StakeReserve))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::DeactivateStake))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::MergeStakes))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::WithdrawStakeAccount))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::UpdateActive))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::UpdateDeactivated))
+        .or_else(|| decode_as(&data).map(MarinadeEvent::Slashed))
+}
+
+/// Decodes all the marinade events from the transaction log messages.
+/// Only the lines logged by the marinade program itself are decoded,
+/// so other programs can not fake events by logging the same data.
+/// A failed transaction is reverted and has no events
+pub fn decode_events<'a>(logs: impl IntoIterator<Item = &'a String>) -> Vec<MarinadeEvent> {
+    let marinade_id = marinade_finance::ID.to_string();
+    // program ids of the invoke frames, innermost last
+    let mut frames: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        if let Some(message) = log.strip_prefix(PROGRAM_LOG) {
+            if frames.last() == Some(&marinade_id.as_str()) {
+                events.extend(decode_event(message));
+            }
+        } else if let Some(frame) = log.strip_prefix(PROGRAM) {
+            let mut words = frame.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program_id), Some("invoke")) => frames.push(program_id),
+                (Some(_), Some("success")) => {
+                    frames.pop();
+                }
+                (Some(_), Some(status)) if status.starts_with("failed") => return Vec::new(),
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{prelude::Pubkey, AnchorSerialize};
+
+    const OTHER_PROGRAM: &str = "11111111111111111111111111111111";
+
+    fn claim_event_data(lamports: u64) -> String {
+        let event = ClaimEvent {
+            state: Pubkey::new_unique(),
+            ticket: Pubkey::new_unique(),
+            beneficiary: Pubkey::new_unique(),
+            lamports,
+            ticket_lamports_left: 0,
+            msol_price: 0x1_0000_0000,
+            epoch: 10,
+        };
+        let mut data = ClaimEvent::discriminator().to_vec();
+        data.extend(event.try_to_vec().unwrap());
+        base64::encode(data)
+    }
+
+    fn claimed_lamports(logs: &[String]) -> Vec<u64> {
+        decode_events(logs)
+            .into_iter()
+            .map(|event| match event {
+                MarinadeEvent::Claim(claim) => claim.lamports,
+                _ => panic!("expected a claim event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_nested_cpi() {
+        let marinade_id = marinade_finance::ID.to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", OTHER_PROGRAM),
+            format!("Program {} invoke [2]", marinade_id),
+            "Program log: Instruction: Claim".to_string(),
+            format!("Program {} invoke [3]", OTHER_PROGRAM),
+            format!("Program log: {}", claim_event_data(1)),
+            format!("Program {} success", OTHER_PROGRAM),
+            format!("Program log: {}", claim_event_data(2)),
+            format!(
+                "Program {} consumed 20000 of 200000 compute units",
+                marinade_id
+            ),
+            format!("Program {} success", marinade_id),
+            format!("Program log: {}", claim_event_data(3)),
+            format!("Program {} success", OTHER_PROGRAM),
+        ];
+        // only the line logged in the marinade frame
+        assert_eq!(claimed_lamports(&logs), vec![2]);
+    }
+
+    #[test]
+    fn test_decode_ignores_other_programs() {
+        let marinade_id = marinade_finance::ID.to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", OTHER_PROGRAM),
+            format!("Program log: {}", claim_event_data(1)),
+            format!("Program data: {}", claim_event_data(2)),
+            format!("Program {} success", OTHER_PROGRAM),
+            format!("Program {} invoke [1]", marinade_id),
+            format!("Program log: {}", claim_event_data(3)),
+            format!("Program {} success", marinade_id),
+        ];
+        assert_eq!(claimed_lamports(&logs), vec![3]);
+    }
+
+    #[test]
+    fn test_decode_failed_tx() {
+        let marinade_id = marinade_finance::ID.to_string();
+        let logs = vec![
+            format!("Program {} invoke [1]", marinade_id),
+            format!("Program log: {}", claim_event_data(1)),
+            format!("Program {} success", marinade_id),
+            format!("Program {} invoke [1]", marinade_id),
+            format!("Program log: {}", claim_event_data(2)),
+            format!(
+                "Program {} failed: custom program error: 0x1105",
+                marinade_id
+            ),
+        ];
+        // the whole tx is reverted, including the first instruction
+        assert!(claimed_lamports(&logs).is_empty());
+    }
+}
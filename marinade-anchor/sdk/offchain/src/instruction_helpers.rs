@@ -9,7 +9,10 @@ use std::sync::Arc;
 use crate::transaction_builder::TransactionBuilder;
 use log::error;
 use marinade_finance_onchain_sdk::{
-    marinade_finance::{located::Located, *},
+    marinade_finance::{
+        liq_pool::FeeCurve, located::Located, pending_stake_deposit::PendingStakeDepositData,
+        queued_change::ParamsChange, *,
+    },
     *,
 };
 use solana_offchain_common::solana_sdk::{pubkey::Pubkey, signer::Signer};
@@ -26,6 +29,15 @@ pub trait InstructionHelpers {
         lamports: u64,
     );
 
+    fn add_liquidity_msol(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        msol_amount: u64,
+    );
+
     fn add_validator(
         &mut self,
         state: &impl Located<State>,
@@ -60,6 +72,15 @@ pub trait InstructionHelpers {
         lamports: u64,
     );
 
+    fn deposit_with_min_out(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+    );
+
     fn deposit_stake_account(
         &mut self,
         state: &impl Located<State>,
@@ -69,6 +90,7 @@ pub trait InstructionHelpers {
         validator_index: u32,
         validator_vote: Pubkey,
         rent_payer: Arc<dyn Signer>,
+        custodian: Option<Arc<dyn Signer>>,
     );
 
     fn emergency_unstake(
@@ -95,6 +117,16 @@ pub trait InstructionHelpers {
         msol_amount: u64,
     );
 
+    fn liquid_unstake_with_min_out(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        msol_amount: u64,
+        min_lamports_out: u64,
+    );
+
     fn merge_stakes(
         &mut self,
         state: &impl Located<State>,
@@ -115,6 +147,18 @@ pub trait InstructionHelpers {
         tokens: u64,
     );
 
+    fn remove_liquidity_with_ticket(
+        &mut self,
+        state: &impl Located<State>,
+        burn_from: Pubkey,
+        burn_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        transfer_msol_to: Pubkey,
+        tokens: u64,
+        pool_ticket: Pubkey,
+        new_ticket_account: Pubkey,
+    );
+
     fn remove_validator(
         &mut self,
         state: &impl Located<State>,
@@ -130,6 +174,7 @@ pub trait InstructionHelpers {
         min_fee: Fee,
         max_fee: Fee,
         liquidity_target: u64,
+        fee_curve: FeeCurve,
     ) -> Result<(), InstructionError>;
 
     fn config_marinade(
@@ -178,6 +223,21 @@ pub trait InstructionHelpers {
         stake_index: u32,
     );
 
+    fn update_active_batch(
+        &mut self,
+        state: &impl Located<State>,
+        stake_accounts: &[Pubkey],
+        stake_indexes: Vec<u32>,
+        validator_indexes: Vec<u32>,
+    );
+
+    fn update_deactivated_batch(
+        &mut self,
+        state: &impl Located<State>,
+        stake_accounts: &[Pubkey],
+        stake_indexes: Vec<u32>,
+    );
+
     /* TODO:
     fn update_cooling_down(
         &mut self,
@@ -199,6 +259,210 @@ pub trait InstructionHelpers {
     );
 
     fn claim(&mut self, state: &impl Located<State>, ticket_account: Pubkey, beneficiary: Pubkey);
+
+    fn partial_claim(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Pubkey,
+        lamports: u64,
+    );
+
+    fn split_ticket(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        new_ticket_account: Pubkey,
+        new_ticket_lamports: u64,
+    );
+
+    fn change_beneficiary(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        new_beneficiary: Pubkey,
+    );
+
+    fn instant_claim(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+    );
+
+    fn rebalance_liq_pool(
+        &mut self,
+        state: &impl Located<State>,
+        new_ticket_account: Pubkey,
+        msol_amount: u64,
+    );
+
+    fn deposit_directed(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+        validator_index: u32,
+        validator_vote: Pubkey,
+    );
+
+    fn set_validator_stake_bounds(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        index: u32,
+        validator_vote: Pubkey,
+        min_stake: u64,
+        max_stake: u64,
+    ) -> Result<(), InstructionError>;
+
+    fn propose_authority(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        data: ProposeAuthorityData,
+    ) -> Result<(), InstructionError>;
+
+    fn accept_authority(&mut self, state: &impl Located<State>, new_authority: Arc<dyn Signer>);
+
+    fn queue_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        queued_change: Pubkey,
+        change: ParamsChange,
+    ) -> Result<(), InstructionError>;
+
+    fn execute_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        queued_change: Pubkey,
+        rent_payer: Pubkey,
+    );
+
+    fn cancel_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        queued_change: Pubkey,
+        rent_payer: Pubkey,
+    ) -> Result<(), InstructionError>;
+
+    fn withdraw_stake_account(
+        &mut self,
+        state: &impl Located<State>,
+        burn_msol_from: Pubkey,
+        burn_msol_authority: Arc<dyn Signer>,
+        stake_account: Pubkey,
+        split_stake_account: Arc<dyn Signer>,
+        split_stake_rent_payer: Arc<dyn Signer>,
+        stake_index: u32,
+        validator_index: u32,
+        msol_amount: u64,
+    );
+
+    fn deposit_activating_stake_account(
+        &mut self,
+        state: &impl Located<State>,
+        stake_account: Pubkey,
+        stake_authority: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        validator_index: u32,
+        validator_vote: Pubkey,
+        rent_payer: Arc<dyn Signer>,
+        custodian: Option<Arc<dyn Signer>>,
+    );
+
+    fn finish_stake_deposit(
+        &mut self,
+        state: &impl Located<State>,
+        stake_account: Pubkey,
+        rent_payer: Pubkey,
+        mint_to: Pubkey,
+        validator_index: u32,
+    );
+
+    fn deposit_with_referral(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+        referral_state: Pubkey,
+    );
+
+    fn liquid_unstake_with_referral(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        msol_amount: u64,
+        min_lamports_out: u64,
+        referral_state: Pubkey,
+        referral_msol_account: Pubkey,
+    );
+
+    fn create_referral(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        referral_state: Pubkey,
+        partner_account: Pubkey,
+        msol_token_account: Pubkey,
+        referral_code: String,
+        treasury_cut_share: Fee,
+    ) -> Result<(), InstructionError>;
+
+    fn update_referral(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        referral_state: Pubkey,
+        pause: Option<bool>,
+        treasury_cut_share: Option<Fee>,
+    ) -> Result<(), InstructionError>;
+
+    fn grow_list(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        list: Pubkey,
+        new_list: Pubkey,
+        rent_receiver: Pubkey,
+        max_copy_count: u32,
+    ) -> Result<(), InstructionError>;
+
+    fn block_validator(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        validator_vote: Pubkey,
+        rent_payer: Arc<dyn Signer>,
+    ) -> Result<(), InstructionError>;
+
+    fn unblock_validator(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        validator_vote: Pubkey,
+        rent_receiver: Pubkey,
+    ) -> Result<(), InstructionError>;
+
+    fn reclaim_rent(
+        &mut self,
+        state: &impl Located<State>,
+        orphaned_account: Pubkey,
+        validator_vote: Option<Pubkey>,
+    );
+
+    fn create_epoch_rewards(&mut self, state: &impl Located<State>, rent_payer: Arc<dyn Signer>);
 }
 
 impl InstructionHelpers for TransactionBuilder {
@@ -221,6 +485,32 @@ impl InstructionHelpers for TransactionBuilder {
         .unwrap();
     }
 
+    fn add_liquidity_msol(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        msol_amount: u64,
+    ) {
+        let get_msol_from_authority = self.add_signer(get_msol_from_authority);
+        self.add_instruction(
+            add_liquidity_msol(
+                state,
+                get_msol_from,
+                get_msol_from_authority,
+                mint_to,
+                msol_amount,
+            ),
+            format!(
+                "Add {} mSOL lamports liquidity to marinade {}",
+                msol_amount,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
     fn add_validator(
         &mut self,
         state: &impl Located<State>,
@@ -332,6 +622,29 @@ impl InstructionHelpers for TransactionBuilder {
         .unwrap();
     }
 
+    fn deposit_with_min_out(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+    ) {
+        let transfer_from = self.add_signer(transfer_from);
+        self.add_instruction(
+            deposit_with_min_out(state, transfer_from, mint_to, lamports, min_msol_out),
+            format!(
+                "Deposit into marinade {} {} lamports from {} mint to {} at least {} mSOL",
+                state.key(),
+                lamports,
+                transfer_from,
+                mint_to,
+                min_msol_out
+            ),
+        )
+        .unwrap();
+    }
+
     fn deposit_stake_account(
         &mut self,
         state: &impl Located<State>,
@@ -341,9 +654,11 @@ impl InstructionHelpers for TransactionBuilder {
         validator_index: u32,
         validator_vote: Pubkey,
         rent_payer: Arc<dyn Signer>,
+        custodian: Option<Arc<dyn Signer>>,
     ) {
         let stake_authority = self.add_signer(stake_authority);
         let rent_payer = self.add_signer(rent_payer);
+        let custodian = custodian.map(|custodian| self.add_signer(custodian));
         self.add_instruction(
             deposit_stake_account(
                 state,
@@ -353,6 +668,7 @@ impl InstructionHelpers for TransactionBuilder {
                 validator_index,
                 validator_vote,
                 rent_payer,
+                custodian,
             ),
             format!(
                 "Deposit stake account {} into marinade {}",
@@ -428,6 +744,34 @@ impl InstructionHelpers for TransactionBuilder {
         .unwrap();
     }
 
+    fn liquid_unstake_with_min_out(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        msol_amount: u64,
+        min_lamports_out: u64,
+    ) {
+        let get_msol_from_authority = self.add_signer(get_msol_from_authority);
+        self.add_instruction(
+            liquid_unstake_with_min_out(
+                state,
+                get_msol_from,
+                get_msol_from_authority,
+                transfer_sol_to,
+                msol_amount,
+                min_lamports_out,
+            ),
+            format!(
+                "Liquid unstake from marinade {} at least {} lamports",
+                state.key(),
+                min_lamports_out
+            ),
+        )
+        .unwrap();
+    }
+
     fn merge_stakes(
         &mut self,
         state: &impl Located<State>,
@@ -475,21 +819,55 @@ impl InstructionHelpers for TransactionBuilder {
         .unwrap();
     }
 
-    fn remove_validator(
+    fn remove_liquidity_with_ticket(
         &mut self,
         state: &impl Located<State>,
-        validator_manager_authority: Arc<dyn Signer>,
-        index: u32,
-        validator_vote: Pubkey,
-    ) -> Result<(), InstructionError> {
-        if validator_manager_authority.pubkey() != state.as_ref().validator_system.manager_authority
-        {
-            error!(
-                "Validator manager authority not match. Expected {} got {}",
-                state.as_ref().validator_system.manager_authority,
-                validator_manager_authority.pubkey()
-            );
-            return Err(InstructionError::InvalidValidatorManagerAuthority {
+        burn_from: Pubkey,
+        burn_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        transfer_msol_to: Pubkey,
+        tokens: u64,
+        pool_ticket: Pubkey,
+        new_ticket_account: Pubkey,
+    ) {
+        let burn_from_authority = self.add_signer(burn_from_authority);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::remove_liquidity_with_ticket(
+                state,
+                burn_from,
+                burn_from_authority,
+                transfer_sol_to,
+                transfer_msol_to,
+                tokens,
+                pool_ticket,
+                new_ticket_account,
+            ),
+            format!(
+                "Remove {} liquidity from marinade {} splitting pool ticket {} into {}",
+                tokens,
+                state.key(),
+                pool_ticket,
+                new_ticket_account
+            ),
+        )
+        .unwrap();
+    }
+
+    fn remove_validator(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        index: u32,
+        validator_vote: Pubkey,
+    ) -> Result<(), InstructionError> {
+        if validator_manager_authority.pubkey() != state.as_ref().validator_system.manager_authority
+        {
+            error!(
+                "Validator manager authority not match. Expected {} got {}",
+                state.as_ref().validator_system.manager_authority,
+                validator_manager_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidValidatorManagerAuthority {
                 expected: state.as_ref().validator_system.manager_authority,
                 got: validator_manager_authority.pubkey(),
             });
@@ -515,6 +893,7 @@ impl InstructionHelpers for TransactionBuilder {
         min_fee: Fee,
         max_fee: Fee,
         liquidity_target: u64,
+        fee_curve: FeeCurve,
     ) -> Result<(), InstructionError> {
         if admin_authority.pubkey() != state.as_ref().admin_authority {
             error!(
@@ -530,7 +909,7 @@ impl InstructionHelpers for TransactionBuilder {
 
         self.add_signer(admin_authority);
         self.add_instruction(
-            set_lp_params(state, min_fee, max_fee, liquidity_target),
+            set_lp_params(state, min_fee, max_fee, liquidity_target, fee_curve),
             format!("Set lp params to marinade {}", state.key()),
         )
         .unwrap();
@@ -675,6 +1054,41 @@ impl InstructionHelpers for TransactionBuilder {
         .unwrap();
     }
 
+    fn update_active_batch(
+        &mut self,
+        state: &impl Located<State>,
+        stake_accounts: &[Pubkey],
+        stake_indexes: Vec<u32>,
+        validator_indexes: Vec<u32>,
+    ) {
+        self.add_instruction(
+            update_active_batch(state, stake_accounts, stake_indexes, validator_indexes),
+            format!(
+                "Update {} active stakes for marinade {}",
+                stake_accounts.len(),
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    fn update_deactivated_batch(
+        &mut self,
+        state: &impl Located<State>,
+        stake_accounts: &[Pubkey],
+        stake_indexes: Vec<u32>,
+    ) {
+        self.add_instruction(
+            update_deactivated_batch(state, stake_accounts, stake_indexes),
+            format!(
+                "Update {} deactivated stakes for marinade {}",
+                stake_accounts.len(),
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
     /* TODO:
     fn update_cooling_down(
         &mut self,
@@ -730,6 +1144,680 @@ impl InstructionHelpers for TransactionBuilder {
         )
         .unwrap()
     }
+
+    fn partial_claim(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Pubkey,
+        lamports: u64,
+    ) {
+        self.add_instruction(
+            marinade_finance_onchain_sdk::partial_claim(
+                state,
+                ticket_account,
+                beneficiary,
+                lamports,
+            ),
+            format!(
+                "Claim {} lamports from ticket {}",
+                lamports, ticket_account
+            ),
+        )
+        .unwrap()
+    }
+
+    fn split_ticket(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        new_ticket_account: Pubkey,
+        new_ticket_lamports: u64,
+    ) {
+        let beneficiary = self.add_signer(beneficiary);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::split_ticket(
+                state,
+                ticket_account,
+                beneficiary,
+                new_ticket_account,
+                new_ticket_lamports,
+            ),
+            format!(
+                "Split {} lamports from ticket {} into {}",
+                new_ticket_lamports, ticket_account, new_ticket_account
+            ),
+        )
+        .unwrap()
+    }
+
+    fn change_beneficiary(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        new_beneficiary: Pubkey,
+    ) {
+        let beneficiary = self.add_signer(beneficiary);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::change_beneficiary(
+                state,
+                ticket_account,
+                beneficiary,
+                new_beneficiary,
+            ),
+            format!(
+                "Change ticket {} beneficiary to {}",
+                ticket_account, new_beneficiary
+            ),
+        )
+        .unwrap()
+    }
+
+    fn instant_claim(
+        &mut self,
+        state: &impl Located<State>,
+        ticket_account: Pubkey,
+        beneficiary: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+    ) {
+        let beneficiary = self.add_signer(beneficiary);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::instant_claim(
+                state,
+                ticket_account,
+                beneficiary,
+                transfer_sol_to,
+            ),
+            format!(
+                "Instant claim ticket {} from marinade {}",
+                ticket_account,
+                state.key()
+            ),
+        )
+        .unwrap()
+    }
+
+    fn rebalance_liq_pool(
+        &mut self,
+        state: &impl Located<State>,
+        new_ticket_account: Pubkey,
+        msol_amount: u64,
+    ) {
+        self.add_instruction(
+            marinade_finance_onchain_sdk::rebalance_liq_pool(
+                state,
+                new_ticket_account,
+                msol_amount,
+            ),
+            format!(
+                "Rebalance {} mSOL from liq-pool of marinade {}",
+                msol_amount,
+                state.key()
+            ),
+        )
+        .unwrap()
+    }
+
+    fn deposit_directed(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+        validator_index: u32,
+        validator_vote: Pubkey,
+    ) {
+        let transfer_from = self.add_signer(transfer_from);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::deposit_directed(
+                state,
+                transfer_from,
+                mint_to,
+                lamports,
+                min_msol_out,
+                validator_index,
+                validator_vote,
+            ),
+            format!(
+                "Deposit into marinade {} {} lamports from {} mint to {} directed to validator {}",
+                state.key(),
+                lamports,
+                transfer_from,
+                mint_to,
+                validator_vote
+            ),
+        )
+        .unwrap();
+    }
+
+    fn set_validator_stake_bounds(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        index: u32,
+        validator_vote: Pubkey,
+        min_stake: u64,
+        max_stake: u64,
+    ) -> Result<(), InstructionError> {
+        if validator_manager_authority.pubkey() != state.as_ref().validator_system.manager_authority
+        {
+            error!(
+                "Validator manager authority not match. Expected {} got {}",
+                state.as_ref().validator_system.manager_authority,
+                validator_manager_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidValidatorManagerAuthority {
+                expected: state.as_ref().validator_system.manager_authority,
+                got: validator_manager_authority.pubkey(),
+            });
+        }
+        self.add_signer(validator_manager_authority);
+        self.add_instruction(
+            marinade_finance_onchain_sdk::set_validator_stake_bounds(
+                state,
+                index,
+                validator_vote,
+                min_stake,
+                max_stake,
+            ),
+            format!(
+                "Set stake bounds of validator #{} {} from marinade {} to min {} max {}",
+                index,
+                validator_vote,
+                state.key(),
+                min_stake,
+                max_stake
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn propose_authority(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        data: ProposeAuthorityData,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        self.add_instruction(
+            propose_authority(state, data),
+            format!("Propose authority for marinade instance {}", state.key()),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn accept_authority(&mut self, state: &impl Located<State>, new_authority: Arc<dyn Signer>) {
+        let new_authority = self.add_signer(new_authority);
+        self.add_instruction(
+            accept_authority(state, new_authority),
+            format!(
+                "Accept authority {} for marinade instance {}",
+                new_authority,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    /// queued_change account must be created by the fee payer who gets the rent back
+    fn queue_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        queued_change: Pubkey,
+        change: ParamsChange,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        let rent_payer = self.fee_payer();
+        self.add_instruction(
+            queue_params_change(state, queued_change, rent_payer, change),
+            format!(
+                "Queue params change {} for marinade instance {}",
+                queued_change,
+                state.key()
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn execute_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        queued_change: Pubkey,
+        rent_payer: Pubkey,
+    ) {
+        self.add_instruction(
+            execute_params_change(state, queued_change, rent_payer),
+            format!(
+                "Execute params change {} for marinade instance {}",
+                queued_change,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    fn cancel_params_change(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        queued_change: Pubkey,
+        rent_payer: Pubkey,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        self.add_instruction(
+            cancel_params_change(state, queued_change, rent_payer),
+            format!(
+                "Cancel params change {} for marinade instance {}",
+                queued_change,
+                state.key()
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn withdraw_stake_account(
+        &mut self,
+        state: &impl Located<State>,
+        burn_msol_from: Pubkey,
+        burn_msol_authority: Arc<dyn Signer>,
+        stake_account: Pubkey,
+        split_stake_account: Arc<dyn Signer>,
+        split_stake_rent_payer: Arc<dyn Signer>,
+        stake_index: u32,
+        validator_index: u32,
+        msol_amount: u64,
+    ) {
+        let burn_msol_authority = self.add_signer(burn_msol_authority);
+        let split_stake_account = self.add_signer(split_stake_account);
+        let split_stake_rent_payer = self.add_signer(split_stake_rent_payer);
+        self.add_instruction(
+            withdraw_stake_account(
+                state,
+                burn_msol_from,
+                burn_msol_authority,
+                stake_account,
+                split_stake_account,
+                split_stake_rent_payer,
+                stake_index,
+                validator_index,
+                msol_amount,
+            ),
+            format!(
+                "Withdraw {} mSOL as stake {} split from {} of marinade {}",
+                msol_amount,
+                split_stake_account,
+                stake_account,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    fn deposit_activating_stake_account(
+        &mut self,
+        state: &impl Located<State>,
+        stake_account: Pubkey,
+        stake_authority: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        validator_index: u32,
+        validator_vote: Pubkey,
+        rent_payer: Arc<dyn Signer>,
+        custodian: Option<Arc<dyn Signer>>,
+    ) {
+        let stake_authority = self.add_signer(stake_authority);
+        let rent_payer = self.add_signer(rent_payer);
+        let custodian = custodian.map(|custodian| self.add_signer(custodian));
+        self.add_instruction(
+            deposit_activating_stake_account(
+                state,
+                stake_account,
+                stake_authority,
+                mint_to,
+                validator_index,
+                validator_vote,
+                rent_payer,
+                custodian,
+            ),
+            format!(
+                "Deposit activating stake account {} into marinade {} pending in {}",
+                stake_account,
+                state.key(),
+                PendingStakeDepositData::find_address(&state.key(), &stake_account).0
+            ),
+        )
+        .unwrap();
+    }
+
+    fn finish_stake_deposit(
+        &mut self,
+        state: &impl Located<State>,
+        stake_account: Pubkey,
+        rent_payer: Pubkey,
+        mint_to: Pubkey,
+        validator_index: u32,
+    ) {
+        self.add_instruction(
+            finish_stake_deposit(state, stake_account, rent_payer, mint_to, validator_index),
+            format!(
+                "Finish deposit of stake account {} into marinade {}",
+                stake_account,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    fn deposit_with_referral(
+        &mut self,
+        state: &impl Located<State>,
+        transfer_from: Arc<dyn Signer>,
+        mint_to: Pubkey,
+        lamports: u64,
+        min_msol_out: u64,
+        referral_state: Pubkey,
+    ) {
+        let transfer_from = self.add_signer(transfer_from);
+        self.add_instruction(
+            deposit_with_referral(
+                state,
+                transfer_from,
+                mint_to,
+                lamports,
+                min_msol_out,
+                referral_state,
+            ),
+            format!(
+                "Deposit into marinade {} {} lamports from {} mint to {} with referral {}",
+                state.key(),
+                lamports,
+                transfer_from,
+                mint_to,
+                referral_state
+            ),
+        )
+        .unwrap();
+    }
+
+    fn liquid_unstake_with_referral(
+        &mut self,
+        state: &impl Located<State>,
+        get_msol_from: Pubkey,
+        get_msol_from_authority: Arc<dyn Signer>,
+        transfer_sol_to: Pubkey,
+        msol_amount: u64,
+        min_lamports_out: u64,
+        referral_state: Pubkey,
+        referral_msol_account: Pubkey,
+    ) {
+        let get_msol_from_authority = self.add_signer(get_msol_from_authority);
+        self.add_instruction(
+            liquid_unstake_with_referral(
+                state,
+                get_msol_from,
+                get_msol_from_authority,
+                transfer_sol_to,
+                msol_amount,
+                min_lamports_out,
+                referral_state,
+                referral_msol_account,
+            ),
+            format!(
+                "Liquid unstake from marinade {} with referral {}",
+                state.key(),
+                referral_state
+            ),
+        )
+        .unwrap();
+    }
+
+    fn create_referral(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        referral_state: Pubkey,
+        partner_account: Pubkey,
+        msol_token_account: Pubkey,
+        referral_code: String,
+        treasury_cut_share: Fee,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        let description = format!(
+            "Create referral {} ({}) for partner {} of marinade instance {}",
+            referral_state,
+            referral_code,
+            partner_account,
+            state.key()
+        );
+        self.add_instruction(
+            create_referral(
+                state,
+                referral_state,
+                partner_account,
+                msol_token_account,
+                referral_code,
+                treasury_cut_share,
+            ),
+            description,
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn update_referral(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        referral_state: Pubkey,
+        pause: Option<bool>,
+        treasury_cut_share: Option<Fee>,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        self.add_instruction(
+            update_referral(state, referral_state, pause, treasury_cut_share),
+            format!(
+                "Update referral {} of marinade instance {}",
+                referral_state,
+                state.key()
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn grow_list(
+        &mut self,
+        state: &impl Located<State>,
+        admin_authority: Arc<dyn Signer>,
+        list: Pubkey,
+        new_list: Pubkey,
+        rent_receiver: Pubkey,
+        max_copy_count: u32,
+    ) -> Result<(), InstructionError> {
+        if admin_authority.pubkey() != state.as_ref().admin_authority {
+            error!(
+                "Invalid admin authority. Expected {} got {}",
+                state.as_ref().admin_authority,
+                admin_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidAdminAuthority {
+                expected: state.as_ref().admin_authority,
+                got: admin_authority.pubkey(),
+            });
+        }
+
+        self.add_signer(admin_authority);
+        self.add_instruction(
+            grow_list(state, list, new_list, rent_receiver, max_copy_count),
+            format!(
+                "Move list {} of marinade instance {} into {} ({} items max)",
+                list,
+                state.key(),
+                new_list,
+                max_copy_count
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn block_validator(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        validator_vote: Pubkey,
+        rent_payer: Arc<dyn Signer>,
+    ) -> Result<(), InstructionError> {
+        if validator_manager_authority.pubkey() != state.as_ref().validator_system.manager_authority
+        {
+            error!(
+                "Validator manager authority not match. Expected {} got {}",
+                state.as_ref().validator_system.manager_authority,
+                validator_manager_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidValidatorManagerAuthority {
+                expected: state.as_ref().validator_system.manager_authority,
+                got: validator_manager_authority.pubkey(),
+            });
+        }
+        self.add_signer(validator_manager_authority);
+        let rent_payer = self.add_signer(rent_payer);
+        self.add_instruction(
+            block_validator(state, validator_vote, rent_payer),
+            format!(
+                "Block validator {} in marinade {}",
+                validator_vote,
+                state.key(),
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn unblock_validator(
+        &mut self,
+        state: &impl Located<State>,
+        validator_manager_authority: Arc<dyn Signer>,
+        validator_vote: Pubkey,
+        rent_receiver: Pubkey,
+    ) -> Result<(), InstructionError> {
+        if validator_manager_authority.pubkey() != state.as_ref().validator_system.manager_authority
+        {
+            error!(
+                "Validator manager authority not match. Expected {} got {}",
+                state.as_ref().validator_system.manager_authority,
+                validator_manager_authority.pubkey()
+            );
+            return Err(InstructionError::InvalidValidatorManagerAuthority {
+                expected: state.as_ref().validator_system.manager_authority,
+                got: validator_manager_authority.pubkey(),
+            });
+        }
+        self.add_signer(validator_manager_authority);
+        self.add_instruction(
+            unblock_validator(state, validator_vote, rent_receiver),
+            format!(
+                "Unblock validator {} in marinade {}",
+                validator_vote,
+                state.key(),
+            ),
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    fn reclaim_rent(
+        &mut self,
+        state: &impl Located<State>,
+        orphaned_account: Pubkey,
+        validator_vote: Option<Pubkey>,
+    ) {
+        self.add_instruction(
+            reclaim_rent(state, orphaned_account, validator_vote),
+            format!(
+                "Reclaim rent of {} to marinade {} operational account",
+                orphaned_account,
+                state.key()
+            ),
+        )
+        .unwrap();
+    }
+
+    fn create_epoch_rewards(&mut self, state: &impl Located<State>, rent_payer: Arc<dyn Signer>) {
+        let rent_payer = self.add_signer(rent_payer);
+        self.add_instruction(
+            create_epoch_rewards(state, rent_payer),
+            format!("Create epoch rewards for marinade {}", state.key()),
+        )
+        .unwrap();
+    }
 }
 
 #[derive(Debug, Clone, Error)]
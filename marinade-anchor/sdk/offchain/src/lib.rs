@@ -1,5 +1,6 @@
 #![cfg_attr(not(debug_assertions), deny(warnings))]
 
+pub mod events;
 pub mod instruction_helpers;
 
 use std::ops::{Deref, DerefMut};
@@ -5,7 +5,7 @@ pub use ::marinade_finance; // reexport contract crate
 
 use ::marinade_finance::*;
 use anchor_lang::solana_program::{
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     stake, system_program,
     sysvar::{clock, epoch_schedule, rent, stake_history},
@@ -17,11 +17,14 @@ use anchor_lang::solana_program::{
 use anchor_lang::{InstructionData, ToAccountMetas};
 use anchor_spl::token;
 use marinade_finance::{
-    liq_pool::{LiqPool, LiqPoolHelpers},
+    epoch_rewards::EpochRewards,
+    liq_pool::{FeeCurve, LiqPool, LiqPoolHelpers},
     located::Located,
+    pending_stake_deposit::PendingStakeDepositData,
+    queued_change::ParamsChange,
     stake_system::StakeSystemHelpers,
     state::StateHelpers,
-    validator_system::ValidatorRecord,
+    validator_system::{ValidatorRecord, ValidatorSystem},
 };
 
 pub fn add_liquidity(
@@ -53,6 +56,35 @@ pub fn add_liquidity(
     }
 }
 
+pub fn add_liquidity_msol(
+    state: &impl Located<State>,
+    get_msol_from: Pubkey,
+    get_msol_from_authority: Pubkey,
+    mint_to: Pubkey,
+    msol_amount: u64,
+) -> Instruction {
+    let accounts = accounts::AddLiquidityMsol {
+        state: state.key(),
+        lp_mint: state.as_ref().liq_pool.lp_mint,
+        lp_mint_authority: state.lp_mint_authority(),
+        liq_pool_msol_leg: state.as_ref().liq_pool.msol_leg,
+        liq_pool_sol_leg_pda: state.liq_pool_sol_leg_address(),
+        get_msol_from,
+        get_msol_from_authority,
+        mint_to,
+        token_program: token::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::AddLiquidityMsol { msol_amount };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
 pub fn add_validator(
     state: &impl Located<State>,
     validator_vote: Pubkey,
@@ -65,6 +97,7 @@ pub fn add_validator(
         validator_list: *state.as_ref().validator_system.validator_list_address(),
         validator_vote,
         duplication_flag: ValidatorRecord::find_duplication_flag(&state.key(), &validator_vote).0,
+        blocked_flag: ValidatorSystem::find_blocked_flag(&state.key(), &validator_vote).0,
         rent_payer,
         clock: clock::ID,
         rent: rent::ID,
@@ -167,6 +200,23 @@ pub fn deposit(
     }
 }
 
+/// deposit failing when it mints less than min_msol_out
+pub fn deposit_with_min_out(
+    state: &impl Located<State>,
+    transfer_from: Pubkey,
+    mint_to: Pubkey,
+    lamports: u64,
+    min_msol_out: u64,
+) -> Instruction {
+    let mut instruction = deposit(state, transfer_from, mint_to, lamports);
+    instruction.data = instruction::DepositWithMinOut {
+        lamports,
+        min_msol_out,
+    }
+    .data();
+    instruction
+}
+
 pub fn deposit_stake_account(
     state: &impl Located<State>,
     stake_account: Pubkey,
@@ -175,6 +225,7 @@ pub fn deposit_stake_account(
     validator_index: u32,
     validator_vote: Pubkey,
     rent_payer: Pubkey,
+    custodian: Option<Pubkey>,
 ) -> Instruction {
     let accounts = accounts::DepositStakeAccount {
         state: state.key(),
@@ -182,13 +233,18 @@ pub fn deposit_stake_account(
         stake_list: *state.as_ref().stake_system.stake_list_address(),
         stake_account,
         stake_authority,
+        custodian: custodian.unwrap_or(stake_authority),
         duplication_flag: ValidatorRecord::find_duplication_flag(&state.key(), &validator_vote).0,
+        blocked_flag: ValidatorSystem::find_blocked_flag(&state.key(), &validator_vote).0,
+        validator_vote,
         rent_payer,
         msol_mint: state.as_ref().msol_mint,
         mint_to,
         msol_mint_authority: state.msol_mint_authority(),
+        reserve_pda: state.reserve_address(),
         clock: clock::id(),
         rent: rent::id(),
+        stake_history: stake_history::ID,
         system_program: system_program::ID,
         token_program: token::ID,
         stake_program: stake::program::ID,
@@ -358,6 +414,30 @@ pub fn liquid_unstake(
     }
 }
 
+/// liquid unstake failing when it pays less than min_lamports_out
+pub fn liquid_unstake_with_min_out(
+    state: &impl Located<State>,
+    get_msol_from: Pubkey,
+    get_msol_from_authority: Pubkey,
+    transfer_sol_to: Pubkey,
+    msol_amount: u64,
+    min_lamports_out: u64,
+) -> Instruction {
+    let mut instruction = liquid_unstake(
+        state,
+        get_msol_from,
+        get_msol_from_authority,
+        transfer_sol_to,
+        msol_amount,
+    );
+    instruction.data = instruction::LiquidUnstakeWithMinOut {
+        msol_amount,
+        min_lamports_out,
+    }
+    .data();
+    instruction
+}
+
 pub fn merge_stakes(
     state: &impl Located<State>,
     destination_stake: Pubkey,
@@ -457,6 +537,35 @@ pub fn remove_liquidity(
     }
 }
 
+/// remove liquidity getting the LP share of the tickets receivable as a split of pool_ticket
+/// (owned by the SOL leg) into new_ticket_account (uninitialized, rent exempt)
+pub fn remove_liquidity_with_ticket(
+    state: &impl Located<State>,
+    burn_from: Pubkey,
+    burn_from_authority: Pubkey,
+    transfer_sol_to: Pubkey,
+    transfer_msol_to: Pubkey,
+    tokens: u64,
+    pool_ticket: Pubkey,
+    new_ticket_account: Pubkey,
+) -> Instruction {
+    let mut instruction = remove_liquidity(
+        state,
+        burn_from,
+        burn_from_authority,
+        transfer_sol_to,
+        transfer_msol_to,
+        tokens,
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new(pool_ticket, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new(new_ticket_account, false));
+    instruction
+}
+
 pub fn remove_validator(
     state: &impl Located<State>,
     index: u32,
@@ -488,6 +597,7 @@ pub fn set_lp_params(
     min_fee: Fee,
     max_fee: Fee,
     liquidity_target: u64,
+    fee_curve: FeeCurve,
 ) -> Instruction {
     let accounts = accounts::SetLpParams {
         state: state.key(),
@@ -499,6 +609,7 @@ pub fn set_lp_params(
         min_fee,
         max_fee,
         liquidity_target,
+        fee_curve,
     };
 
     Instruction {
@@ -619,6 +730,7 @@ pub fn update_active(
             treasury_msol_account: state.as_ref().treasury_msol_account,
             token_program: token::ID,
             stake_program: stake::program::ID,
+            epoch_rewards: EpochRewards::find_address(&state.key()).0,
         },
 
         validator_list: *state.as_ref().validator_system.validator_list_address(),
@@ -656,6 +768,7 @@ pub fn update_deactivated(
             treasury_msol_account: state.as_ref().treasury_msol_account,
             token_program: token::ID,
             stake_program: stake::program::ID,
+            epoch_rewards: EpochRewards::find_address(&state.key()).0,
         },
         operational_sol_account: state.as_ref().operational_sol_account,
         system_program: system_program::ID,
@@ -671,6 +784,43 @@ pub fn update_deactivated(
     }
 }
 
+/// update_active of many stake accounts, stake_indexes[i] is the index of stake_accounts[i]
+pub fn update_active_batch(
+    state: &impl Located<State>,
+    stake_accounts: &[Pubkey],
+    stake_indexes: Vec<u32>,
+    validator_indexes: Vec<u32>,
+) -> Instruction {
+    let mut instruction = update_active(state, stake_accounts[0], 0, 0);
+    instruction.data = instruction::UpdateActiveBatch {
+        stake_indexes,
+        validator_indexes,
+    }
+    .data();
+    for stake_account in &stake_accounts[1..] {
+        instruction
+            .accounts
+            .push(AccountMeta::new(*stake_account, false));
+    }
+    instruction
+}
+
+/// update_deactivated of many stake accounts, stake_indexes[i] is the index of stake_accounts[i]
+pub fn update_deactivated_batch(
+    state: &impl Located<State>,
+    stake_accounts: &[Pubkey],
+    stake_indexes: Vec<u32>,
+) -> Instruction {
+    let mut instruction = update_deactivated(state, stake_accounts[0], 0);
+    instruction.data = instruction::UpdateDeactivatedBatch { stake_indexes }.data();
+    for stake_account in &stake_accounts[1..] {
+        instruction
+            .accounts
+            .push(AccountMeta::new(*stake_account, false));
+    }
+    instruction
+}
+
 /* TODO:
 pub fn update_cooling_down(
     state: &impl Located<State>,
@@ -731,3 +881,639 @@ pub fn claim(
         data: data.data(),
     }
 }
+
+pub fn partial_claim(
+    state: &impl Located<State>,
+    ticket_account: Pubkey,
+    transfer_sol_to: Pubkey,
+    lamports: u64,
+) -> Instruction {
+    let accounts = accounts::Claim {
+        state: state.key(),
+        reserve_pda: state.reserve_address(),
+        ticket_account,
+        transfer_sol_to,
+        system_program: system_program::ID,
+        clock: clock::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::PartialClaim { lamports };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn split_ticket(
+    state: &impl Located<State>,
+    ticket_account: Pubkey,
+    beneficiary: Pubkey,
+    new_ticket_account: Pubkey,
+    new_ticket_lamports: u64,
+) -> Instruction {
+    let accounts = accounts::SplitTicket {
+        state: state.key(),
+        ticket_account,
+        beneficiary,
+        new_ticket_account,
+        rent: rent::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::SplitTicket {
+        new_ticket_lamports,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn change_beneficiary(
+    state: &impl Located<State>,
+    ticket_account: Pubkey,
+    beneficiary: Pubkey,
+    new_beneficiary: Pubkey,
+) -> Instruction {
+    let accounts = accounts::ChangeBeneficiary {
+        state: state.key(),
+        ticket_account,
+        beneficiary,
+        new_beneficiary,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::ChangeBeneficiary {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn instant_claim(
+    state: &impl Located<State>,
+    ticket_account: Pubkey,
+    beneficiary: Pubkey,
+    transfer_sol_to: Pubkey,
+) -> Instruction {
+    let accounts = accounts::InstantClaim {
+        state: state.key(),
+        liq_pool_sol_leg_pda: state.liq_pool_sol_leg_address(),
+        ticket_account,
+        beneficiary,
+        transfer_sol_to,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::InstantClaim {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn rebalance_liq_pool(
+    state: &impl Located<State>,
+    new_ticket_account: Pubkey,
+    msol_amount: u64,
+) -> Instruction {
+    let accounts = accounts::RebalanceLiqPool {
+        state: state.key(),
+        msol_mint: state.as_ref().msol_mint,
+        liq_pool_sol_leg_pda: state.liq_pool_sol_leg_address(),
+        liq_pool_msol_leg: state.as_ref().liq_pool.msol_leg,
+        liq_pool_msol_leg_authority: state.liq_pool_msol_leg_authority(),
+        new_ticket_account,
+        clock: clock::ID,
+        rent: rent::ID,
+        token_program: token::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::RebalanceLiqPool { msol_amount };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn deposit_directed(
+    state: &impl Located<State>,
+    transfer_from: Pubkey,
+    mint_to: Pubkey,
+    lamports: u64,
+    min_msol_out: u64,
+    validator_index: u32,
+    validator_vote: Pubkey,
+) -> Instruction {
+    let accounts = accounts::DepositDirected {
+        deposit: accounts::Deposit {
+            state: state.key(),
+            msol_mint: state.as_ref().msol_mint,
+            liq_pool_sol_leg_pda: state.liq_pool_sol_leg_address(),
+            liq_pool_msol_leg: state.as_ref().liq_pool.msol_leg,
+            liq_pool_msol_leg_authority: state.liq_pool_msol_leg_authority(),
+            reserve_pda: state.reserve_address(),
+            transfer_from,
+            mint_to,
+            msol_mint_authority: state.msol_mint_authority(),
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        validator_list: *state.as_ref().validator_system.validator_list_address(),
+        validator_vote,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::DepositDirected {
+        lamports,
+        min_msol_out,
+        validator_index,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn set_validator_stake_bounds(
+    state: &impl Located<State>,
+    index: u32,
+    validator_vote: Pubkey,
+    min_stake: u64,
+    max_stake: u64,
+) -> Instruction {
+    let accounts = accounts::SetValidatorStakeBounds {
+        state: state.key(),
+        manager_authority: state.as_ref().validator_system.manager_authority,
+        validator_list: *state.as_ref().validator_system.validator_list_address(),
+    }
+    .to_account_metas(None);
+
+    let data = instruction::SetValidatorStakeBounds {
+        index,
+        validator_vote,
+        min_stake,
+        max_stake,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn propose_authority(state: &impl Located<State>, data: ProposeAuthorityData) -> Instruction {
+    let accounts = accounts::ProposeAuthority {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::ProposeAuthority { data };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn accept_authority(state: &impl Located<State>, new_authority: Pubkey) -> Instruction {
+    let accounts = accounts::AcceptAuthority {
+        state: state.key(),
+        new_authority,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::AcceptAuthority {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn queue_params_change(
+    state: &impl Located<State>,
+    queued_change: Pubkey,
+    rent_payer: Pubkey,
+    change: ParamsChange,
+) -> Instruction {
+    let accounts = accounts::QueueParamsChange {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+        queued_change,
+        rent_payer,
+        clock: clock::ID,
+        rent: rent::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::QueueParamsChange { change };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn execute_params_change(
+    state: &impl Located<State>,
+    queued_change: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let accounts = accounts::ExecuteParamsChange {
+        state: state.key(),
+        queued_change,
+        rent_payer,
+        clock: clock::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::ExecuteParamsChange {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn cancel_params_change(
+    state: &impl Located<State>,
+    queued_change: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let accounts = accounts::CancelParamsChange {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+        queued_change,
+        rent_payer,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::CancelParamsChange {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn withdraw_stake_account(
+    state: &impl Located<State>,
+    burn_msol_from: Pubkey,
+    burn_msol_authority: Pubkey,
+    stake_account: Pubkey,
+    split_stake_account: Pubkey,
+    split_stake_rent_payer: Pubkey,
+    stake_index: u32,
+    validator_index: u32,
+    msol_amount: u64,
+) -> Instruction {
+    let accounts = accounts::WithdrawStakeAccount {
+        state: state.key(),
+        msol_mint: state.as_ref().msol_mint,
+        burn_msol_from,
+        burn_msol_authority,
+        treasury_msol_account: state.as_ref().treasury_msol_account,
+        validator_list: *state.as_ref().validator_system.validator_list_address(),
+        stake_list: *state.as_ref().stake_system.stake_list_address(),
+        stake_account,
+        stake_deposit_authority: state.stake_deposit_authority(),
+        stake_withdraw_authority: state.stake_withdraw_authority(),
+        split_stake_account,
+        split_stake_rent_payer,
+        clock: clock::ID,
+        rent: rent::ID,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        stake_program: stake::program::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::WithdrawStakeAccount {
+        stake_index,
+        validator_index,
+        msol_amount,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn deposit_activating_stake_account(
+    state: &impl Located<State>,
+    stake_account: Pubkey,
+    stake_authority: Pubkey,
+    mint_to: Pubkey,
+    validator_index: u32,
+    validator_vote: Pubkey,
+    rent_payer: Pubkey,
+    custodian: Option<Pubkey>,
+) -> Instruction {
+    let accounts = accounts::DepositActivatingStakeAccount {
+        deposit: accounts::DepositStakeAccount {
+            state: state.key(),
+            validator_list: *state.as_ref().validator_system.validator_list_address(),
+            stake_list: *state.as_ref().stake_system.stake_list_address(),
+            stake_account,
+            stake_authority,
+            custodian: custodian.unwrap_or(stake_authority),
+            duplication_flag: ValidatorRecord::find_duplication_flag(&state.key(), &validator_vote)
+                .0,
+            blocked_flag: ValidatorSystem::find_blocked_flag(&state.key(), &validator_vote).0,
+            validator_vote,
+            rent_payer,
+            msol_mint: state.as_ref().msol_mint,
+            mint_to,
+            msol_mint_authority: state.msol_mint_authority(),
+            reserve_pda: state.reserve_address(),
+            clock: clock::id(),
+            rent: rent::id(),
+            stake_history: stake_history::ID,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            stake_program: stake::program::ID,
+            epoch_rewards: EpochRewards::find_address(&state.key()).0,
+        },
+        pending_deposit: PendingStakeDepositData::find_address(&state.key(), &stake_account).0,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::DepositActivatingStakeAccount { validator_index };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn finish_stake_deposit(
+    state: &impl Located<State>,
+    stake_account: Pubkey,
+    rent_payer: Pubkey,
+    mint_to: Pubkey,
+    validator_index: u32,
+) -> Instruction {
+    let accounts = accounts::FinishStakeDeposit {
+        state: state.key(),
+        validator_list: *state.as_ref().validator_system.validator_list_address(),
+        stake_list: *state.as_ref().stake_system.stake_list_address(),
+        stake_account,
+        pending_deposit: PendingStakeDepositData::find_address(&state.key(), &stake_account).0,
+        rent_payer,
+        msol_mint: state.as_ref().msol_mint,
+        mint_to,
+        msol_mint_authority: state.msol_mint_authority(),
+        clock: clock::ID,
+        token_program: token::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::FinishStakeDeposit { validator_index };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+/// deposit routed by the referral partner
+pub fn deposit_with_referral(
+    state: &impl Located<State>,
+    transfer_from: Pubkey,
+    mint_to: Pubkey,
+    lamports: u64,
+    min_msol_out: u64,
+    referral_state: Pubkey,
+) -> Instruction {
+    let mut instruction =
+        deposit_with_min_out(state, transfer_from, mint_to, lamports, min_msol_out);
+    instruction
+        .accounts
+        .push(AccountMeta::new(referral_state, false));
+    instruction
+}
+
+/// liquid unstake routed by the referral partner,
+/// referral_msol_account must be the msol_token_account of the referral
+pub fn liquid_unstake_with_referral(
+    state: &impl Located<State>,
+    get_msol_from: Pubkey,
+    get_msol_from_authority: Pubkey,
+    transfer_sol_to: Pubkey,
+    msol_amount: u64,
+    min_lamports_out: u64,
+    referral_state: Pubkey,
+    referral_msol_account: Pubkey,
+) -> Instruction {
+    let mut instruction = liquid_unstake_with_min_out(
+        state,
+        get_msol_from,
+        get_msol_from_authority,
+        transfer_sol_to,
+        msol_amount,
+        min_lamports_out,
+    );
+    instruction
+        .accounts
+        .push(AccountMeta::new(referral_state, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new(referral_msol_account, false));
+    instruction
+}
+
+pub fn create_referral(
+    state: &impl Located<State>,
+    referral_state: Pubkey,
+    partner_account: Pubkey,
+    msol_token_account: Pubkey,
+    referral_code: String,
+    treasury_cut_share: Fee,
+) -> Instruction {
+    let accounts = accounts::CreateReferral {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+        referral_state,
+        partner_account,
+        msol_token_account,
+        rent: rent::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::CreateReferral {
+        referral_code,
+        treasury_cut_share,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn update_referral(
+    state: &impl Located<State>,
+    referral_state: Pubkey,
+    pause: Option<bool>,
+    treasury_cut_share: Option<Fee>,
+) -> Instruction {
+    let accounts = accounts::UpdateReferral {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+        referral_state,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::UpdateReferral {
+        pause,
+        treasury_cut_share,
+    };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+/// list is the current stake list or validator list address
+pub fn grow_list(
+    state: &impl Located<State>,
+    list: Pubkey,
+    new_list: Pubkey,
+    rent_receiver: Pubkey,
+    max_copy_count: u32,
+) -> Instruction {
+    let accounts = accounts::GrowList {
+        state: state.key(),
+        admin_authority: state.as_ref().admin_authority,
+        list,
+        new_list,
+        rent_receiver,
+        rent: rent::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::GrowList { max_copy_count };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn block_validator(
+    state: &impl Located<State>,
+    validator_vote: Pubkey,
+    rent_payer: Pubkey,
+) -> Instruction {
+    let accounts = accounts::BlockValidator {
+        state: state.key(),
+        manager_authority: state.as_ref().validator_system.manager_authority,
+        blocked_flag: ValidatorSystem::find_blocked_flag(&state.key(), &validator_vote).0,
+        rent_payer,
+        rent: rent::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::BlockValidator { validator_vote };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn unblock_validator(
+    state: &impl Located<State>,
+    validator_vote: Pubkey,
+    rent_receiver: Pubkey,
+) -> Instruction {
+    let accounts = accounts::UnblockValidator {
+        state: state.key(),
+        manager_authority: state.as_ref().validator_system.manager_authority,
+        blocked_flag: ValidatorSystem::find_blocked_flag(&state.key(), &validator_vote).0,
+        rent_receiver,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::UnblockValidator { validator_vote };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn reclaim_rent(
+    state: &impl Located<State>,
+    orphaned_account: Pubkey,
+    validator_vote: Option<Pubkey>,
+) -> Instruction {
+    let accounts = accounts::ReclaimRent {
+        state: state.key(),
+        validator_list: *state.as_ref().validator_system.validator_list_address(),
+        orphaned_account,
+        operational_sol_account: state.as_ref().operational_sol_account,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::ReclaimRent { validator_vote };
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+pub fn create_epoch_rewards(state: &impl Located<State>, rent_payer: Pubkey) -> Instruction {
+    let accounts = accounts::CreateEpochRewards {
+        state: state.key(),
+        epoch_rewards: EpochRewards::find_address(&state.key()).0,
+        rent_payer,
+        rent: rent::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    let data = instruction::CreateEpochRewards {};
+
+    Instruction {
+        program_id: marinade_finance::ID,
+        accounts,
+        data: data.data(),
+    }
+}
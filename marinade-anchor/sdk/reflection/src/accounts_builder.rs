@@ -57,6 +57,7 @@ pub struct ValidatorBuilder {
     pub current_stakes: u32,
     pub current_active_balance: u64,
     pub current_delegated_delta: u64,
+    pub current_slashed: u64,
     pub current_extra_balance: u64,
 }
 #[derive(Debug, Clone)]
@@ -137,6 +138,7 @@ impl<'a> AccountsBuilder<'a> {
             current_stakes: 0,
             current_active_balance: 0,
             current_delegated_delta: 0,
+            current_slashed: 0,
             current_extra_balance: 0,
         });
         Ok(())
@@ -189,12 +191,15 @@ impl<'a> AccountsBuilder<'a> {
                     validator_builder.current_stakes += 1;
                     validator_builder.current_active_balance +=
                         stake.last_update_delegated_lamports;
-                    validator_builder.current_delegated_delta = validator_builder
-                        .current_delegated_delta
-                        .checked_add(stake.stake)
-                        .expect("delegate_delta overflow")
-                        .checked_sub(stake.last_update_delegated_lamports)
-                        .expect("negative delegate_delta is not supported");
+                    if stake.stake >= stake.last_update_delegated_lamports {
+                        validator_builder.current_delegated_delta = validator_builder
+                            .current_delegated_delta
+                            .checked_add(stake.stake - stake.last_update_delegated_lamports)
+                            .expect("delegate_delta overflow");
+                    } else {
+                        validator_builder.current_slashed +=
+                            stake.last_update_delegated_lamports - stake.stake;
+                    }
                     validator_builder.current_extra_balance += stake.extra_balance;
 
                     self.stakes.push(stake);
@@ -283,6 +288,7 @@ impl<'a> AccountsBuilder<'a> {
                     current_stakes: 0,
                     current_active_balance: 0,
                     current_delegated_delta: 0,
+                    current_slashed: 0,
                     current_extra_balance: 0,
                 });
                 self.validators.last_mut().unwrap()
@@ -320,15 +326,25 @@ impl<'a> AccountsBuilder<'a> {
                     extra_balance_left
                 };
 
+                // the last stake takes all the slashing left
+                let slashed = if i + 1 < validator_reflection.stake_count {
+                    0
+                } else {
+                    validator_reflection.total_slashed - validator_builder.current_slashed
+                };
+
                 validator_builder.current_stakes += 1;
                 validator_builder.current_active_balance += delegated;
                 validator_builder.current_delegated_delta += delegated_delta;
+                validator_builder.current_slashed += slashed;
                 validator_builder.current_extra_balance += extra_balance;
 
                 self.stakes.push(StakeBuilder {
                     address: random_pubkey(rng),
                     voter_pubkey: *validator_key,
-                    stake: delegated + delegated_delta,
+                    stake: (delegated + delegated_delta)
+                        .checked_sub(slashed)
+                        .expect("Too high slashing for the stake"),
                     is_active: true,
                     last_update_delegated_lamports: delegated,
                     last_update_epoch: 0, // TODO
@@ -524,6 +540,7 @@ impl<'a> AccountsBuilder<'a> {
                 last_update_delegated_lamports: stake.last_update_delegated_lamports,
                 last_update_epoch: stake.last_update_epoch,
                 is_emergency_unstaking: 0,
+                reserved: [0; 7],
             };
             state
                 .stake_system
@@ -596,6 +613,9 @@ impl<'a> AccountsBuilder<'a> {
             if validator.current_delegated_delta != validator_reflection.total_delegated_delta {
                 bail!("Wrong validator {} delegated delta", validator.vote_address)
             }
+            if validator.current_slashed != validator_reflection.total_slashed {
+                bail!("Wrong validator {} slashed", validator.vote_address)
+            }
             if validator.current_extra_balance != validator_reflection.total_extra_balance {
                 bail!("Wrong validator {} extra balance", validator.vote_address)
             }
@@ -607,6 +627,10 @@ impl<'a> AccountsBuilder<'a> {
                 score: validator_reflection.score,
                 last_stake_delta_epoch: validator_reflection.last_stake_delta_epoch,
                 duplication_flag_bump_seed,
+                directed_stake_lamports: validator_reflection.directed_stake_lamports,
+                min_stake: validator_reflection.min_stake,
+                max_stake: validator_reflection.max_stake,
+                reserved: [0; 3],
             };
             // Save validator to list
             state.validator_system.set(
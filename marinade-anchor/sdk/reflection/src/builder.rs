@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, iter::FromIterator, ops::Range};
 
 use anyhow::{anyhow, bail};
-use marinade_finance_offchain_sdk::marinade_finance::{Fee, MAX_REWARD_FEE};
+use marinade_finance_offchain_sdk::marinade_finance::{liq_pool::FeeCurve, Fee, MAX_REWARD_FEE};
 use marinade_finance_offchain_sdk::solana_sdk::program_pack::Pack;
 use marinade_finance_offchain_sdk::solana_sdk::{
     clock::Epoch, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, rent::Rent,
@@ -51,6 +51,8 @@ pub struct Builder {
     pub lp_min_fee: OnceCell<Fee>,
     pub lp_treasury_cut: OnceCell<Fee>,
     pub lent_from_liq_pool: OnceCell<u64>,
+    pub liq_pool_tickets_receivable: OnceCell<u64>,
+    pub lp_fee_curve: OnceCell<FeeCurve>,
 
     pub available_reserve_balance: OnceCell<u64>, // reserve_pda.lamports() - self.rent_exempt_for_token_acc. Virtual value (real may be > because of transfers into reserve). Use Update* to align
     pub actual_reserve_balance: OnceCell<u64>,
@@ -65,6 +67,13 @@ pub struct Builder {
     pub min_withdraw: OnceCell<u64>,
     pub staking_sol_cap: OnceCell<u64>,
     pub liquidity_sol_cap: OnceCell<u64>,
+    pub paused_operations: OnceCell<u32>,
+    pub pending_admin_authority: OnceCell<Pubkey>,
+    pub pending_validator_manager_authority: OnceCell<Pubkey>,
+    pub pending_operational_sol_account: OnceCell<Pubkey>,
+    pub params_change_delay_epochs: OnceCell<u64>,
+    pub withdraw_stake_account_fee: OnceCell<Fee>,
+    pub max_commission: OnceCell<u8>,
 }
 
 pub struct RandomBuildParams {
@@ -406,6 +415,51 @@ impl Builder {
             .expect("double liquidity_sol_cap set calls");
     }
 
+    pub fn set_paused_operations(&mut self, paused_operations: u32) {
+        self.paused_operations
+            .set(paused_operations)
+            .expect("double paused_operations set calls");
+    }
+
+    pub fn set_pending_admin_authority(&mut self, pending_admin_authority: Pubkey) {
+        self.pending_admin_authority
+            .set(pending_admin_authority)
+            .expect("double pending_admin_authority set calls");
+    }
+
+    pub fn set_pending_validator_manager_authority(
+        &mut self,
+        pending_validator_manager_authority: Pubkey,
+    ) {
+        self.pending_validator_manager_authority
+            .set(pending_validator_manager_authority)
+            .expect("double pending_validator_manager_authority set calls");
+    }
+
+    pub fn set_pending_operational_sol_account(&mut self, pending_operational_sol_account: Pubkey) {
+        self.pending_operational_sol_account
+            .set(pending_operational_sol_account)
+            .expect("double pending_operational_sol_account set calls");
+    }
+
+    pub fn set_params_change_delay_epochs(&mut self, params_change_delay_epochs: u64) {
+        self.params_change_delay_epochs
+            .set(params_change_delay_epochs)
+            .expect("double params_change_delay_epochs set calls");
+    }
+
+    pub fn set_withdraw_stake_account_fee(&mut self, withdraw_stake_account_fee: Fee) {
+        self.withdraw_stake_account_fee
+            .set(withdraw_stake_account_fee)
+            .expect("double withdraw_stake_account_fee set calls");
+    }
+
+    pub fn set_max_commission(&mut self, max_commission: u8) {
+        self.max_commission
+            .set(max_commission)
+            .expect("double max_commission set calls");
+    }
+
     pub fn add_empty_validator(&mut self, rng: &mut impl RngCore) -> anyhow::Result<Pubkey> {
         let vote = random_pubkey(rng);
         self.add_validator(
@@ -415,7 +469,11 @@ impl Builder {
                 stake_count: 0,
                 score: Uniform::from(1..200).sample(rng),
                 last_stake_delta_epoch: Epoch::MAX,
+                directed_stake_lamports: 0,
+                min_stake: 0,
+                max_stake: 0,
                 total_delegated_delta: 0,
+                total_slashed: 0,
                 total_extra_balance: 0,
             },
         )?;
@@ -443,7 +501,11 @@ impl Builder {
                 stake_count: Uniform::from(stake_count).sample(rng),
                 score: Uniform::from(1..200).sample(rng),
                 last_stake_delta_epoch: Epoch::MAX,
+                directed_stake_lamports: 0,
+                min_stake: 0,
+                max_stake: 0,
                 total_delegated_delta: Uniform::from(total_delegated_delta).sample(rng),
+                total_slashed: 0,
                 total_extra_balance: Uniform::from(total_extra_balance).sample(rng),
             },
         )?;
@@ -675,6 +737,8 @@ impl Builder {
             lp_min_fee,
             lp_treasury_cut,
             lent_from_liq_pool,
+            liq_pool_tickets_receivable,
+            lp_fee_curve,
             available_reserve_balance,
             actual_reserve_balance,
             msol_supply,
@@ -687,6 +751,13 @@ impl Builder {
             min_withdraw,
             staking_sol_cap,
             liquidity_sol_cap,
+            paused_operations,
+            pending_admin_authority,
+            pending_validator_manager_authority,
+            pending_operational_sol_account,
+            params_change_delay_epochs,
+            withdraw_stake_account_fee,
+            max_commission,
         } = self;
         let rent_exempt_for_token_acc = rent.minimum_balance(spl_token::state::Account::LEN);
         let lp_supply = lp_supply.into_inner().expect("lp_supply msut be set");
@@ -744,6 +815,11 @@ impl Builder {
                 lp_supply,
                 actual_lp_supply: actual_lp_supply.into_inner().unwrap_or(lp_supply),
                 lent_from_sol_leg: lent_from_liq_pool.into_inner().unwrap_or(0),
+                tickets_receivable: liq_pool_tickets_receivable.into_inner().unwrap_or(0),
+                fee_curve: lp_fee_curve.into_inner().unwrap_or_default(),
+                epoch_outflow_cap: 0, // Unlimited
+                epoch_outflow: 0,
+                outflow_epoch: 0,
             },
             available_reserve_balance,
             actual_reserve_balance: actual_reserve_balance
@@ -763,6 +839,17 @@ impl Builder {
             min_withdraw: min_withdraw.into_inner().expect("min_withdraw must be set"),
             staking_sol_cap: staking_sol_cap.into_inner().unwrap_or(std::u64::MAX),
             liquidity_sol_cap: liquidity_sol_cap.into_inner().unwrap_or(std::u64::MAX),
+            paused_operations: paused_operations.into_inner().unwrap_or(0),
+            pending_admin_authority: pending_admin_authority.into_inner().unwrap_or_default(),
+            pending_validator_manager_authority: pending_validator_manager_authority
+                .into_inner()
+                .unwrap_or_default(),
+            pending_operational_sol_account: pending_operational_sol_account
+                .into_inner()
+                .unwrap_or_default(),
+            params_change_delay_epochs: params_change_delay_epochs.into_inner().unwrap_or(0),
+            withdraw_stake_account_fee: withdraw_stake_account_fee.into_inner().unwrap_or_default(),
+            max_commission: max_commission.into_inner().unwrap_or(0), // No cap
         }
     }
 }
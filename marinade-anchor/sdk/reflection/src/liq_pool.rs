@@ -1,5 +1,5 @@
 use crate::FeeDef;
-use marinade_finance_offchain_sdk::marinade_finance::Fee;
+use marinade_finance_offchain_sdk::marinade_finance::{liq_pool::FeeCurve, Fee};
 use marinade_finance_offchain_sdk::solana_sdk::pubkey::Pubkey;
 use serde::Serialize;
 
@@ -26,4 +26,10 @@ pub struct LiqPool {
     pub lp_supply: u64, // virtual lp token supply. May be > real supply because of burning tokens. Use UpdateLiqPool to align it with real value
     pub actual_lp_supply: u64,
     pub lent_from_sol_leg: u64,
+    pub tickets_receivable: u64,
+    #[serde(skip)]
+    pub fee_curve: FeeCurve,
+    pub epoch_outflow_cap: u64,
+    pub epoch_outflow: u64,
+    pub outflow_epoch: u64,
 }
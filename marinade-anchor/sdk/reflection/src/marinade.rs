@@ -30,8 +30,13 @@ pub struct Validator {
     pub stake_count: u32,
     pub score: u32,
     pub last_stake_delta_epoch: u64,
+    pub directed_stake_lamports: u64,
+    pub min_stake: u64,
+    pub max_stake: u64,
     // difference between actual total delegated and active_balance recorded field
     pub total_delegated_delta: u64,
+    // total lost by stakes delegated less than their last update
+    pub total_slashed: u64,
     // total not delegates lamports on stakes
     pub total_extra_balance: u64,
 }
@@ -89,6 +94,14 @@ pub struct Marinade {
     pub min_withdraw: u64,
     pub staking_sol_cap: u64,
     pub liquidity_sol_cap: u64,
+    pub paused_operations: u32,
+    pub pending_admin_authority: Pubkey,
+    pub pending_validator_manager_authority: Pubkey,
+    pub pending_operational_sol_account: Pubkey,
+    pub params_change_delay_epochs: u64,
+    #[serde(with = "FeeDef")]
+    pub withdraw_stake_account_fee: Fee,
+    pub max_commission: u8,
 }
 
 impl Marinade {
@@ -118,6 +131,20 @@ impl Marinade {
             .sum()
     }
 
+    pub fn total_directed_stake(&self) -> u64 {
+        self.validators
+            .values()
+            .map(|validator| validator.directed_stake_lamports)
+            .sum()
+    }
+
+    pub fn total_min_stake(&self) -> u64 {
+        self.validators
+            .values()
+            .map(|validator| validator.min_stake)
+            .sum()
+    }
+
     pub fn circulating_ticket_balance(&self) -> u64 {
         self.claim_tickets
             .iter()
@@ -161,7 +188,11 @@ impl Marinade {
                         stake_count: 0, // Will be counted later
                         score: validator.score,
                         last_stake_delta_epoch: validator.last_stake_delta_epoch,
+                        directed_stake_lamports: validator.directed_stake_lamports,
+                        min_stake: validator.min_stake,
+                        max_stake: validator.max_stake,
                         total_delegated_delta: 0,
+                        total_slashed: 0,
                         total_extra_balance: 0,
                     },
                 )
@@ -227,10 +258,13 @@ impl Marinade {
                     })?;
 
                 stake_validator.stake_count += 1;
-                stake_validator.total_delegated_delta += stake_delegation
-                    .stake
-                    .checked_sub(stake_record.last_update_delegated_lamports)
-                    .expect("Slashing is not supported");
+                if stake_delegation.stake >= stake_record.last_update_delegated_lamports {
+                    stake_validator.total_delegated_delta +=
+                        stake_delegation.stake - stake_record.last_update_delegated_lamports;
+                } else {
+                    stake_validator.total_slashed +=
+                        stake_record.last_update_delegated_lamports - stake_delegation.stake;
+                }
                 stake_validator.total_extra_balance += stake_account.lamports
                     - stake_delegation.stake
                     - stake_state.meta().unwrap().rent_exempt_reserve;
@@ -320,6 +354,11 @@ impl Marinade {
                 lp_supply: state.liq_pool.lp_supply,
                 actual_lp_supply,
                 lent_from_sol_leg: state.liq_pool.lent_from_sol_leg,
+                tickets_receivable: state.liq_pool_tickets_receivable,
+                fee_curve: state.liq_pool_fee_curve,
+                epoch_outflow_cap: state.liq_pool_epoch_outflow_cap,
+                epoch_outflow: state.liq_pool_epoch_outflow,
+                outflow_epoch: state.liq_pool_outflow_epoch,
             },
             available_reserve_balance: state.available_reserve_balance,
             actual_reserve_balance,
@@ -333,6 +372,13 @@ impl Marinade {
             min_withdraw: state.min_withdraw,
             staking_sol_cap: state.staking_sol_cap,
             liquidity_sol_cap: state.liq_pool.liquidity_sol_cap,
+            paused_operations: state.paused_operations,
+            pending_admin_authority: state.pending_admin_authority,
+            pending_validator_manager_authority: state.pending_validator_manager_authority,
+            pending_operational_sol_account: state.pending_operational_sol_account,
+            params_change_delay_epochs: state.params_change_delay_epochs,
+            withdraw_stake_account_fee: state.withdraw_stake_account_fee,
+            max_commission: state.max_commission,
         })
     }
 
@@ -349,7 +395,7 @@ impl Marinade {
         let stake_system = StakeSystem {
             stake_list: List {
                 account: stake_list_account,
-                item_size: StakeRecord::default().try_to_vec().unwrap().len() as u32
+                item_size: std::mem::size_of::<StakeRecord>() as u32
                     + additional_stake_record_space,
                 count: self.stake_count(),
                 new_account: Pubkey::default(),
@@ -368,7 +414,7 @@ impl Marinade {
         let validator_system = ValidatorSystem {
             validator_list: List {
                 account: validator_list_account,
-                item_size: ValidatorRecord::default().try_to_vec().unwrap().len() as u32
+                item_size: std::mem::size_of::<ValidatorRecord>() as u32
                     + additional_validator_record_space,
                 count: self.validator_count(),
                 new_account: Pubkey::default(),
@@ -419,6 +465,20 @@ impl Marinade {
             min_withdraw: self.min_withdraw,
             staking_sol_cap: self.staking_sol_cap,
             emergency_cooling_down: 0,
+            paused_operations: self.paused_operations,
+            pending_admin_authority: self.pending_admin_authority,
+            pending_validator_manager_authority: self.pending_validator_manager_authority,
+            pending_operational_sol_account: self.pending_operational_sol_account,
+            params_change_delay_epochs: self.params_change_delay_epochs,
+            withdraw_stake_account_fee: self.withdraw_stake_account_fee,
+            total_directed_stake: self.total_directed_stake(),
+            max_commission: self.max_commission,
+            total_min_stake: self.total_min_stake(),
+            liq_pool_tickets_receivable: self.liq_pool.tickets_receivable,
+            liq_pool_fee_curve: self.liq_pool.fee_curve,
+            liq_pool_epoch_outflow_cap: self.liq_pool.epoch_outflow_cap,
+            liq_pool_epoch_outflow: self.liq_pool.epoch_outflow,
+            liq_pool_outflow_epoch: self.liq_pool.outflow_epoch,
         }
     }
 